@@ -0,0 +1,189 @@
+//! Example demonstrating derived conversions between Rust types and plugin values.
+//!
+//! This example shows how to:
+//! - Derive `FromValue` and `IntoValue` for structs and enums
+//! - Rename, default, skip and flatten fields
+//! - Choose between external, internal, adjacent and untagged enum representations
+//! - Use derived types as host function arguments and return values
+//! - Rely on the declaration order of fields, and compare and hash values regardless of it
+//!
+//! Run with: `cargo run --example derive_values --features derive`
+
+use std::collections::HashSet;
+
+use tosic_plugin_core::*;
+
+/// Connection settings shared by several requests.
+#[derive(Debug, FromValue, IntoValue)]
+struct Endpoint {
+    host: String,
+    port: i64,
+}
+
+/// Request sent by a plugin to the host.
+#[derive(Debug, FromValue, IntoValue)]
+#[value(rename_all = "camelCase")]
+struct FetchRequest {
+    request_id: String,
+    #[value(flatten)]
+    endpoint: Endpoint,
+    #[value(default = "default_timeout")]
+    timeout_ms: i64,
+    #[value(rename = "type")]
+    method: Method,
+    #[value(skip)]
+    attempts: i64,
+}
+
+fn default_timeout() -> i64 {
+    5_000
+}
+
+/// HTTP method, externally tagged as its name.
+#[derive(Debug, FromValue, IntoValue)]
+#[value(rename_all = "UPPERCASE")]
+enum Method {
+    Get,
+    Post(String),
+}
+
+/// Response returned to the plugin, adjacently tagged.
+#[derive(Debug, FromValue, IntoValue)]
+#[value(tag = "status", content = "body", rename_all = "snake_case")]
+enum FetchResponse {
+    Ok(String),
+    NotFound,
+    Redirect { location: String },
+}
+
+/// Event reported by a plugin, internally tagged.
+#[derive(Debug, FromValue, IntoValue)]
+#[value(tag = "event")]
+enum Event {
+    Started,
+    Progress { done: i64, total: i64 },
+}
+
+/// Identifier accepted either as a number or as a name.
+#[derive(Debug, FromValue, IntoValue)]
+#[value(untagged)]
+enum Id {
+    Number(i64),
+    Name(String),
+}
+
+fn main() -> PluginResult<()> {
+    println!("=== Derived Values Example ===\n");
+
+    println!("1. Converting a request into a value:");
+    let request = FetchRequest {
+        request_id: "req-1".to_string(),
+        endpoint: Endpoint {
+            host: "example.com".to_string(),
+            port: 443,
+        },
+        timeout_ms: 1_000,
+        method: Method::Post("payload".to_string()),
+        attempts: 2,
+    };
+    let value = request.into_value();
+    println!("   {:?}", value);
+
+    println!("\n2. Converting the value back, filling in skipped fields:");
+    let parsed = FetchRequest::from_value(&value)?;
+    println!("   {:?}", parsed);
+    println!("   Attempts reset to {}", parsed.attempts);
+
+    println!("\n3. Filling in a missing field with its default:");
+    let mut object = value.as_object().cloned().unwrap_or_default();
+    object.shift_remove("timeoutMs");
+    println!("   {:?}", FetchRequest::from_value(&Value::Object(object))?);
+
+    println!("\n4. Converting enum representations:");
+    println!("   Method:   {:?}", Method::Get.into_value());
+    println!(
+        "   Response: {:?}",
+        FetchResponse::Ok("done".to_string()).into_value()
+    );
+    println!("   Response: {:?}", FetchResponse::NotFound.into_value());
+    println!(
+        "   Response: {:?}",
+        FetchResponse::Redirect {
+            location: "/next".to_string()
+        }
+        .into_value()
+    );
+    println!("   Event:    {:?}", Event::Started.into_value());
+    println!(
+        "   Event:    {:?}",
+        Event::Progress { done: 1, total: 4 }.into_value()
+    );
+    println!(
+        "   Ids:      {:?}, {:?}",
+        Id::from_value(&Value::Int(7))?,
+        Id::from_value(&Value::from("seven"))?
+    );
+
+    println!("\n5. Calling a host function taking and returning derived types:");
+    let mut context = HostContext::new();
+    context.register("fetch", |request: FetchRequest| match request.method {
+        Method::Get => FetchResponse::Redirect {
+            location: format!(
+                "https://{}:{}/",
+                request.endpoint.host, request.endpoint.port
+            ),
+        },
+        Method::Post(body) => FetchResponse::Ok(format!("posted {} bytes", body.len())),
+    });
+    let result = context.call_function("fetch", &[value])?;
+    println!("   {:?}", FetchResponse::from_value(&result)?);
+
+    println!("\n6. Comparing and hashing objects regardless of key order:");
+    let endpoint = Endpoint {
+        host: "example.com".to_string(),
+        port: 443,
+    }
+    .into_value();
+    let keys: Vec<&String> = endpoint
+        .as_object()
+        .map(|object| object.keys().collect())
+        .unwrap_or_default();
+    println!("   Keys in declaration order: {:?}", keys);
+    let mut reordered = endpoint.as_object().cloned().unwrap_or_default();
+    reordered.reverse();
+    let reordered = Value::Object(reordered);
+    println!("   Equal when reordered: {}", endpoint == reordered);
+    println!(
+        "   Same canonical encoding: {}",
+        endpoint.encode_canonical() == reordered.encode_canonical()
+    );
+    let unique: HashSet<Value> = [endpoint, reordered].into_iter().collect();
+    println!("   Distinct values in a set: {}", unique.len());
+
+    println!("\n=== Testing Error Cases ===\n");
+
+    println!("7. Converting an object with a field of the wrong type:");
+    let mut object = result.as_object().cloned().unwrap_or_default();
+    object.insert("body".to_string(), Value::Int(1));
+    match FetchResponse::from_value(&Value::Object(object)) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n8. Converting an object missing a required field:");
+    match Endpoint::from_value(&Value::Object(
+        [("host".to_string(), Value::from("example.com"))].into(),
+    )) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n9. Converting an unknown variant:");
+    match Method::from_value(&Value::from("DELETE")) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n=== Example completed successfully! ===");
+    Ok(())
+}
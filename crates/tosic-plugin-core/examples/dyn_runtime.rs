@@ -0,0 +1,157 @@
+//! Example demonstrating type-erased runtimes chosen at run time.
+//!
+//! This example shows how to:
+//! - Store runtimes with different plugin types as `Box<dyn DynRuntime>`
+//! - Pick a runtime by name, as a host would from its configuration
+//! - Hold the loaded plugins uniformly as `DynPlugin`s and downcast them
+//! - Manage plugins through a type-erased runtime
+//!
+//! Run with: `cargo run --example dyn_runtime`
+
+use std::collections::HashMap;
+use tosic_plugin_core::*;
+
+/// Plugin of the uppercasing runtime.
+struct UpperPlugin {
+    prefix: String,
+}
+
+impl Plugin for UpperPlugin {
+    fn name(&self) -> Option<&str> {
+        Some("upper")
+    }
+}
+
+/// Mock runtime whose plugins uppercase their input.
+struct UpperRuntime;
+
+impl Runtime for UpperRuntime {
+    type Plugin = UpperPlugin;
+
+    fn load(&self, bytes: &[u8], _context: &HostContext) -> PluginResult<Self::Plugin> {
+        Ok(UpperPlugin {
+            prefix: String::from_utf8_lossy(bytes).into_owned(),
+        })
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        match function_name {
+            "transform" => {
+                let input = String::from_value(args.first().unwrap_or(&Value::Null))?;
+                Ok(Value::String(format!(
+                    "{}{}",
+                    plugin.prefix,
+                    input.to_uppercase()
+                )))
+            }
+            _ => Err(PluginError::FunctionNotFound(function_name.to_string())),
+        }
+    }
+}
+
+/// Plugin of the reversing runtime.
+struct ReversePlugin;
+
+impl Plugin for ReversePlugin {
+    fn name(&self) -> Option<&str> {
+        Some("reverse")
+    }
+}
+
+/// Mock runtime whose plugins reverse their input.
+struct ReverseRuntime;
+
+impl Runtime for ReverseRuntime {
+    type Plugin = ReversePlugin;
+
+    fn load(&self, _bytes: &[u8], _context: &HostContext) -> PluginResult<Self::Plugin> {
+        Ok(ReversePlugin)
+    }
+
+    fn call(
+        &self,
+        _plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        match function_name {
+            "transform" => {
+                let input = String::from_value(args.first().unwrap_or(&Value::Null))?;
+                Ok(Value::String(input.chars().rev().collect()))
+            }
+            _ => Err(PluginError::FunctionNotFound(function_name.to_string())),
+        }
+    }
+}
+
+fn main() -> PluginResult<()> {
+    println!("=== Dynamic Runtime Example ===\n");
+
+    let mut runtimes: HashMap<&str, Box<dyn DynRuntime>> = HashMap::new();
+    runtimes.insert("upper", Box::new(UpperRuntime));
+    runtimes.insert("reverse", Box::new(ReverseRuntime));
+
+    // Each entry names the runtime to use and the plugin code, as a configuration file would
+    let config = [("upper", "up: "), ("reverse", "")];
+    let context = HostContext::new();
+
+    println!("1. Loading one plugin per configured runtime:");
+    let mut plugins = Vec::new();
+    for (runtime_name, code) in config {
+        let plugin = runtimes[runtime_name].load(code.as_bytes(), &context)?;
+        println!(
+            "   Loaded {:?} with the '{}' runtime",
+            plugin.name(),
+            runtime_name
+        );
+        plugins.push((runtime_name, plugin));
+    }
+
+    println!("\n2. Calling transform(\"plugin\") on every plugin:");
+    for (runtime_name, plugin) in &plugins {
+        let result = runtimes[runtime_name].call(plugin, "transform", &[Value::from("plugin")])?;
+        println!("   {:?}: {:?}", plugin.name(), result);
+    }
+
+    println!("\n3. Downcasting to the concrete plugin types:");
+    for (_, plugin) in &plugins {
+        match plugin.downcast_ref::<UpperPlugin>() {
+            Some(upper) => println!(
+                "   {:?} is an UpperPlugin with prefix {:?}",
+                plugin.name(),
+                upper.prefix
+            ),
+            None => println!("   {:?} is not an UpperPlugin", plugin.name()),
+        }
+    }
+
+    println!("\n4. Managing plugins through a runtime picked at run time:");
+    let runtime: Box<dyn DynRuntime> = Box::new(ReverseRuntime);
+    let mut manager = DefaultPluginManager::new(runtime, context);
+    manager.load("palindromes", b"")?;
+    let result = manager.call("palindromes", "transform", &[Value::from("stressed")])?;
+    println!("   Result: {:?}", result);
+
+    println!("\n=== Testing Error Cases ===\n");
+
+    println!("5. Calling a plugin through a runtime of a different type:");
+    let (_, upper_plugin) = &plugins[0];
+    match runtimes["reverse"].call(upper_plugin, "transform", &[Value::from("plugin")]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n6. Calling non-existent function:");
+    match runtimes["upper"].call(upper_plugin, "nonexistent", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n=== Example completed successfully! ===");
+    Ok(())
+}
@@ -0,0 +1,215 @@
+//! Example demonstrating host functions declared with the `#[host_function]` attribute.
+//!
+//! This example shows how to:
+//! - Declare sync and async host functions with `#[host_function]`
+//! - Register them along with their metadata
+//! - Query the metadata of registered functions, e.g. to generate plugin-side stubs
+//! - Query the value-level signatures recorded for every registered function
+//! - Add optional trailing parameters without breaking existing callers
+//! - Take any number of arguments with `Rest<T>` and keyword arguments with `Kwargs<T>`
+//!
+//! Run with: `cargo run --example host_functions --features derive,async`
+
+use tosic_plugin_core::*;
+
+/// Adds two numbers.
+#[host_function]
+fn add(a: i64, b: i64) -> i64 {
+    a + b
+}
+
+/// Greets the calling plugin.
+///
+/// The plugin's name is taken from the caller, so plugins only pass the greeting.
+#[host_function(name = "greet")]
+fn greet_caller(caller: Caller, greeting: String) -> String {
+    format!("{}, {}!", greeting, caller.name().unwrap_or("anonymous"))
+}
+
+/// Writes a message to the host's log.
+///
+/// The level was added later; plugins omitting it still log at `info`.
+#[host_function]
+fn log(message: String, level: Option<String>) {
+    println!(
+        "   [host log] {}: {}",
+        level.as_deref().unwrap_or("info"),
+        message
+    );
+}
+
+/// Sums any number of integers.
+#[host_function]
+fn sum(Rest(numbers): Rest<i64>) -> i64 {
+    numbers.iter().sum()
+}
+
+/// Options of the `notify` host function, passed by plugins as an object.
+#[derive(FromValue)]
+struct NotifyOptions {
+    #[value(default)]
+    urgent: bool,
+    #[value(default)]
+    channel: Option<String>,
+}
+
+/// Sends a notification, configured through keyword arguments.
+#[host_function]
+fn notify(message: String, Kwargs(options): Kwargs<NotifyOptions>) -> String {
+    let channel = options.channel.as_deref().unwrap_or("default");
+    let marker = if options.urgent { "!" } else { "" };
+    format!("[{}] {}{}", channel, message, marker)
+}
+
+/// Converts the message to upper case.
+///
+/// Async host functions are registered with `register_async_with_metadata`.
+#[cfg(feature = "async")]
+#[host_function]
+async fn shout(message: String) -> String {
+    message.to_uppercase()
+}
+
+fn print_metadata(metadata: &HostFunctionMetadata) {
+    let params: Vec<String> = metadata
+        .params()
+        .iter()
+        .map(|param| format!("{}: {}", param.name(), param.ty()))
+        .collect();
+    println!(
+        "   fn {}({}) -> {}",
+        metadata.name(),
+        params.join(", "),
+        metadata.returns().unwrap_or("()")
+    );
+    if let Some(doc) = metadata.doc() {
+        for line in doc.lines() {
+            println!("      {}", format!("/// {}", line).trim_end());
+        }
+    }
+}
+
+fn main() -> PluginResult<()> {
+    println!("=== Host Function Attribute Example ===\n");
+
+    println!("1. Registering annotated host functions:");
+    let mut context = HostContext::new();
+    add::register(&mut context);
+    greet_caller::register(&mut context);
+    log::register(&mut context);
+    sum::register(&mut context);
+    notify::register(&mut context);
+    #[cfg(feature = "async")]
+    shout::register(&mut context);
+    println!(
+        "   Registered {} functions",
+        context.function_names().count()
+    );
+
+    println!("\n2. Calling them like any other host function:");
+    let result = context.call_function("add", &[Value::Int(2), Value::Int(3)])?;
+    println!("   add(2, 3) = {:?}", result);
+    let context = context.with_caller(Caller::new("example-plugin-id").with_name("example-plugin"));
+    let result = context.call_function("greet", &[Value::from("Hello")])?;
+    println!("   greet(\"Hello\") = {:?}", result);
+    context.call_function("log", &[Value::from("written through the host function")])?;
+    context.call_function(
+        "log",
+        &[Value::from("with an explicit level"), Value::from("warn")],
+    )?;
+    let numbers: Vec<Value> = (1..=5).map(Value::Int).collect();
+    println!(
+        "   sum(1, 2, 3, 4, 5) = {:?}",
+        context.call_function("sum", &numbers)?
+    );
+    println!("   sum() = {:?}", context.call_function("sum", &[])?);
+    let options = Value::Object(
+        [
+            ("urgent".to_string(), Value::Bool(true)),
+            ("channel".to_string(), Value::from("ops")),
+        ]
+        .into(),
+    );
+    let result = context.call_function("notify", &[Value::from("disk full"), options])?;
+    println!(
+        "   notify(\"disk full\", {{ urgent, channel }}) = {:?}",
+        result
+    );
+    let result = context.call_function("notify", &[Value::from("backup done")])?;
+    println!("   notify(\"backup done\") = {:?}", result);
+    #[cfg(feature = "async")]
+    {
+        let result = futures::executor::block_on(
+            context.call_function_async("shout", &[Value::from("quiet")]),
+        )?;
+        println!("   shout(\"quiet\") = {:?}", result);
+        let blocking = context.with_block_on(futures::executor::block_on);
+        let result = blocking.call_function("shout", &[Value::from("loud")])?;
+        println!("   shout(\"loud\") with a blocking executor = {:?}", result);
+    }
+
+    println!("\n3. Listing the metadata of registered functions:");
+    let mut names: Vec<&String> = context.function_names().collect();
+    names.sort();
+    for name in names {
+        if let Some(metadata) = context.metadata(name) {
+            print_metadata(metadata);
+        }
+        if let Some(signature) = context.signature(name) {
+            println!("      signature: {}", signature);
+        }
+    }
+
+    println!("\n4. Calling the annotated functions directly from Rust:");
+    println!("   add(20, 22) = {}", add(20, 22));
+
+    println!("\n=== Testing Error Cases ===\n");
+
+    println!("5. Looking up metadata of an unknown function:");
+    match context.metadata("unknown") {
+        Some(_) => println!("   Unexpected metadata!"),
+        None => println!("   No metadata registered for 'unknown'"),
+    }
+
+    println!("\n6. Calling add with an argument of the wrong type:");
+    match context.call_function("add", &[Value::Int(1), Value::from("two")]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n7. Calling add with too few arguments:");
+    match context.call_function("add", &[Value::Int(1)]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n8. Calling sum with an argument of the wrong type:");
+    match context.call_function("sum", &[Value::Int(1), Value::Int(2), Value::from("three")]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n9. Calling notify with keyword arguments that are not an object:");
+    match context.call_function("notify", &[Value::from("disk full"), Value::Bool(true)]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n10. Calling notify with too many arguments:");
+    match context.call_function("notify", &[Value::from("a"), Value::Null, Value::from("b")]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    #[cfg(feature = "async")]
+    {
+        println!("\n11. Calling the async shout synchronously without a blocking executor:");
+        match context.call_function("shout", &[Value::from("quiet")]) {
+            Ok(_) => println!("   Unexpected success!"),
+            Err(e) => println!("   Expected error: {}", e),
+        }
+    }
+
+    println!("\n=== Example completed successfully! ===");
+    Ok(())
+}
@@ -0,0 +1,150 @@
+//! Example demonstrating plugin lifecycle management with the default plugin manager.
+//!
+//! This example shows how to:
+//! - Load several plugins under their own ids through one runtime
+//! - Look plugins up by id or by the name they report
+//! - Call plugin functions by plugin id
+//! - Identify the calling plugin in host functions with `Caller`
+//! - Track a crashed plugin, which refuses further calls, and unload it
+//!
+//! Run with: `cargo run --example plugin_manager`
+
+use tosic_plugin_core::*;
+
+/// Mock plugin whose name is the code it was loaded from.
+struct MockPlugin {
+    name: String,
+    context: HostContext,
+}
+
+impl Plugin for MockPlugin {
+    fn name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+}
+
+/// Mock runtime whose plugins can greet, log through the host, and crash.
+struct MockRuntime;
+
+impl Runtime for MockRuntime {
+    type Plugin = MockPlugin;
+
+    fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        Ok(MockPlugin {
+            name: String::from_utf8_lossy(bytes).into_owned(),
+            context: context.clone(),
+        })
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        match function_name {
+            "greet" => {
+                let name = String::from_value(args.first().unwrap_or(&Value::Null))?;
+                Ok(Value::String(format!(
+                    "Hello from {}, {}!",
+                    plugin.name, name
+                )))
+            }
+            "log" => plugin.context.call_function("log", args),
+            "crash" => Err(PluginError::PluginCrashed(format!(
+                "{} aborted",
+                plugin.name
+            ))),
+            _ => Err(PluginError::FunctionNotFound(function_name.to_string())),
+        }
+    }
+}
+
+fn print_plugins(manager: &DefaultPluginManager<MockRuntime>) {
+    for info in manager.plugins() {
+        println!(
+            "   {} (name: {:?}, state: {:?})",
+            info.id, info.name, info.state
+        );
+    }
+}
+
+fn main() -> PluginResult<()> {
+    println!("=== Plugin Manager Example ===\n");
+
+    // Host functions taking a `Caller` learn which plugin called them
+    let mut context = HostContext::new();
+    context.register("log", |caller: Caller, message: String| {
+        let count = caller
+            .get("logged")
+            .and_then(|value| value.as_int())
+            .unwrap_or(0)
+            + 1;
+        caller.set("logged", count);
+        println!(
+            "   [{} ({})] #{}: {}",
+            caller.id(),
+            caller.name().unwrap_or("unnamed"),
+            count,
+            message
+        );
+    });
+
+    let mut manager = DefaultPluginManager::new(MockRuntime, context);
+
+    println!("1. Loading plugins 'formatter' and 'linter':");
+    manager.load("formatter", b"pretty-printer")?;
+    manager.load("linter", b"style-checker")?;
+    print_plugins(&manager);
+
+    println!("\n2. Calling greet on 'formatter':");
+    let result = manager.call("formatter", "greet", &[Value::from("World")])?;
+    println!("   Result: {:?}", result);
+
+    println!("\n3. Logging through the host from both plugins:");
+    manager.call("formatter", "log", &[Value::from("formatting started")])?;
+    manager.call("linter", "log", &[Value::from("linting started")])?;
+    manager.call("formatter", "log", &[Value::from("formatting done")])?;
+
+    println!("\n4. Looking up the plugin named 'style-checker':");
+    let plugin = manager.get_by_name("style-checker");
+    println!("   Found: {:?}", plugin.and_then(Plugin::name));
+
+    println!("\n5. Crashing 'linter':");
+    match manager.call("linter", "crash", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Error: {}", e),
+    }
+    print_plugins(&manager);
+    match manager.call("linter", "greet", &[Value::from("World")]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Calling it again: {}", e),
+    }
+
+    println!("\n6. Unloading 'linter':");
+    manager.unload("linter")?;
+    print_plugins(&manager);
+
+    println!("\n=== Testing Error Cases ===\n");
+
+    println!("7. Loading a second plugin under 'formatter':");
+    match manager.load("formatter", b"another-printer") {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n8. Calling the unloaded plugin 'linter':");
+    match manager.call("linter", "greet", &[Value::from("World")]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n9. Calling non-existent function:");
+    match manager.call("formatter", "nonexistent", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n=== Example completed successfully! ===");
+    Ok(())
+}
@@ -0,0 +1,102 @@
+//! Example demonstrating conversions between serde types and plugin values.
+//!
+//! This example shows how to:
+//! - Convert `Serialize` types into `Value`s with `to_value`
+//! - Convert `Value`s back into `Deserialize` types with `from_value`
+//! - Take and return serde types in host functions with `Serde<T>`
+//!
+//! Run with: `cargo run --example serde_values --features serde`
+
+use serde::{Deserialize, Serialize};
+use tosic_plugin_core::*;
+
+/// Severity of an event reported by a plugin.
+#[derive(Debug, Serialize, Deserialize)]
+enum Severity {
+    Info,
+    Warning,
+    Error { code: i64 },
+}
+
+/// Event reported by a plugin to the host.
+#[derive(Debug, Serialize, Deserialize)]
+struct Event {
+    source: String,
+    severity: Severity,
+    tags: Vec<String>,
+    retries: Option<u32>,
+}
+
+/// Summary the host returns for every reported event.
+#[derive(Debug, Serialize, Deserialize)]
+struct Receipt {
+    accepted: bool,
+    message: String,
+}
+
+fn main() -> PluginResult<()> {
+    println!("=== Serde Values Example ===\n");
+
+    let event = Event {
+        source: "scheduler".to_string(),
+        severity: Severity::Error { code: 42 },
+        tags: vec!["cron".to_string(), "nightly".to_string()],
+        retries: None,
+    };
+
+    println!("1. Converting an event into a value:");
+    let value = to_value(&event)?;
+    println!("   {:?}", value);
+
+    println!("\n2. Converting the value back into an event:");
+    let event: Event = from_value(value.clone())?;
+    println!("   {:?}", event);
+
+    println!("\n3. Calling a host function taking and returning serde types:");
+    let mut context = HostContext::new();
+    context.register("report", |Serde(event): Serde<Event>| {
+        let accepted = !matches!(event.severity, Severity::Info);
+        Serde(Receipt {
+            accepted,
+            message: format!(
+                "{} event from {} with {} tag(s)",
+                if accepted { "stored" } else { "ignored" },
+                event.source,
+                event.tags.len()
+            ),
+        })
+    });
+    let result = context.call_function("report", &[value])?;
+    let receipt: Receipt = from_value(result)?;
+    println!("   {:?}", receipt);
+
+    println!("\n4. Converting unit variants:");
+    println!(
+        "   {:?} and {:?}",
+        to_value(&Severity::Info)?,
+        to_value(&Severity::Warning)?
+    );
+
+    println!("\n=== Testing Error Cases ===\n");
+
+    println!("5. Converting a value of the wrong shape:");
+    match from_value::<Event>(Value::from("not an event")) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n6. Converting an integer out of range for a value:");
+    match to_value(&u64::MAX) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n7. Calling the host function with a malformed event:");
+    match context.call_function("report", &[Value::Int(1)]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n=== Example completed successfully! ===");
+    Ok(())
+}
@@ -3,20 +3,22 @@
 //! This example shows how to:
 //! - Implement the Runtime and Plugin traits for a mock runtime
 //! - Register host functions with automatic type conversion
+//! - Inject shared application state into host functions with `State<T>`
 //! - Load and execute plugin functions
-//! 
+//!
 //! Run with: `cargo run --example sync_runtime`
 
-#[cfg(feature = "async")]
-compile_error!("This example requires the 'async' feature to be disabled.");
-
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
 use tosic_plugin_core::*;
 
+/// Type-erased plugin function stored by the mock plugin.
+type PluginFunction = Box<dyn Fn(&[Value]) -> PluginResult<Value> + Send + Sync>;
+
 /// Mock plugin implementation that simulates a simple plugin with predefined functions.
 struct MockPlugin {
     name: String,
-    functions: HashMap<String, Box<dyn Fn(&[Value]) -> PluginResult<Value> + Send + Sync>>,
+    functions: HashMap<String, PluginFunction>,
 }
 
 impl Plugin for MockPlugin {
@@ -42,36 +44,48 @@ impl Runtime for MockRuntime {
         // Simulate plugin loading from bytes
         let plugin_code = String::from_utf8_lossy(bytes);
         println!("Loading plugin from {} bytes: {}", bytes.len(), plugin_code);
-        
+
         // Create a mock plugin with some predefined functions
-        let mut functions: HashMap<String, Box<dyn Fn(&[Value]) -> PluginResult<Value> + Send + Sync>> = HashMap::new();
-        
+        let mut functions: HashMap<String, PluginFunction> = HashMap::new();
+
         // Add a simple "add" function
-        functions.insert("add".to_string(), Box::new(|args: &[Value]| -> PluginResult<Value> {
-            if args.len() != 2 {
-                return Err(PluginError::InvalidArgumentType);
-            }
-            
-            let a = args[0].as_int().ok_or(PluginError::InvalidArgumentType)?;
-            let b = args[1].as_int().ok_or(PluginError::InvalidArgumentType)?;
-            
-            Ok(Value::Int(a + b))
-        }));
-        
+        functions.insert(
+            "add".to_string(),
+            Box::new(|args: &[Value]| -> PluginResult<Value> {
+                if args.len() != 2 {
+                    return Err(PluginError::CallError {
+                        function: "add".to_string(),
+                        message: format!("expected 2 argument(s), received {}", args.len()),
+                    });
+                }
+
+                let a = i64::from_value(&args[0])?;
+                let b = i64::from_value(&args[1])?;
+
+                Ok(Value::Int(a + b))
+            }),
+        );
+
         // Add a "greet" function
-        functions.insert("greet".to_string(), Box::new(|args: &[Value]| -> PluginResult<Value> {
-            if args.len() != 1 {
-                return Err(PluginError::InvalidArgumentType);
-            }
-            
-            let name = args[0].as_string().ok_or(PluginError::InvalidArgumentType)?;
-            
-            // Simulate plugin logging (in a real implementation, this would call host functions)
-            println!("[PLUGIN LOG] Plugin is greeting: {}", name);
-            
-            Ok(Value::String(format!("Hello from plugin, {}!", name)))
-        }));
-        
+        functions.insert(
+            "greet".to_string(),
+            Box::new(|args: &[Value]| -> PluginResult<Value> {
+                if args.len() != 1 {
+                    return Err(PluginError::CallError {
+                        function: "greet".to_string(),
+                        message: format!("expected 1 argument(s), received {}", args.len()),
+                    });
+                }
+
+                let name = String::from_value(&args[0])?;
+
+                // Simulate plugin logging (in a real implementation, this would call host functions)
+                println!("[PLUGIN LOG] Plugin is greeting: {}", name);
+
+                Ok(Value::String(format!("Hello from plugin, {}!", name)))
+            }),
+        );
+
         Ok(MockPlugin {
             name: "mock-plugin".to_string(),
             functions,
@@ -84,8 +98,12 @@ impl Runtime for MockRuntime {
         function_name: &str,
         args: &[Value],
     ) -> PluginResult<Value> {
-        println!("Calling function '{}' with {} arguments", function_name, args.len());
-        
+        println!(
+            "Calling function '{}' with {} arguments",
+            function_name,
+            args.len()
+        );
+
         match plugin.functions.get(function_name) {
             Some(func) => func(args),
             None => Err(PluginError::FunctionNotFound(function_name.to_string())),
@@ -95,80 +113,116 @@ impl Runtime for MockRuntime {
 
 fn main() -> PluginResult<()> {
     println!("=== Synchronous Plugin Runtime Example ===\n");
-    
+
     // Create a host context and register some host functions
     let mut host_context = HostContext::new();
-    
+
     // Register a logging function that the plugin can call
     host_context.register("log", |message: String| {
         println!("[HOST LOG] {}", message);
     });
-    
+
     // Register a math utility function
     host_context.register("multiply", |a: i64, b: i64| -> i64 {
         println!("[HOST] Multiplying {} * {}", a, b);
         a * b
     });
-    
+
+    // Register a function that can fail; its error is reported to the caller
+    host_context.register("divide", |a: i64, b: i64| -> Result<i64, String> {
+        a.checked_div(b)
+            .ok_or_else(|| format!("cannot divide {} by {}", a, b))
+    });
+
+    // Register a function taking narrower integer types; arguments that do not fit are rejected
+    host_context.register("shift", |value: u32, bits: u8| {
+        value.checked_shl(bits.into()).unwrap_or(0)
+    });
+
     // Register a function that returns no value
     host_context.register("ping", || {
         println!("[HOST] Ping received!");
         // Returns () which converts to Value::Null
     });
-    
-    println!("Registered host functions: {:?}\n", 
-             host_context.function_names().collect::<Vec<_>>());
-    
+
+    // Register a function using shared state instead of a captured clone
+    host_context.insert_state(AtomicI64::new(1));
+    host_context.register("next_id", |ids: State<AtomicI64>| {
+        ids.fetch_add(1, Ordering::SeqCst)
+    });
+
+    println!(
+        "Registered host functions: {:?}\n",
+        host_context.function_names().collect::<Vec<_>>()
+    );
+
     // Create the runtime
     let runtime = MockRuntime::new();
-    
+
     // Simulate plugin code (in a real implementation, this would be WASM, JS, etc.)
     let plugin_code = b"mock plugin code with add and greet functions";
-    
+
     // Load the plugin
     println!("Loading plugin...");
     let plugin = runtime.load(plugin_code, &host_context)?;
     println!("Loaded plugin: {:?}\n", plugin.name());
-    
+
     // Test calling plugin functions
     println!("=== Testing Plugin Function Calls ===\n");
-    
+
     // Test the "add" function
     println!("1. Calling add(5, 3):");
     let result = runtime.call(&plugin, "add", &[Value::Int(5), Value::Int(3)])?;
     println!("   Result: {:?}\n", result);
-    
+
     // Test the "greet" function (which calls host functions)
     println!("2. Calling greet('World'):");
     let result = runtime.call(&plugin, "greet", &[Value::String("World".to_string())])?;
     println!("   Result: {:?}\n", result);
-    
+
     // Test calling host functions directly
     println!("=== Testing Host Function Calls ===\n");
-    
+
     println!("3. Calling host function multiply(7, 6):");
     let result = host_context.call_function("multiply", &[Value::Int(7), Value::Int(6)])?;
     println!("   Result: {:?}\n", result);
-    
+
     println!("4. Calling host function ping():");
     let result = host_context.call_function("ping", &[])?;
     println!("   Result: {:?}\n", result);
-    
+
+    println!("5. Calling host function next_id() twice:");
+    let first = host_context.call_function("next_id", &[])?;
+    let second = host_context.call_function("next_id", &[])?;
+    println!("   Results: {:?}, {:?}\n", first, second);
+
     // Test error cases
     println!("=== Testing Error Cases ===\n");
-    
-    println!("5. Calling non-existent function:");
+
+    println!("6. Calling non-existent function:");
     match runtime.call(&plugin, "nonexistent", &[]) {
         Ok(_) => println!("   Unexpected success!"),
         Err(e) => println!("   Expected error: {}", e),
     }
-    
-    println!("\n6. Calling add with wrong number of arguments:");
+
+    println!("\n7. Calling add with wrong number of arguments:");
     match runtime.call(&plugin, "add", &[Value::Int(1)]) {
         Ok(_) => println!("   Unexpected success!"),
         Err(e) => println!("   Expected error: {}", e),
     }
-    
+
+    println!("\n8. Calling host function divide(1, 0):");
+    match host_context.call_function("divide", &[Value::Int(1), Value::Int(0)]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n9. Calling host function shift(1, 300):");
+    match host_context.call_function("shift", &[Value::Int(1), Value::Int(300)]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
     println!("\n=== Example completed successfully! ===");
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,308 @@
+//! Support code for the `FromValue` and `IntoValue` derive macros. Not public API.
+
+use crate::traits::host_function::FromValue;
+use crate::types::{Map, Value};
+use crate::{PluginError, PluginResult};
+
+/// Map held by [`Value::Object`].
+pub type Object = Map;
+
+static NULL: Value = Value::Null;
+
+/// Wraps the error of converting a field, naming the field and its type.
+pub fn field_error(type_name: &str, field: &str, error: &PluginError) -> PluginError {
+    PluginError::ConversionError(format!(
+        "invalid field `{field}` of `{type_name}`: {}",
+        error.detail()
+    ))
+}
+
+fn invalid(type_name: &str, expected: &str, found: &Value) -> PluginError {
+    PluginError::ConversionError(format!(
+        "expected {expected} for `{type_name}`, found {found:?}"
+    ))
+}
+
+/// Returns the entries of an object value.
+pub fn object<'a>(value: &'a Value, type_name: &str) -> PluginResult<&'a Object> {
+    value
+        .as_object()
+        .ok_or_else(|| PluginError::type_mismatch(type_name, value))
+}
+
+/// Returns the items of an array value holding exactly `len` items.
+pub fn array<'a>(value: &'a Value, type_name: &str, len: usize) -> PluginResult<&'a [Value]> {
+    match value.as_array() {
+        Some(items) if items.len() == len => Ok(items),
+        Some(_) => Err(invalid(
+            type_name,
+            &format!("an array of {len} items"),
+            value,
+        )),
+        None => Err(PluginError::type_mismatch(type_name, value)),
+    }
+}
+
+/// Checks that a unit struct is represented by null.
+pub fn unit(value: &Value, type_name: &str) -> PluginResult<()> {
+    if value.is_null() {
+        Ok(())
+    } else {
+        Err(PluginError::type_mismatch(type_name, value))
+    }
+}
+
+/// Converts the field stored under `key`, returning `None` if the object has no such key.
+pub fn field<T: FromValue>(object: &Object, type_name: &str, key: &str) -> PluginResult<Option<T>> {
+    object
+        .get(key)
+        .map(|value| T::from_value(value).map_err(|error| field_error(type_name, key, &error)))
+        .transpose()
+}
+
+/// Converts a field missing from its object, which only succeeds for types accepting null.
+pub fn missing<T: FromValue>(type_name: &str, key: &str) -> PluginResult<T> {
+    T::from_value(&NULL).map_err(|_| {
+        PluginError::ConversionError(format!("missing field `{key}` of `{type_name}`"))
+    })
+}
+
+/// Converts an item of a tuple struct or tuple variant.
+pub fn item<T: FromValue>(items: &[Value], type_name: &str, index: usize) -> PluginResult<T> {
+    T::from_value(&items[index]).map_err(|error| field_error(type_name, &index.to_string(), &error))
+}
+
+/// Returns the name and content of an externally tagged variant, which is either its name
+/// alone or an object with the name as its only key.
+pub fn external_variant<'a>(
+    value: &'a Value,
+    type_name: &str,
+) -> PluginResult<(&'a str, &'a Value)> {
+    match value {
+        Value::String(name) => Ok((name, &NULL)),
+        Value::Object(object) if object.len() == 1 => {
+            let (name, content) = object.iter().next().expect("object has one entry");
+            Ok((name, content))
+        }
+        other => Err(invalid(
+            type_name,
+            "a variant name or an object with a single key",
+            other,
+        )),
+    }
+}
+
+/// Returns the variant name stored under `tag`.
+pub fn tag<'a>(object: &'a Object, type_name: &str, tag: &str) -> PluginResult<&'a str> {
+    match object.get(tag) {
+        Some(Value::String(name)) => Ok(name),
+        Some(other) => Err(invalid(
+            type_name,
+            &format!("a variant name under `{tag}`"),
+            other,
+        )),
+        None => Err(PluginError::ConversionError(format!(
+            "missing tag `{tag}` of `{type_name}`"
+        ))),
+    }
+}
+
+/// Returns the content stored under `key`, or null if there is none.
+pub fn content<'a>(object: &'a Object, key: &str) -> &'a Value {
+    object.get(key).unwrap_or(&NULL)
+}
+
+/// Reports a variant name that matches no variant.
+pub fn unknown_variant(type_name: &str, name: &str) -> PluginError {
+    PluginError::ConversionError(format!("unknown variant `{name}` of `{type_name}`"))
+}
+
+/// Reports a value that matches no variant of an untagged enum.
+pub fn no_variant(type_name: &str, value: &Value) -> PluginError {
+    invalid(type_name, "a value matching any variant", value)
+}
+
+/// Merges a flattened field into its parent object. Values other than objects are stored
+/// under the field's own key.
+pub fn flatten(object: &mut Object, key: &str, value: Value) {
+    match value {
+        Value::Object(entries) => object.extend(entries),
+        other => {
+            object.insert(key.to_string(), other);
+        }
+    }
+}
+
+/// Wraps the content of an externally tagged variant in an object keyed by its name.
+pub fn tagged(name: &str, content: Value) -> Value {
+    Value::Object(Object::from([(name.to_string(), content)]))
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use crate::{FromValue, HostContext, IntoValue, PluginError, Value, host_function};
+
+    fn object(entries: &[(&str, Value)]) -> Value {
+        Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+        )
+    }
+
+    /// Converts `item` into a value, checks it against `expected`, and converts it back.
+    fn round_trip<T>(item: T, expected: &Value)
+    where
+        T: FromValue + IntoValue + Clone + PartialEq + std::fmt::Debug,
+    {
+        assert_eq!(&item.clone().into_value(), expected);
+        assert_eq!(T::from_value(expected).unwrap(), item);
+    }
+
+    #[derive(Debug, Clone, PartialEq, FromValue, IntoValue)]
+    #[value(crate = "crate", rename_all = "snake_case")]
+    enum External {
+        Unit,
+        Newtype(i64),
+        Tuple(i64, String),
+        Struct { long_name: bool },
+    }
+
+    #[derive(Debug, Clone, PartialEq, FromValue, IntoValue)]
+    #[value(crate = "crate", tag = "type")]
+    enum Internal {
+        Unit,
+        Struct { a: i64 },
+    }
+
+    #[derive(Debug, Clone, PartialEq, FromValue, IntoValue)]
+    #[value(crate = "crate", tag = "t", content = "c")]
+    enum Adjacent {
+        Unit,
+        Newtype(String),
+        Struct { a: i64 },
+    }
+
+    #[derive(Debug, Clone, PartialEq, FromValue, IntoValue)]
+    #[value(crate = "crate", untagged)]
+    enum Untagged {
+        Unit,
+        Int(i64),
+        Text(String),
+        Struct { a: i64 },
+    }
+
+    #[test]
+    fn external_tagging() {
+        round_trip(External::Unit, &Value::from("unit"));
+        round_trip(External::Newtype(1), &object(&[("newtype", Value::Int(1))]));
+        let tuple = Value::Array(vec![Value::Int(1), Value::from("x")]);
+        round_trip(
+            External::Tuple(1, "x".to_string()),
+            &object(&[("tuple", tuple)]),
+        );
+        let fields = object(&[("long_name", Value::Bool(true))]);
+        round_trip(
+            External::Struct { long_name: true },
+            &object(&[("struct", fields)]),
+        );
+
+        assert!(External::from_value(&Value::from("other")).is_err());
+        assert!(
+            External::from_value(&object(&[
+                ("unit", Value::Null),
+                ("newtype", Value::Int(1))
+            ]))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn internal_tagging() {
+        round_trip(Internal::Unit, &object(&[("type", Value::from("Unit"))]));
+        round_trip(
+            Internal::Struct { a: 1 },
+            &object(&[("type", Value::from("Struct")), ("a", Value::Int(1))]),
+        );
+
+        let error = Internal::from_value(&object(&[("a", Value::Int(1))])).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Value conversion error: missing tag `type` of `Internal`"
+        );
+    }
+
+    #[test]
+    fn adjacent_tagging() {
+        round_trip(Adjacent::Unit, &object(&[("t", Value::from("Unit"))]));
+        round_trip(
+            Adjacent::Newtype("x".to_string()),
+            &object(&[("t", Value::from("Newtype")), ("c", Value::from("x"))]),
+        );
+        let content = object(&[("a", Value::Int(1))]);
+        round_trip(
+            Adjacent::Struct { a: 1 },
+            &object(&[("t", Value::from("Struct")), ("c", content)]),
+        );
+
+        assert!(Adjacent::from_value(&object(&[("t", Value::from("Newtype"))])).is_err());
+    }
+
+    #[test]
+    fn untagged() {
+        round_trip(Untagged::Unit, &Value::Null);
+        round_trip(Untagged::Int(1), &Value::Int(1));
+        round_trip(Untagged::Text("x".to_string()), &Value::from("x"));
+        round_trip(Untagged::Struct { a: 1 }, &object(&[("a", Value::Int(1))]));
+
+        assert!(matches!(
+            Untagged::from_value(&Value::Bool(true)),
+            Err(PluginError::ConversionError(_))
+        ));
+    }
+
+    #[test]
+    fn field_errors_name_the_field() {
+        let value = object(&[("struct", object(&[("long_name", Value::Int(1))]))]);
+        let error = External::from_value(&value).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("invalid field `long_name` of `External::Struct`"),
+            "{error}"
+        );
+    }
+
+    /// Repeats a string.
+    #[host_function(crate = "crate", name = "repeat_text")]
+    fn repeat(text: String, times: Option<i64>) -> String {
+        text.repeat(times.unwrap_or(1) as usize)
+    }
+
+    #[test]
+    fn host_functions_register_with_their_metadata() {
+        let mut context = HostContext::new();
+        repeat::register(&mut context);
+
+        let metadata = context.metadata("repeat_text").unwrap();
+        assert_eq!(metadata.doc(), Some("Repeats a string."));
+        let params: Vec<_> = metadata
+            .params()
+            .iter()
+            .map(|param| (param.name(), param.ty()))
+            .collect();
+        assert_eq!(params, [("text", "String"), ("times", "Option<i64>")]);
+        assert_eq!(metadata.returns(), Some("String"));
+
+        let args = [Value::from("ab"), Value::Int(2)];
+        assert_eq!(
+            context.call_function("repeat_text", &args).unwrap(),
+            Value::from("abab")
+        );
+        assert_eq!(
+            context.signature("repeat_text").unwrap().to_string(),
+            "fn(string, int?) -> string"
+        );
+    }
+}
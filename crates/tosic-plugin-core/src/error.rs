@@ -2,42 +2,190 @@
 
 use thiserror::Error;
 
+use crate::types::{Value, ValueKind};
+
 /// Errors that can occur during plugin operations.
 #[derive(Error, Debug)]
 pub enum PluginError {
     /// Failed to load plugin from bytes.
     #[error("Failed to load plugin: {0}")]
     LoadError(String),
-    
+
     /// Failed to call a specific function in the plugin.
     #[error("Failed to call function '{function}': {message}")]
-    CallError { 
+    CallError {
         /// The name of the function that failed to call.
-        function: String, 
+        function: String,
         /// The error message describing why the call failed.
-        message: String 
+        message: String,
     },
-    
+
     /// Function was not found in the loaded plugin.
     #[error("Function '{0}' not found in plugin")]
     FunctionNotFound(String),
-    
+
     /// Invalid argument type provided to a function call.
     #[error("Invalid argument type for function call")]
     InvalidArgumentType,
-    
+
+    /// A value is of a different kind than the Rust type it is converted to expects.
+    #[error("Type mismatch: expected `{expected}`, found {found}")]
+    TypeMismatch {
+        /// The Rust type the value was converted to.
+        expected: String,
+        /// The kind of the value found instead.
+        found: ValueKind,
+    },
+
+    /// A number does not fit in the Rust numeric type it is converted to.
+    #[error("Value {value} is out of range for `{expected}`")]
+    OutOfRange {
+        /// The Rust type the number was converted to.
+        expected: String,
+        /// The number that does not fit.
+        value: String,
+    },
+
+    /// A host function was called with the wrong number of arguments.
+    #[error("Host function '{function}' expects {}, but received {received}", expected_arguments(.min, .max))]
+    ArityMismatch {
+        /// The name of the host function.
+        function: String,
+        /// The number of arguments the host function requires.
+        min: usize,
+        /// The number of arguments the host function takes, including optional ones, or
+        /// `None` if it is variadic.
+        max: Option<usize>,
+        /// The number of arguments it was called with.
+        received: usize,
+    },
+
+    /// An argument passed to a host function could not be converted to its parameter type.
+    #[error("Invalid argument at index {index} of host function '{function}': {source}")]
+    InvalidArgument {
+        /// The name of the host function.
+        function: String,
+        /// The index of the argument among those passed by the plugin.
+        index: usize,
+        /// Why the argument could not be converted, usually a [`PluginError::TypeMismatch`].
+        source: Box<PluginError>,
+    },
+
     /// General runtime error during plugin execution.
     #[error("Runtime error: {0}")]
     RuntimeError(String),
-    
+
     /// Host function was not found in the context.
     #[error("Host function '{0}' not found")]
     HostFunctionNotFound(String),
-    
+
+    /// An async host function was called synchronously through a context without a
+    /// blocking executor.
+    #[error(
+        "Host function '{0}' is async and must be awaited, or called through a context with a blocking executor"
+    )]
+    AsyncHostFunction(String),
+
     /// Plugin is in an invalid state for the requested operation.
     #[error("Invalid plugin state")]
     InvalidPluginState,
+
+    /// A value could not be encoded to or decoded from its binary representation.
+    #[error("Encoding error: {0}")]
+    EncodingError(String),
+
+    /// The plugin terminated unexpectedly, such as an out-of-process plugin exiting mid-call.
+    #[error("Plugin crashed: {0}")]
+    PluginCrashed(String),
+
+    /// No plugin is loaded under the given id.
+    #[error("Plugin '{0}' not found")]
+    PluginNotFound(String),
+
+    /// A plugin is already loaded under the given id.
+    #[error("Plugin '{0}' is already loaded")]
+    PluginAlreadyLoaded(String),
+
+    /// A host function reported an error.
+    #[error("Host function error: {0}")]
+    HostError(String),
+
+    /// A value could not be converted to or from a Rust type.
+    #[error("Value conversion error: {0}")]
+    ConversionError(String),
+}
+
+impl PluginError {
+    /// Creates a [`PluginError::HostError`] from any displayable error, for host functions
+    /// failing with error types that do not convert into `PluginError`.
+    ///
+    /// ```rust
+    /// use tosic_plugin_core::*;
+    ///
+    /// let mut context = HostContext::new();
+    /// context.register("parse", |text: String| text.parse::<i64>().map_err(PluginError::host));
+    ///
+    /// assert!(context.call_function("parse", &[Value::from("12")]).is_ok());
+    /// assert!(context.call_function("parse", &[Value::from("twelve")]).is_err());
+    /// ```
+    pub fn host(error: impl std::fmt::Display) -> Self {
+        Self::HostError(error.to_string())
+    }
+
+    /// Describes the error for nesting in the message of a [`PluginError::ConversionError`],
+    /// leaving out the prefix of conversion errors and type mismatches.
+    pub(crate) fn detail(&self) -> String {
+        match self {
+            Self::ConversionError(message) => message.clone(),
+            Self::TypeMismatch { expected, found } => {
+                format!("expected `{expected}`, found {found}")
+            }
+            other => other.to_string(),
+        }
+    }
+
+    /// Creates a [`PluginError::TypeMismatch`] for `found`, which cannot be converted to the
+    /// Rust type named `expected`.
+    ///
+    /// ```rust
+    /// use tosic_plugin_core::*;
+    ///
+    /// let error = PluginError::type_mismatch("i64", &Value::from("twelve"));
+    /// assert_eq!(error.to_string(), "Type mismatch: expected `i64`, found string");
+    /// ```
+    pub fn type_mismatch(expected: impl Into<String>, found: &Value) -> Self {
+        Self::TypeMismatch {
+            expected: expected.into(),
+            found: found.kind(),
+        }
+    }
+}
+
+impl From<String> for PluginError {
+    fn from(message: String) -> Self {
+        Self::HostError(message)
+    }
+}
+
+impl From<&str> for PluginError {
+    fn from(message: &str) -> Self {
+        Self::HostError(message.to_string())
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for PluginError {
+    fn from(error: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        Self::HostError(error.to_string())
+    }
+}
+
+fn expected_arguments(min: &usize, max: &Option<usize>) -> String {
+    match max {
+        Some(max) if max == min => format!("{min} argument(s)"),
+        Some(max) => format!("{min} to {max} arguments"),
+        None => format!("at least {min} argument(s)"),
+    }
 }
 
 /// Result type for plugin operations that may fail.
-pub type PluginResult<T, E = PluginError> = Result<T, E>;
\ No newline at end of file
+pub type PluginResult<T, E = PluginError> = Result<T, E>;
@@ -6,15 +6,27 @@
 //!
 //! # Features
 //!
-//! - **async**: Enable async/await support for plugin operations (recommended)
+//! - **async**: Add the `AsyncRuntime` and `AsyncPluginManager` traits, async host functions
+//!   registered with `HostContext::register_async`, and the `SpawnBlocking` and `BlockOn`
+//!   adapters between sync and async runtimes
+//! - **serde**: Implement `Serialize` and `Deserialize` for [`Value`], and convert any serde
+//!   type to and from it with `to_value`, `from_value` and the `Serde<T>` host function wrapper
+//! - **derive**: Re-export the `FromValue` and `IntoValue` derive macros and the `host_function`
+//!   attribute from `tosic-plugin-derive`
 //!
 //! # Core Concepts
 //!
-//! - [`Runtime`]: Trait for plugin runtime implementations
+//! - [`Runtime`]: Trait for plugin runtime implementations, with an async counterpart
+//!   behind the `async` feature
+//! - [`DynRuntime`]: Object-safe, type-erased runtime for mixing runtimes of different types
 //! - [`Plugin`]: Opaque handle to loaded plugin instances  
 //! - [`Value`]: Boundary type for data exchange between host and plugins
 //! - [`HostContext`]: Container for host functions that plugins can call
 //! - [`HostFunction`]: Trait for type-safe host function registration
+//! - [`Signature`]: Arity and value kinds of a registered host function, for validating imports
+//! - [`State`]: Host function parameter injecting typed application state from the context
+//! - [`Caller`]: Host function parameter identifying the calling plugin, with per-plugin storage
+//! - [`PluginManager`]: Trait for plugin lifecycle management, implemented by [`DefaultPluginManager`]
 //!
 //! # Example
 //!
@@ -36,11 +48,18 @@
 #![cfg_attr(not(debug_assertions), deny(unsafe_code))]
 #![cfg_attr(not(debug_assertions), deny(unused))]
 
+#[macro_use]
+mod macros;
+#[doc(hidden)]
+pub mod __private;
+mod error;
 pub mod traits;
 pub mod types;
-mod error;
 
 // Re-export core types and traits
 pub use error::*;
-pub use traits::{host_function::*, runtime::*};
+pub use traits::{dynamic::*, host_function::*, manager::*, runtime::*};
 pub use types::*;
+
+#[cfg(feature = "derive")]
+pub use tosic_plugin_derive::{FromValue, IntoValue, host_function};
@@ -0,0 +1,20 @@
+//! Internal macros shared across the crate.
+
+/// Invokes `$mac` once for every arity from 0 to 32, with the type parameters `A1` to `An`.
+///
+/// Used to implement the host function traits for functions and tuples of each arity.
+macro_rules! for_each_arity {
+    ($mac:ident) => {
+        for_each_arity!(@step $mac []
+            A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16
+            A17 A18 A19 A20 A21 A22 A23 A24 A25 A26 A27 A28 A29 A30 A31 A32
+        );
+    };
+    (@step $mac:ident [$($done:ident)*] $next:ident $($rest:ident)*) => {
+        $mac!($($done),*);
+        for_each_arity!(@step $mac [$($done)* $next] $($rest)*);
+    };
+    (@step $mac:ident [$($done:ident)*]) => {
+        $mac!($($done),*);
+    };
+}
@@ -0,0 +1,159 @@
+//! Type-erased runtimes for holding different plugin runtimes behind one trait object.
+
+use std::any::Any;
+use std::fmt;
+
+use crate::traits::runtime::{Plugin, Runtime};
+use crate::types::{HostContext, Value};
+use crate::{PluginError, PluginResult};
+
+/// Object-safe counterpart of [`Runtime`] whose plugins are type-erased [`DynPlugin`]s.
+///
+/// Every [`Runtime`] implements this trait, so runtimes of different types can be stored
+/// together as `Box<dyn DynRuntime>` and picked at run time, for example from
+/// configuration. `dyn DynRuntime` implements [`Runtime`] in turn, with [`DynPlugin`] as
+/// its plugin type.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> tosic_plugin_core::PluginResult<()> {
+/// use tosic_plugin_core::*;
+///
+/// struct EchoPlugin;
+///
+/// impl Plugin for EchoPlugin {}
+///
+/// struct EchoRuntime;
+///
+/// impl Runtime for EchoRuntime {
+///     type Plugin = EchoPlugin;
+///
+///     fn load(&self, _bytes: &[u8], _context: &HostContext) -> PluginResult<EchoPlugin> {
+///         Ok(EchoPlugin)
+///     }
+///
+///     fn call(&self, _plugin: &EchoPlugin, _function_name: &str, args: &[Value]) -> PluginResult<Value> {
+///         Ok(Value::Array(args.to_vec()))
+///     }
+/// }
+///
+/// let runtimes: Vec<Box<dyn DynRuntime>> = vec![Box::new(EchoRuntime)];
+/// let plugin = runtimes[0].load(b"", &HostContext::new())?;
+/// let result = runtimes[0].call(&plugin, "echo", &[Value::Int(1)])?;
+/// assert_eq!(result, Value::Array(vec![Value::Int(1)]));
+/// # Ok(())
+/// # }
+/// ```
+pub trait DynRuntime: Send + Sync {
+    /// Loads plugin code from bytes with the provided host context.
+    /// Returns a type-erased plugin instance that can be used to call functions.
+    ///
+    /// # Errors
+    /// Returns the underlying runtime's error if loading fails.
+    fn load_dyn(&self, bytes: &[u8], context: &HostContext) -> PluginResult<DynPlugin>;
+
+    /// Calls a function in a plugin loaded by this runtime with the given arguments.
+    ///
+    /// # Errors
+    /// Returns `PluginError::RuntimeError` if the plugin was loaded by a runtime of a
+    /// different type, or the underlying runtime's error if the call fails.
+    fn call_dyn(
+        &self,
+        plugin: &DynPlugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value>;
+}
+
+impl<R> DynRuntime for R
+where
+    R: Runtime,
+    R::Plugin: 'static,
+{
+    fn load_dyn(&self, bytes: &[u8], context: &HostContext) -> PluginResult<DynPlugin> {
+        self.load(bytes, context).map(DynPlugin::new)
+    }
+
+    fn call_dyn(
+        &self,
+        plugin: &DynPlugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        let plugin = plugin.downcast_ref::<R::Plugin>().ok_or_else(|| {
+            PluginError::RuntimeError(format!(
+                "cannot call '{function_name}' on a plugin loaded by a different runtime"
+            ))
+        })?;
+        self.call(plugin, function_name, args)
+    }
+}
+
+impl Runtime for dyn DynRuntime {
+    type Plugin = DynPlugin;
+
+    fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        self.load_dyn(bytes, context)
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        self.call_dyn(plugin, function_name, args)
+    }
+}
+
+/// Type-erased plugin loaded by a [`DynRuntime`].
+///
+/// The concrete plugin can be recovered with [`downcast_ref`](Self::downcast_ref).
+pub struct DynPlugin {
+    plugin: Box<dyn AnyPlugin>,
+}
+
+impl DynPlugin {
+    /// Erases the type of a plugin.
+    pub fn new<P: Plugin + 'static>(plugin: P) -> Self {
+        Self {
+            plugin: Box::new(plugin),
+        }
+    }
+
+    /// Returns true if the erased plugin is of type `P`.
+    pub fn is<P: Plugin + 'static>(&self) -> bool {
+        (*self.plugin).as_any().is::<P>()
+    }
+
+    /// Returns the erased plugin if it is of type `P`.
+    pub fn downcast_ref<P: Plugin + 'static>(&self) -> Option<&P> {
+        (*self.plugin).as_any().downcast_ref()
+    }
+}
+
+impl Plugin for DynPlugin {
+    fn name(&self) -> Option<&str> {
+        self.plugin.name()
+    }
+}
+
+impl fmt::Debug for DynPlugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynPlugin")
+            .field("name", &self.name())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A plugin that can be downcast back to its concrete type.
+trait AnyPlugin: Plugin {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<P: Plugin + 'static> AnyPlugin for P {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
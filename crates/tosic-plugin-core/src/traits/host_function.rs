@@ -1,43 +1,212 @@
 //! Host function traits for type-safe function registration and calling.
 
-use crate::PluginResult;
-use crate::types::Value;
+use crate::types::{HostCall, Signature, Value, ValueKind};
+use crate::{PluginError, PluginResult};
+
+#[cfg(feature = "async")]
+use futures::future::BoxFuture;
 
 /// Trait for types that can be extracted from plugin Values.
+///
+/// Besides scalars, containers of convertible types are supported: `Option<T>` is `Null`
+/// or its content, sequences, sets, arrays and tuples are arrays, and maps with `String`
+/// keys are objects. Binary data is converted through [`Bytes`](crate::Bytes).
+///
+/// # Binary data
+///
+/// `Vec<u8>` is no longer special-cased: it converts into an array of integers like any
+/// other `Vec`, where it used to become [`Value::Bytes`], and `&[u8]` no longer implements
+/// [`IntoValue`]. Host functions exchanging raw bytes take and return
+/// [`Bytes`](crate::Bytes) instead, e.g. `Bytes(vec)` or `Bytes::from(slice)`. A `Vec<u8>`
+/// parameter still accepts [`Value::Bytes`] as well as arrays.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> tosic_plugin_core::PluginResult<()> {
+/// use std::collections::HashMap;
+/// use tosic_plugin_core::*;
+///
+/// let mut context = HostContext::new();
+/// context.register("count_words", |lines: Vec<String>| {
+///     let mut counts = HashMap::<String, i64>::new();
+///     for word in lines.iter().flat_map(|line| line.split_whitespace()) {
+///         *counts.entry(word.to_string()).or_default() += 1;
+///     }
+///     counts
+/// });
+///
+/// let lines = Value::Array(vec![Value::from("a b"), Value::from("b")]);
+/// let counts = context.call_function("count_words", &[lines])?;
+/// assert_eq!(HashMap::<String, i64>::from_value(&counts)?["b"], 2);
+/// # Ok(())
+/// # }
+/// ```
 #[diagnostic::on_unimplemented(
     message = "the type `{Self}` cannot be extracted from a plugin Value",
-    note = "ensure your type implements `FromValue` or use one of the built-in types: bool, i32, i64, f32, f64, String, Vec<u8>, Vec<Value>, HashMap<String, Value>"
+    note = "ensure your type implements `FromValue` or use one of the built-in types: bool, integers, f32, f64, String, Value, Bytes, Option<T>, Vec<T>, HashMap<String, T>, tuples, Lenient<T>"
 )]
 pub trait FromValue: Sized {
     /// Extracts a Rust type from a plugin Value.
-    /// 
+    ///
     /// # Errors
-    /// Returns `PluginError::InvalidArgumentType` if the value cannot be converted to the target type.
+    /// Returns `PluginError::TypeMismatch` if the value is not of the kind the target type is
+    /// converted from, or another error if its content cannot be converted.
     fn from_value(value: &Value) -> PluginResult<Self>;
+
+    /// Returns the kind of value this type is extracted from, recorded in the
+    /// [`Signature`] of host functions taking it as a parameter.
+    ///
+    /// Defaults to [`ValueKind::Any`], for types accepting more than one kind of value.
+    fn expected_kind() -> ValueKind {
+        ValueKind::Any
+    }
+
+    /// Returns the value of a host function parameter of this type when the plugin omits
+    /// its argument, or `None` if the argument is required.
+    ///
+    /// Only trailing parameters can be omitted, so a parameter with a default value is
+    /// optional when every parameter after it is optional too. `Option<T>` defaults to
+    /// `None`, and [`OrDefault<T>`](crate::OrDefault) to `T::default()`.
+    fn default_value() -> Option<Self> {
+        None
+    }
+}
+
+/// Trait for host function parameters, which extract themselves from a [`HostCall`].
+///
+/// Every [`FromValue`] type consumes the next argument passed by the plugin, while
+/// extractors such as [`State`](crate::State) and [`Caller`](crate::Caller) take their value
+/// from the call's context.
+#[diagnostic::on_unimplemented(
+    message = "the type `{Self}` cannot be used as a host function parameter",
+    note = "implement `FromValue` for types passed by the plugin, or `FromHostCall` for extractors"
+)]
+pub trait FromHostCall: Sized {
+    /// Extracts a parameter from the host function call.
+    ///
+    /// # Errors
+    /// Returns `PluginError::ArityMismatch` if the call has no argument left for a required
+    /// parameter, `PluginError::InvalidArgument` if it cannot be converted, or an
+    /// extractor-specific error.
+    fn from_host_call(call: &mut HostCall<'_>) -> PluginResult<Self>;
+
+    /// Adds the arguments this parameter consumes to the signature of its host function.
+    ///
+    /// The default adds nothing, which suits extractors that take their value from the
+    /// call's context rather than from the plugin's arguments.
+    fn describe(signature: &mut Signature) {
+        let _ = signature;
+    }
+}
+
+impl<T: FromValue> FromHostCall for T {
+    fn from_host_call(call: &mut HostCall<'_>) -> PluginResult<Self> {
+        let index = call.position();
+        let Some(value) = call.next_arg() else {
+            let signature = call.signature();
+            return T::default_value()
+                .ok_or_else(|| call.arity_mismatch(signature.required(), signature.max_arity()));
+        };
+        T::from_value(value).map_err(|error| PluginError::InvalidArgument {
+            function: call.function().to_string(),
+            index,
+            source: Box::new(error),
+        })
+    }
+
+    fn describe(signature: &mut Signature) {
+        if T::default_value().is_some() {
+            signature.push_optional_param(T::expected_kind());
+        } else {
+            signature.push_param(T::expected_kind());
+        }
+    }
 }
 
 /// Trait for types that can be converted into plugin Values.
+///
+/// A `Vec<u8>` converts into an array of integers, and `&[u8]` has no implementation; see
+/// [`FromValue`] for how binary data is passed instead.
 #[diagnostic::on_unimplemented(
     message = "the type `{Self}` cannot be converted into a plugin Value",
-    note = "ensure your type implements `IntoValue` or use one of the built-in types: bool, i32, i64, f32, f64, String, &str, Vec<u8>, &[u8], Vec<Value>, HashMap<String, Value>, Value, ()"
+    note = "ensure your type implements `IntoValue` or use one of the built-in types: bool, integers, f32, f64, String, &str, Value, Bytes, Option<T>, Vec<T>, HashMap<String, T>, tuples, ()"
 )]
 pub trait IntoValue {
     /// Converts a Rust type into a plugin Value.
     fn into_value(self) -> Value;
+
+    /// Returns the kind of value this type converts into, recorded in the [`Signature`] of
+    /// host functions returning it.
+    ///
+    /// Defaults to [`ValueKind::Any`], for types producing more than one kind of value.
+    fn produced_kind() -> ValueKind {
+        ValueKind::Any
+    }
+}
+
+/// Trait for the return types of host functions.
+///
+/// Every [`IntoValue`] type succeeds with its value, while `Result<T, E>` lets a host
+/// function fail: its error is converted into a [`PluginError`] and propagated through
+/// [`HostContext::call_function`](crate::HostContext::call_function) to the runtime, which
+/// raises it in the plugin as a trap or exception. Errors of other types can be mapped with
+/// [`PluginError::host`].
+#[diagnostic::on_unimplemented(
+    message = "the type `{Self}` cannot be returned from a host function",
+    note = "return a type implementing `IntoValue`, or `Result<T, E>` where `T: IntoHostResult` and `E: Into<PluginError>`; map other errors with `PluginError::host`"
+)]
+pub trait IntoHostResult {
+    /// Converts the return value of a host function into the result of calling it.
+    ///
+    /// # Errors
+    /// Returns the host function's error if it failed.
+    fn into_host_result(self) -> PluginResult<Value>;
+
+    /// Returns the kind of value the host function succeeds with.
+    ///
+    /// Defaults to [`ValueKind::Any`].
+    fn return_kind() -> ValueKind {
+        ValueKind::Any
+    }
+}
+
+impl<T: IntoValue> IntoHostResult for T {
+    fn into_host_result(self) -> PluginResult<Value> {
+        Ok(self.into_value())
+    }
+
+    fn return_kind() -> ValueKind {
+        T::produced_kind()
+    }
+}
+
+impl<T, E> IntoHostResult for Result<T, E>
+where
+    T: IntoHostResult,
+    E: Into<PluginError>,
+{
+    fn into_host_result(self) -> PluginResult<Value> {
+        self.map_err(Into::into)?.into_host_result()
+    }
+
+    fn return_kind() -> ValueKind {
+        T::return_kind()
+    }
 }
 
 /// Trait for functions that can be used as host functions.
 /// This trait is implemented for functions with different arities.
 #[diagnostic::on_unimplemented(
     message = "the function `{Self}` cannot be used as a host function",
-    note = "ensure your function arguments implement `FromValue` and return type implements `IntoValue`. Functions must be `Fn(...) -> R + Send + Sync`. Maximum 16 arguments supported."
+    note = "ensure your function arguments implement `FromValue` (or `FromHostCall`, for extractors such as `State` and `Caller`) and return type implements `IntoHostResult`. Functions must be `Fn(...) -> R + Send + Sync`. Maximum 32 arguments supported; use `Rest<T>` or `Kwargs<T>` for more."
 )]
 pub trait HostFunction<Args>: Send + Sync {
     /// The return type of the host function.
-    type Output: IntoValue;
-    
+    type Output: IntoHostResult;
+
     /// Calls the host function with the provided arguments.
-    /// 
+    ///
     /// # Errors
     /// Returns an error if the function call fails or if argument types are invalid.
     fn call(&self, args: Args) -> PluginResult<Value>;
@@ -50,49 +219,76 @@ macro_rules! impl_host_function {
         impl<F, R> HostFunction<()> for F
         where
             F: Fn() -> R + Send + Sync,
-            R: IntoValue,
+            R: IntoHostResult,
         {
             type Output = R;
-            
+
             fn call(&self, _args: ()) -> PluginResult<Value> {
-                Ok(self().into_value())
+                self().into_host_result()
             }
         }
     };
-    
+
     // Recursive case: generate implementation for N arguments
     ($($arg:ident),+) => {
         impl<F, $($arg,)+ R> HostFunction<($($arg,)+)> for F
         where
             F: Fn($($arg,)+) -> R + Send + Sync,
-            $($arg: FromValue,)+
-            R: IntoValue,
+            $($arg: FromHostCall,)+
+            R: IntoHostResult,
         {
             type Output = R;
-            
+
             #[allow(non_snake_case)]
             fn call(&self, ($($arg,)+): ($($arg,)+)) -> PluginResult<Value> {
-                Ok(self($($arg,)+).into_value())
+                self($($arg,)+).into_host_result()
+            }
+        }
+    };
+}
+
+// Generate implementations for 0 to 32 arguments
+for_each_arity!(impl_host_function);
+
+/// Trait for async functions that can be used as host functions.
+/// This trait is implemented for functions with different arities that return a future.
+///
+/// Arguments are extracted before the function is called, so the returned future owns
+/// everything it needs and may be awaited after the call returns.
+#[cfg(feature = "async")]
+#[diagnostic::on_unimplemented(
+    message = "the function `{Self}` cannot be used as an async host function",
+    note = "ensure your function arguments implement `FromValue` (or `FromHostCall`, for extractors such as `State` and `Caller`) and it returns a `Send + 'static` future whose output implements `IntoHostResult`. Functions must be `Fn(...) -> Fut + Send + Sync`. Maximum 32 arguments supported; use `Rest<T>` or `Kwargs<T>` for more."
+)]
+pub trait AsyncHostFunction<Args>: Send + Sync {
+    /// The output of the future returned by the host function.
+    type Output: IntoHostResult;
+
+    /// Calls the host function with the provided arguments, returning its future.
+    fn call(&self, args: Args) -> BoxFuture<'static, PluginResult<Value>>;
+}
+
+/// Macro to generate AsyncHostFunction implementations for different arities.
+macro_rules! impl_async_host_function {
+    ($($arg:ident),*) => {
+        #[cfg(feature = "async")]
+        impl<F, Fut, $($arg,)*> AsyncHostFunction<($($arg,)*)> for F
+        where
+            F: Fn($($arg,)*) -> Fut + Send + Sync,
+            Fut: Future + Send + 'static,
+            Fut::Output: IntoHostResult,
+            $($arg: FromHostCall,)*
+        {
+            type Output = Fut::Output;
+
+            #[allow(non_snake_case)]
+            fn call(&self, ($($arg,)*): ($($arg,)*)) -> BoxFuture<'static, PluginResult<Value>> {
+                let future = self($($arg,)*);
+                Box::pin(async move { future.await.into_host_result() })
             }
         }
     };
 }
 
-// Generate implementations for 0 to 16 arguments
-impl_host_function!();
-impl_host_function!(A1);
-impl_host_function!(A1, A2);
-impl_host_function!(A1, A2, A3);
-impl_host_function!(A1, A2, A3, A4);
-impl_host_function!(A1, A2, A3, A4, A5);
-impl_host_function!(A1, A2, A3, A4, A5, A6);
-impl_host_function!(A1, A2, A3, A4, A5, A6, A7);
-impl_host_function!(A1, A2, A3, A4, A5, A6, A7, A8);
-impl_host_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9);
-impl_host_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10);
-impl_host_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11);
-impl_host_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12);
-impl_host_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13);
-impl_host_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14);
-impl_host_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15);
-impl_host_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15, A16);
\ No newline at end of file
+// Generate async implementations for 0 to 32 arguments
+for_each_arity!(impl_async_host_function);
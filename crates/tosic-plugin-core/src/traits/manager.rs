@@ -1,7 +1,91 @@
 //! Plugin manager trait for managing multiple plugins.
 
+use crate::PluginResult;
+use crate::traits::runtime::Plugin;
+use crate::types::{PluginInfo, Value};
+
 /// Trait for managing multiple plugin instances.
-/// 
-/// This trait provides a high-level interface for plugin lifecycle management.
-/// Implementations can handle plugin discovery, loading, unloading, and coordination.
-pub trait PluginManager: Send + Sync {}
\ No newline at end of file
+///
+/// This trait provides a high-level interface for plugin lifecycle management:
+/// plugins are loaded under a caller-chosen id, looked up and called by that id,
+/// and unloaded when no longer needed. [`DefaultPluginManager`](crate::DefaultPluginManager)
+/// implements it on top of any [`Runtime`](crate::Runtime).
+pub trait PluginManager: Send + Sync {
+    /// The plugin type this manager holds.
+    type Plugin: Plugin;
+
+    /// Loads a plugin from bytes and registers it under `id`.
+    ///
+    /// # Errors
+    /// Returns `PluginError::PluginAlreadyLoaded` if a plugin with this id is already
+    /// loaded, or the runtime's error if loading fails.
+    fn load(&mut self, id: &str, bytes: &[u8]) -> PluginResult<()>;
+
+    /// Returns the plugin loaded under `id`.
+    fn get(&self, id: &str) -> Option<&Self::Plugin>;
+
+    /// Returns the plugin reporting `name` as its name, preferring the lowest id if
+    /// several do.
+    fn get_by_name(&self, name: &str) -> Option<&Self::Plugin>;
+
+    /// Calls a function in the plugin loaded under `plugin_id`.
+    ///
+    /// # Errors
+    /// Returns `PluginError::PluginNotFound` if no plugin is loaded under `plugin_id`,
+    /// or the runtime's error if the call fails.
+    fn call(&self, plugin_id: &str, function_name: &str, args: &[Value]) -> PluginResult<Value>;
+
+    /// Unloads the plugin loaded under `id`, dropping it.
+    ///
+    /// # Errors
+    /// Returns `PluginError::PluginNotFound` if no plugin is loaded under `id`.
+    fn unload(&mut self, id: &str) -> PluginResult<()>;
+
+    /// Lists the loaded plugins, ordered by id.
+    fn plugins(&self) -> Vec<PluginInfo>;
+}
+
+/// Async counterpart of [`PluginManager`], loading and calling plugins through an async
+/// runtime. [`DefaultAsyncPluginManager`](crate::DefaultAsyncPluginManager) implements it
+/// on top of any [`AsyncRuntime`](crate::AsyncRuntime).
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+pub trait AsyncPluginManager: Send + Sync {
+    /// The plugin type this manager holds.
+    type Plugin: Plugin;
+
+    /// Loads a plugin from bytes and registers it under `id`.
+    ///
+    /// # Errors
+    /// Returns `PluginError::PluginAlreadyLoaded` if a plugin with this id is already
+    /// loaded, or the runtime's error if loading fails.
+    async fn load_async(&mut self, id: &str, bytes: &[u8]) -> PluginResult<()>;
+
+    /// Returns the plugin loaded under `id`.
+    fn get(&self, id: &str) -> Option<&Self::Plugin>;
+
+    /// Returns the plugin reporting `name` as its name, preferring the lowest id if
+    /// several do.
+    fn get_by_name(&self, name: &str) -> Option<&Self::Plugin>;
+
+    /// Calls a function in the plugin loaded under `plugin_id`.
+    ///
+    /// # Errors
+    /// Returns `PluginError::PluginNotFound` if no plugin is loaded under `plugin_id`,
+    /// or the runtime's error if the call fails.
+    async fn call_async(
+        &self,
+        plugin_id: &str,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value>;
+
+    /// Unloads the plugin loaded under `id`, dropping it.
+    ///
+    /// # Errors
+    /// Returns `PluginError::PluginNotFound` if no plugin is loaded under `id`.
+    fn unload(&mut self, id: &str) -> PluginResult<()>;
+
+    /// Lists the loaded plugins, ordered by id.
+    fn plugins(&self) -> Vec<PluginInfo>;
+}
@@ -1,5 +1,6 @@
 //! Core traits for plugin system abstractions.
 
-pub mod runtime;
+pub mod dynamic;
+pub mod host_function;
 pub mod manager;
-pub mod host_function;
\ No newline at end of file
+pub mod runtime;
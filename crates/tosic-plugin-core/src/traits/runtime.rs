@@ -1,7 +1,9 @@
 //! Runtime abstraction traits for plugin loading and execution.
 
-use crate::types::{HostContext, Value};
+use std::sync::Arc;
+
 use crate::PluginResult;
+use crate::types::{HostContext, Value};
 
 /// Opaque handle to a loaded plugin instance.
 /// This trait represents a loaded piece of plugin code that can be executed.
@@ -12,9 +14,20 @@ pub trait Plugin: Send + Sync {
     }
 }
 
+impl<P: Plugin + ?Sized> Plugin for Box<P> {
+    fn name(&self) -> Option<&str> {
+        (**self).name()
+    }
+}
+
+impl<P: Plugin + ?Sized> Plugin for Arc<P> {
+    fn name(&self) -> Option<&str> {
+        (**self).name()
+    }
+}
+
 /// Runtime abstraction for loading and executing plugins.
-/// This trait provides a dyn-compatible interface for different plugin runtimes.
-#[cfg(not(feature = "async"))]
+/// Runtimes with different plugin types can be held together through [`DynRuntime`](crate::DynRuntime).
 pub trait Runtime: Send + Sync {
     /// The specific plugin type this runtime creates.
     type Plugin: Plugin;
@@ -33,24 +46,63 @@ pub trait Runtime: Send + Sync {
     ) -> PluginResult<Value>;
 }
 
+impl<R: Runtime + ?Sized> Runtime for Box<R> {
+    type Plugin = R::Plugin;
+
+    fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        (**self).load(bytes, context)
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        (**self).call(plugin, function_name, args)
+    }
+}
+
+impl<R: Runtime + ?Sized> Runtime for Arc<R> {
+    type Plugin = R::Plugin;
+
+    fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        (**self).load(bytes, context)
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        (**self).call(plugin, function_name, args)
+    }
+}
+
 /// Async runtime abstraction for loading and executing plugins.
-/// This trait provides a dyn-compatible interface for different plugin runtimes.
+///
+/// This trait is the async counterpart of [`Runtime`], and a runtime may implement both.
+/// Its methods carry an `_async` suffix so that calls stay unambiguous when both traits
+/// are in scope.
+/// [`SpawnBlocking`](crate::SpawnBlocking) and [`BlockOn`](crate::BlockOn) adapt a runtime
+/// implementing only one of them to the other.
 #[cfg(feature = "async")]
 #[async_trait::async_trait]
-pub trait Runtime: Send + Sync {
+pub trait AsyncRuntime: Send + Sync {
     /// The specific plugin type this runtime creates.
     type Plugin: Plugin;
 
     /// Loads plugin code from bytes with the provided host context.
     /// Returns a plugin instance that can be used to call functions.
-    async fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin>;
+    async fn load_async(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin>;
 
     /// Calls a function in the loaded plugin with the given arguments.
     /// Returns the result value from the plugin function.
-    async fn call(
+    async fn call_async(
         &self,
         plugin: &Self::Plugin,
         function_name: &str,
         args: &[Value],
     ) -> PluginResult<Value>;
-}
\ No newline at end of file
+}
@@ -0,0 +1,119 @@
+//! Adapters between the sync [`Runtime`] and the [`AsyncRuntime`] traits.
+
+use std::sync::Arc;
+
+use crate::traits::runtime::{AsyncRuntime, Runtime};
+use crate::types::{HostContext, Value};
+use crate::{PluginError, PluginResult};
+
+/// Adapts a sync [`Runtime`] to [`AsyncRuntime`] by running every load and call on
+/// Tokio's blocking thread pool.
+///
+/// Plugins are shared with the blocking tasks, so the adapted plugin type is an
+/// [`Arc`] of the runtime's plugin. The returned futures must be polled within a Tokio
+/// runtime.
+pub struct SpawnBlocking<R> {
+    runtime: Arc<R>,
+}
+
+impl<R> SpawnBlocking<R> {
+    /// Wraps a sync runtime.
+    pub fn new(runtime: R) -> Self {
+        Self {
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    /// Returns the wrapped runtime.
+    pub fn inner(&self) -> &R {
+        &self.runtime
+    }
+}
+
+impl<R> Clone for SpawnBlocking<R> {
+    fn clone(&self) -> Self {
+        Self {
+            runtime: Arc::clone(&self.runtime),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<R: Runtime + 'static> AsyncRuntime for SpawnBlocking<R> {
+    type Plugin = Arc<R::Plugin>;
+
+    async fn load_async(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        let runtime = Arc::clone(&self.runtime);
+        let bytes = bytes.to_vec();
+        let context = context.clone();
+
+        tokio::task::spawn_blocking(move || runtime.load(&bytes, &context))
+            .await
+            .map_err(|e| join_error("loading the plugin", &e))?
+            .map(Arc::new)
+    }
+
+    async fn call_async(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        let runtime = Arc::clone(&self.runtime);
+        let plugin = Arc::clone(plugin);
+        let function = function_name.to_string();
+        let args = args.to_vec();
+
+        tokio::task::spawn_blocking(move || runtime.call(&plugin, &function, &args))
+            .await
+            .map_err(|e| join_error(&format!("calling '{function_name}'"), &e))?
+    }
+}
+
+/// Adapts an [`AsyncRuntime`] to the sync [`Runtime`] by blocking the calling thread on
+/// each future.
+///
+/// The futures are driven by a minimal executor, so this adapter must not be used from
+/// within an async task, and runtimes whose futures depend on a particular reactor (such
+/// as Tokio timers or I/O) need that reactor to be available to them.
+#[derive(Clone, Default)]
+pub struct BlockOn<R> {
+    runtime: R,
+}
+
+impl<R> BlockOn<R> {
+    /// Wraps an async runtime.
+    pub fn new(runtime: R) -> Self {
+        Self { runtime }
+    }
+
+    /// Returns the wrapped runtime.
+    pub fn inner(&self) -> &R {
+        &self.runtime
+    }
+}
+
+impl<R: AsyncRuntime> Runtime for BlockOn<R> {
+    type Plugin = R::Plugin;
+
+    fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        futures::executor::block_on(self.runtime.load_async(bytes, context))
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        futures::executor::block_on(self.runtime.call_async(plugin, function_name, args))
+    }
+}
+
+fn join_error(operation: &str, error: &tokio::task::JoinError) -> PluginError {
+    if error.is_panic() {
+        PluginError::RuntimeError(format!("runtime panicked while {operation}"))
+    } else {
+        PluginError::RuntimeError(format!("blocking task was cancelled while {operation}"))
+    }
+}
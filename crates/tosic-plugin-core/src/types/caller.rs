@@ -0,0 +1,129 @@
+//! Identity and storage of the plugin calling a host function.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use crate::traits::host_function::{FromHostCall, IntoValue};
+use crate::types::{HostCall, Value};
+use crate::{PluginError, PluginResult};
+
+/// The plugin calling a host function.
+///
+/// Taking a `Caller` parameter gives a host function the calling plugin's id and name,
+/// along with a key-value storage kept separately for each plugin. It does not consume a
+/// plugin argument and may appear anywhere in the parameter list.
+///
+/// Plugins loaded through a [`DefaultPluginManager`](crate::DefaultPluginManager) are given
+/// a caller automatically. Plugins loaded directly through a runtime need a context
+/// carrying one, created with [`HostContext::with_caller`](crate::HostContext::with_caller);
+/// calling a host function taking a `Caller` without one fails with
+/// `PluginError::HostError`.
+///
+/// Clones share the same storage.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> tosic_plugin_core::PluginResult<()> {
+/// use tosic_plugin_core::*;
+///
+/// let mut context = HostContext::new();
+/// context.register("count", |caller: Caller| {
+///     let count = caller.get("count").and_then(|value| value.as_int()).unwrap_or(0) + 1;
+///     caller.set("count", count);
+///     format!("{} called {} time(s)", caller.id(), count)
+/// });
+///
+/// let context = context.with_caller(Caller::new("counter"));
+/// context.call_function("count", &[])?;
+/// assert_eq!(context.call_function("count", &[])?, Value::from("counter called 2 time(s)"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Caller {
+    inner: Arc<CallerInner>,
+}
+
+struct CallerInner {
+    id: String,
+    name: OnceLock<String>,
+    storage: Mutex<HashMap<String, Value>>,
+}
+
+impl Caller {
+    /// Creates a caller for the plugin with the given id and empty storage.
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            inner: Arc::new(CallerInner {
+                id: id.into(),
+                name: OnceLock::new(),
+                storage: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Sets the name reported by the calling plugin.
+    #[must_use]
+    pub fn with_name(self, name: impl Into<String>) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    /// Returns the id the calling plugin was loaded under.
+    pub fn id(&self) -> &str {
+        &self.inner.id
+    }
+
+    /// Returns the name reported by the calling plugin, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.inner.name.get().map(String::as_str)
+    }
+
+    /// Returns a copy of the value stored under `key` for this plugin.
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.storage().get(key).cloned()
+    }
+
+    /// Stores a value under `key` for this plugin, returning the value it replaces.
+    pub fn set(&self, key: impl Into<String>, value: impl IntoValue) -> Option<Value> {
+        self.storage().insert(key.into(), value.into_value())
+    }
+
+    /// Removes the value stored under `key` for this plugin, returning it.
+    pub fn remove(&self, key: &str) -> Option<Value> {
+        self.storage().remove(key)
+    }
+
+    /// Sets the caller's name unless it already has one.
+    pub(crate) fn set_name(&self, name: impl Into<String>) {
+        let _ = self.inner.name.set(name.into());
+    }
+
+    fn storage(&self) -> std::sync::MutexGuard<'_, HashMap<String, Value>> {
+        self.inner
+            .storage
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for Caller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Caller")
+            .field("id", &self.id())
+            .field("name", &self.name())
+            .finish_non_exhaustive()
+    }
+}
+
+impl FromHostCall for Caller {
+    fn from_host_call(call: &mut HostCall<'_>) -> PluginResult<Self> {
+        call.caller().cloned().ok_or_else(|| {
+            PluginError::HostError(
+                "host function requires a calling plugin, but none was provided".to_string(),
+            )
+        })
+    }
+}
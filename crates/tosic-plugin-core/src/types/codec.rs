@@ -0,0 +1,373 @@
+//! Binary encoding of [`Value`] for runtimes that exchange raw bytes with plugins.
+//!
+//! The format is a compact, little-endian, tag-prefixed encoding:
+//!
+//! | Tag | Variant  | Payload                                              |
+//! |-----|----------|------------------------------------------------------|
+//! | 0   | `Null`   | none                                                 |
+//! | 1   | `Bool`   | 1 byte, `0` or `1`                                   |
+//! | 2   | `Int`    | 8 bytes, `i64`                                       |
+//! | 3   | `Float`  | 8 bytes, `f64` bits                                  |
+//! | 4   | `String` | `u32` length followed by UTF-8 bytes                 |
+//! | 5   | `Bytes`  | `u32` length followed by raw bytes                   |
+//! | 6   | `Array`  | `u32` count followed by `count` encoded values       |
+//! | 7   | `Object` | `u32` count followed by `count` (`u32` key length, key bytes, encoded value) entries |
+//!
+//! Objects are encoded in the insertion order of their keys, which decoding preserves. The
+//! [canonical encoding](Value::encode_canonical) sorts them by key instead, so that equal
+//! values always encode to the same bytes.
+//!
+//! Decoding accepts arrays and objects nested at most 128 levels deep, so that
+//! buffers written by a plugin cannot exhaust the host's stack.
+
+use crate::types::{Map, Value, sorted_entries};
+use crate::{PluginError, PluginResult};
+
+const TAG_NULL: u8 = 0;
+const TAG_BOOL: u8 = 1;
+const TAG_INT: u8 = 2;
+const TAG_FLOAT: u8 = 3;
+const TAG_STRING: u8 = 4;
+const TAG_BYTES: u8 = 5;
+const TAG_ARRAY: u8 = 6;
+const TAG_OBJECT: u8 = 7;
+
+/// Maximum nesting of arrays and objects accepted when decoding a value.
+const MAX_DEPTH: usize = 128;
+
+impl Value {
+    /// Encodes the value into its binary representation.
+    ///
+    /// # Panics
+    /// Panics if a string, byte buffer, array or object in the value has more than
+    /// `u32::MAX` elements, which the format cannot represent.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_into(&mut out);
+        out
+    }
+
+    /// Appends the binary representation of the value to `out`.
+    ///
+    /// # Panics
+    /// Panics if a string, byte buffer, array or object in the value has more than
+    /// `u32::MAX` elements, which the format cannot represent.
+    pub fn encode_into(&self, out: &mut Vec<u8>) {
+        self.encode_with(out, false);
+    }
+
+    /// Encodes the value into its canonical binary representation, in which the keys of
+    /// every object are sorted.
+    ///
+    /// Two values have the same canonical encoding if and only if they are equal, which
+    /// makes it suitable for hashing the content of values, e.g. to cache results or
+    /// deduplicate messages. It decodes like any other encoding.
+    ///
+    /// # Panics
+    /// Panics if a string, byte buffer, array or object in the value has more than
+    /// `u32::MAX` elements, which the format cannot represent.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tosic_plugin_core::*;
+    ///
+    /// let a = Value::Object(Map::from([("x".to_string(), Value::Int(1)), ("y".to_string(), Value::Int(2))]));
+    /// let b = Value::Object(Map::from([("y".to_string(), Value::Int(2)), ("x".to_string(), Value::Int(1))]));
+    /// assert_ne!(a.encode(), b.encode());
+    /// assert_eq!(a.encode_canonical(), b.encode_canonical());
+    /// ```
+    pub fn encode_canonical(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_canonical_into(&mut out);
+        out
+    }
+
+    /// Appends the canonical binary representation of the value to `out`.
+    ///
+    /// # Panics
+    /// Panics if a string, byte buffer, array or object in the value has more than
+    /// `u32::MAX` elements, which the format cannot represent.
+    pub fn encode_canonical_into(&self, out: &mut Vec<u8>) {
+        self.encode_with(out, true);
+    }
+
+    fn encode_with(&self, out: &mut Vec<u8>, canonical: bool) {
+        match self {
+            Value::Null => out.push(TAG_NULL),
+            Value::Bool(b) => {
+                out.push(TAG_BOOL);
+                out.push(u8::from(*b));
+            }
+            Value::Int(i) => {
+                out.push(TAG_INT);
+                out.extend_from_slice(&i.to_le_bytes());
+            }
+            Value::Float(f) => {
+                out.push(TAG_FLOAT);
+                out.extend_from_slice(&f.to_bits().to_le_bytes());
+            }
+            Value::String(s) => {
+                out.push(TAG_STRING);
+                encode_bytes(s.as_bytes(), out);
+            }
+            Value::Bytes(b) => {
+                out.push(TAG_BYTES);
+                encode_bytes(b, out);
+            }
+            Value::Array(items) => {
+                out.push(TAG_ARRAY);
+                encode_len(items.len(), out);
+                for item in items {
+                    item.encode_with(out, canonical);
+                }
+            }
+            Value::Object(map) => {
+                out.push(TAG_OBJECT);
+                encode_len(map.len(), out);
+                let entries = if canonical {
+                    sorted_entries(map)
+                } else {
+                    map.iter().collect()
+                };
+                for (key, value) in entries {
+                    encode_bytes(key.as_bytes(), out);
+                    value.encode_with(out, canonical);
+                }
+            }
+        }
+    }
+
+    /// Decodes a value from its binary representation.
+    ///
+    /// # Errors
+    /// Returns `PluginError::EncodingError` if the bytes are not a single well-formed value,
+    /// or if its arrays and objects are nested more than 128 levels deep.
+    pub fn decode(bytes: &[u8]) -> PluginResult<Value> {
+        let mut reader = Reader {
+            bytes,
+            pos: 0,
+            depth: 0,
+        };
+        let value = reader.value()?;
+        if reader.pos != bytes.len() {
+            return Err(PluginError::EncodingError(format!(
+                "{} trailing bytes after value",
+                bytes.len() - reader.pos
+            )));
+        }
+        Ok(value)
+    }
+}
+
+fn encode_len(len: usize, out: &mut Vec<u8>) {
+    let len = u32::try_from(len).expect("encoded length exceeds u32::MAX");
+    out.extend_from_slice(&len.to_le_bytes());
+}
+
+fn encode_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    encode_len(bytes.len(), out);
+    out.extend_from_slice(bytes);
+}
+
+/// Cursor over an encoded buffer.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// Number of arrays and objects enclosing the value being read.
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> PluginResult<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|end| *end <= self.bytes.len());
+        match end {
+            Some(end) => {
+                let slice = &self.bytes[self.pos..end];
+                self.pos = end;
+                Ok(slice)
+            }
+            None => Err(PluginError::EncodingError(
+                "unexpected end of input".to_string(),
+            )),
+        }
+    }
+
+    fn array<const N: usize>(&mut self) -> PluginResult<[u8; N]> {
+        let mut buf = [0; N];
+        buf.copy_from_slice(self.take(N)?);
+        Ok(buf)
+    }
+
+    fn len(&mut self) -> PluginResult<usize> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+
+    fn string(&mut self) -> PluginResult<String> {
+        let len = self.len()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|e| PluginError::EncodingError(format!("invalid UTF-8 string: {e}")))
+    }
+
+    fn value(&mut self) -> PluginResult<Value> {
+        let [tag] = self.array()?;
+        match tag {
+            TAG_NULL => Ok(Value::Null),
+            TAG_BOOL => match self.array()? {
+                [0] => Ok(Value::Bool(false)),
+                [1] => Ok(Value::Bool(true)),
+                [b] => Err(PluginError::EncodingError(format!("invalid bool byte {b}"))),
+            },
+            TAG_INT => Ok(Value::Int(i64::from_le_bytes(self.array()?))),
+            TAG_FLOAT => Ok(Value::Float(f64::from_bits(u64::from_le_bytes(
+                self.array()?,
+            )))),
+            TAG_STRING => Ok(Value::String(self.string()?)),
+            TAG_BYTES => {
+                let len = self.len()?;
+                Ok(Value::Bytes(self.take(len)?.to_vec()))
+            }
+            TAG_ARRAY | TAG_OBJECT => {
+                if self.depth >= MAX_DEPTH {
+                    return Err(PluginError::EncodingError(format!(
+                        "value nesting exceeds {MAX_DEPTH} levels"
+                    )));
+                }
+                self.depth += 1;
+                let value = if tag == TAG_ARRAY {
+                    self.array_items()
+                } else {
+                    self.object_entries()
+                };
+                self.depth -= 1;
+                value
+            }
+            tag => Err(PluginError::EncodingError(format!(
+                "unknown value tag {tag}"
+            ))),
+        }
+    }
+
+    fn array_items(&mut self) -> PluginResult<Value> {
+        let count = self.len()?;
+        // Every element takes at least one byte, which bounds the allocation.
+        let mut items = Vec::with_capacity(count.min(self.bytes.len() - self.pos));
+        for _ in 0..count {
+            items.push(self.value()?);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn object_entries(&mut self) -> PluginResult<Value> {
+        let count = self.len()?;
+        let mut map = Map::with_capacity(count.min(self.bytes.len() - self.pos));
+        for _ in 0..count {
+            let key = self.string()?;
+            let value = self.value()?;
+            map.insert(key, value);
+        }
+        Ok(Value::Object(map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Value {
+        Value::Object(Map::from([
+            ("null".to_string(), Value::Null),
+            ("bool".to_string(), Value::Bool(true)),
+            ("int".to_string(), Value::Int(i64::MIN)),
+            ("float".to_string(), Value::Float(-0.0)),
+            ("string".to_string(), Value::from("héllo")),
+            ("bytes".to_string(), Value::Bytes(vec![0, 255])),
+            (
+                "array".to_string(),
+                Value::Array(vec![Value::Int(1), Value::Array(Vec::new())]),
+            ),
+        ]))
+    }
+
+    #[test]
+    fn round_trips_every_variant() {
+        let value = sample();
+        let decoded = Value::decode(&value.encode()).unwrap();
+        assert_eq!(decoded, value);
+        let keys: Vec<&String> = decoded.as_object().unwrap().keys().collect();
+        assert_eq!(
+            keys,
+            ["null", "bool", "int", "float", "string", "bytes", "array"]
+        );
+    }
+
+    #[test]
+    fn canonical_encoding_sorts_keys_and_decodes() {
+        let value = sample();
+        let mut reversed = value.as_object().unwrap().clone();
+        reversed.reverse();
+        let reversed = Value::Object(reversed);
+
+        assert_ne!(value.encode(), reversed.encode());
+        assert_eq!(value.encode_canonical(), reversed.encode_canonical());
+        assert_eq!(Value::decode(&value.encode_canonical()).unwrap(), value);
+        assert_ne!(
+            Value::Float(0.0).encode_canonical(),
+            Value::Float(-0.0).encode_canonical()
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let encoded = sample().encode();
+        for len in 0..encoded.len() {
+            assert!(
+                matches!(
+                    Value::decode(&encoded[..len]),
+                    Err(PluginError::EncodingError(_))
+                ),
+                "decoded a value from {len} of {} bytes",
+                encoded.len()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let cases: &[&[u8]] = &[
+            &[TAG_BOOL, 2],
+            &[42],
+            &[TAG_STRING, 2, 0, 0, 0, 0xff, 0xfe],
+            &[TAG_NULL, TAG_NULL],
+            &[TAG_ARRAY, 0xff, 0xff, 0xff, 0xff],
+        ];
+        for bytes in cases {
+            assert!(
+                matches!(Value::decode(bytes), Err(PluginError::EncodingError(_))),
+                "decoded {bytes:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn limits_nesting_depth() {
+        let nested = |depth: usize| {
+            let mut bytes: Vec<u8> = [TAG_ARRAY, 1, 0, 0, 0].repeat(depth);
+            bytes.push(TAG_NULL);
+            bytes
+        };
+        assert!(Value::decode(&nested(MAX_DEPTH)).is_ok());
+        assert!(matches!(
+            Value::decode(&nested(MAX_DEPTH + 1)),
+            Err(PluginError::EncodingError(_))
+        ));
+        assert!(matches!(
+            Value::decode(&nested(1_000_000)),
+            Err(PluginError::EncodingError(_))
+        ));
+    }
+}
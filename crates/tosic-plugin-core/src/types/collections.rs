@@ -0,0 +1,571 @@
+//! Conversions between containers and plugin Values.
+//!
+//! Sequences, sets, fixed-size arrays and tuples are represented by [`Value::Array`], and
+//! maps with string keys by [`Value::Object`]. Hash sets and hash maps have no meaningful
+//! order, so their items and keys are sorted to produce the same value on every run; other
+//! containers keep their order. Binary data is carried by [`Value::Bytes`] through the
+//! [`Bytes`] newtype, so a `Vec<u8>` is a sequence like any other: it converts into an
+//! array of integers, and from either an array or bytes. Only sequences of integers accept
+//! bytes.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::{BuildHasher, Hash};
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+
+use crate::traits::host_function::{FromValue, IntoValue};
+use crate::types::{Map, Value, ValueKind};
+use crate::{PluginError, PluginResult};
+
+/// Binary data, represented by [`Value::Bytes`].
+///
+/// Use it instead of `Vec<u8>` for host function arguments and return values carrying raw
+/// bytes; a `Vec<u8>` converts into an array of integers.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> tosic_plugin_core::PluginResult<()> {
+/// use tosic_plugin_core::*;
+///
+/// let mut context = HostContext::new();
+/// context.register("reverse", |Bytes(mut bytes): Bytes| {
+///     bytes.reverse();
+///     Bytes(bytes)
+/// });
+///
+/// let result = context.call_function("reverse", &[Value::Bytes(vec![1, 2, 3])])?;
+/// assert_eq!(result, Value::Bytes(vec![3, 2, 1]));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bytes(pub Vec<u8>);
+
+impl From<Vec<u8>> for Bytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(bytes: &[u8]) -> Self {
+        Bytes(bytes.to_vec())
+    }
+}
+
+impl From<Bytes> for Value {
+    fn from(bytes: Bytes) -> Self {
+        Value::Bytes(bytes.0)
+    }
+}
+
+impl FromValue for Bytes {
+    fn from_value(value: &Value) -> PluginResult<Self> {
+        match value {
+            Value::Bytes(bytes) => Ok(Bytes(bytes.clone())),
+            _ => Err(PluginError::type_mismatch("Bytes", value)),
+        }
+    }
+
+    fn expected_kind() -> ValueKind {
+        ValueKind::Bytes
+    }
+}
+
+impl IntoValue for Bytes {
+    fn into_value(self) -> Value {
+        Value::Bytes(self.0)
+    }
+
+    fn produced_kind() -> ValueKind {
+        ValueKind::Bytes
+    }
+}
+
+fn item_error(expected: &str, index: usize, error: &PluginError) -> PluginError {
+    PluginError::ConversionError(format!(
+        "invalid item {index} of `{expected}`: {}",
+        error.detail()
+    ))
+}
+
+fn length_error(expected: &str, len: usize, found: usize) -> PluginError {
+    PluginError::ConversionError(format!(
+        "expected {len} items for `{expected}`, found {found}"
+    ))
+}
+
+/// Returns the items of a sequence of `T`, treating the bytes of a `Value::Bytes` as
+/// integers if `T` is converted from integers.
+fn items<'a, T: FromValue>(value: &'a Value, expected: &str) -> PluginResult<Cow<'a, [Value]>> {
+    match value {
+        Value::Array(items) => Ok(Cow::Borrowed(items)),
+        Value::Bytes(bytes) if T::expected_kind() == ValueKind::Int => Ok(Cow::Owned(
+            bytes
+                .iter()
+                .map(|byte| Value::Int(i64::from(*byte)))
+                .collect(),
+        )),
+        _ => Err(PluginError::type_mismatch(expected, value)),
+    }
+}
+
+/// Converts every item of a sequence, naming the item that failed.
+fn collect_items<T: FromValue, C: FromIterator<T>>(
+    value: &Value,
+    expected: &str,
+) -> PluginResult<C> {
+    items::<T>(value, expected)?
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            T::from_value(item).map_err(|error| item_error(expected, index, &error))
+        })
+        .collect()
+}
+
+/// Converts every entry of an object, naming the entry that failed.
+fn collect_entries<T: FromValue, C: FromIterator<(String, T)>>(
+    value: &Value,
+    expected: &str,
+) -> PluginResult<C> {
+    let Value::Object(object) = value else {
+        return Err(PluginError::type_mismatch(expected, value));
+    };
+    object
+        .iter()
+        .map(|(key, item)| {
+            let item = T::from_value(item).map_err(|error| {
+                PluginError::ConversionError(format!(
+                    "invalid entry `{key}` of `{expected}`: {}",
+                    error.detail()
+                ))
+            })?;
+            Ok((key.clone(), item))
+        })
+        .collect()
+}
+
+/// `None` is represented by [`Value::Null`], and `Some` by its content. As a trailing host
+/// function parameter, an omitted argument is `None`.
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> PluginResult<Self> {
+        match value {
+            Value::Null => Ok(None),
+            other => T::from_value(other).map(Some),
+        }
+    }
+
+    fn expected_kind() -> ValueKind {
+        T::expected_kind()
+    }
+
+    fn default_value() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Value {
+        self.map_or(Value::Null, IntoValue::into_value)
+    }
+
+    fn produced_kind() -> ValueKind {
+        T::produced_kind()
+    }
+}
+
+/// Host function parameter that takes its default value when the plugin omits the
+/// argument or passes [`Value::Null`].
+///
+/// Like `Option<T>`, it lets host functions gain trailing parameters without breaking
+/// plugins written against an older signature.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> tosic_plugin_core::PluginResult<()> {
+/// use tosic_plugin_core::*;
+///
+/// let mut context = HostContext::new();
+/// context.register("pad", |text: String, OrDefault(width): OrDefault<usize>| {
+///     format!("{text:>width$}")
+/// });
+///
+/// assert_eq!(context.call_function("pad", &[Value::from("a"), Value::Int(3)])?, Value::from("  a"));
+/// assert_eq!(context.call_function("pad", &[Value::from("a")])?, Value::from("a"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OrDefault<T>(pub T);
+
+impl<T: FromValue + Default> FromValue for OrDefault<T> {
+    fn from_value(value: &Value) -> PluginResult<Self> {
+        match value {
+            Value::Null => Ok(OrDefault(T::default())),
+            other => T::from_value(other).map(OrDefault),
+        }
+    }
+
+    fn expected_kind() -> ValueKind {
+        T::expected_kind()
+    }
+
+    fn default_value() -> Option<Self> {
+        Some(OrDefault(T::default()))
+    }
+}
+
+impl<T: IntoValue> IntoValue for OrDefault<T> {
+    fn into_value(self) -> Value {
+        self.0.into_value()
+    }
+
+    fn produced_kind() -> ValueKind {
+        T::produced_kind()
+    }
+}
+
+/// Implements the conversions of sequences, which are represented by `Value::Array`.
+macro_rules! impl_sequence {
+    ($($ty:ident as $name:literal),*) => {$(
+        impl<T: FromValue> FromValue for $ty<T> {
+            fn from_value(value: &Value) -> PluginResult<Self> {
+                collect_items(value, $name)
+            }
+
+            fn expected_kind() -> ValueKind {
+                ValueKind::Array
+            }
+        }
+
+        impl<T: IntoValue> IntoValue for $ty<T> {
+            fn into_value(self) -> Value {
+                Value::Array(self.into_iter().map(IntoValue::into_value).collect())
+            }
+
+            fn produced_kind() -> ValueKind {
+                ValueKind::Array
+            }
+        }
+    )*};
+}
+
+impl_sequence!(Vec as "Vec<_>", VecDeque as "VecDeque<_>");
+
+impl<T: FromValue + Eq + Hash, S: BuildHasher + Default> FromValue for HashSet<T, S> {
+    fn from_value(value: &Value) -> PluginResult<Self> {
+        collect_items(value, "HashSet<_>")
+    }
+
+    fn expected_kind() -> ValueKind {
+        ValueKind::Array
+    }
+}
+
+/// Items are sorted, since a `HashSet` has no meaningful order.
+impl<T: IntoValue, S> IntoValue for HashSet<T, S> {
+    fn into_value(self) -> Value {
+        let mut items: Vec<Value> = self.into_iter().map(IntoValue::into_value).collect();
+        items.sort_unstable();
+        Value::Array(items)
+    }
+
+    fn produced_kind() -> ValueKind {
+        ValueKind::Array
+    }
+}
+
+/// Implements the conversions of maps with string keys, which are represented by `Value::Object`.
+/// Maps without a meaningful order of their own have their keys sorted.
+macro_rules! impl_map {
+    ($($ty:ident<String, T $(, $hasher:ident)?> as $name:literal, sort_keys = $sort_keys:literal);*) => {$(
+        impl<T: FromValue $(, $hasher: BuildHasher + Default)?> FromValue for $ty<String, T $(, $hasher)?> {
+            fn from_value(value: &Value) -> PluginResult<Self> {
+                collect_entries(value, $name)
+            }
+
+            fn expected_kind() -> ValueKind {
+                ValueKind::Object
+            }
+        }
+
+        impl<T: IntoValue $(, $hasher)?> IntoValue for $ty<String, T $(, $hasher)?> {
+            fn into_value(self) -> Value {
+                let mut map: Map = self.into_iter().map(|(key, item)| (key, item.into_value())).collect();
+                if $sort_keys {
+                    map.sort_unstable_keys();
+                }
+                Value::Object(map)
+            }
+
+            fn produced_kind() -> ValueKind {
+                ValueKind::Object
+            }
+        }
+    )*};
+}
+
+impl_map!(
+    HashMap<String, T, S> as "HashMap<String, _>", sort_keys = true;
+    BTreeMap<String, T> as "BTreeMap<String, _>", sort_keys = false;
+    IndexMap<String, T, S> as "IndexMap<String, _>", sort_keys = false
+);
+
+impl<T: FromValue, const N: usize> FromValue for [T; N] {
+    fn from_value(value: &Value) -> PluginResult<Self> {
+        let expected = format!("[_; {N}]");
+        let items: Vec<T> = collect_items(value, &expected)?;
+        let found = items.len();
+        items
+            .try_into()
+            .map_err(|_| length_error(&expected, N, found))
+    }
+
+    fn expected_kind() -> ValueKind {
+        ValueKind::Array
+    }
+}
+
+impl<T: IntoValue, const N: usize> IntoValue for [T; N] {
+    fn into_value(self) -> Value {
+        Value::Array(self.into_iter().map(IntoValue::into_value).collect())
+    }
+
+    fn produced_kind() -> ValueKind {
+        ValueKind::Array
+    }
+}
+
+/// Implements the conversions of tuples, which are represented by `Value::Array`.
+macro_rules! impl_tuple {
+    ($len:literal => $($name:ident $index:tt),+) => {
+        impl<$($name: FromValue),+> FromValue for ($($name,)+) {
+            fn from_value(value: &Value) -> PluginResult<Self> {
+                let Value::Array(items) = value else {
+                    return Err(PluginError::type_mismatch("tuple", value));
+                };
+                if items.len() != $len {
+                    return Err(length_error("tuple", $len, items.len()));
+                }
+                Ok(($(
+                    $name::from_value(&items[$index]).map_err(|error| item_error("tuple", $index, &error))?,
+                )+))
+            }
+
+            fn expected_kind() -> ValueKind {
+                ValueKind::Array
+            }
+        }
+
+        impl<$($name: IntoValue),+> IntoValue for ($($name,)+) {
+            fn into_value(self) -> Value {
+                Value::Array(vec![$(self.$index.into_value()),+])
+            }
+
+            fn produced_kind() -> ValueKind {
+                ValueKind::Array
+            }
+        }
+    };
+}
+
+impl_tuple!(1 => A 0);
+impl_tuple!(2 => A 0, B 1);
+impl_tuple!(3 => A 0, B 1, C 2);
+impl_tuple!(4 => A 0, B 1, C 2, D 3);
+impl_tuple!(5 => A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(6 => A 0, B 1, C 2, D 3, E 4, F 5);
+impl_tuple!(7 => A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_tuple!(8 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_tuple!(9 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_tuple!(10 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_tuple!(11 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_tuple!(12 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+impl<T: FromValue> FromValue for Box<T> {
+    fn from_value(value: &Value) -> PluginResult<Self> {
+        T::from_value(value).map(Box::new)
+    }
+
+    fn expected_kind() -> ValueKind {
+        T::expected_kind()
+    }
+}
+
+impl<T: IntoValue> IntoValue for Box<T> {
+    fn into_value(self) -> Value {
+        (*self).into_value()
+    }
+
+    fn produced_kind() -> ValueKind {
+        T::produced_kind()
+    }
+}
+
+impl<T: FromValue> FromValue for Arc<T> {
+    fn from_value(value: &Value) -> PluginResult<Self> {
+        T::from_value(value).map(Arc::new)
+    }
+
+    fn expected_kind() -> ValueKind {
+        T::expected_kind()
+    }
+}
+
+/// Converts the shared value, cloning it unless this is the last reference.
+impl<T: IntoValue + Clone> IntoValue for Arc<T> {
+    fn into_value(self) -> Value {
+        Arc::unwrap_or_clone(self).into_value()
+    }
+
+    fn produced_kind() -> ValueKind {
+        T::produced_kind()
+    }
+}
+
+impl FromValue for Cow<'_, str> {
+    fn from_value(value: &Value) -> PluginResult<Self> {
+        String::from_value(value).map(Cow::Owned)
+    }
+
+    fn expected_kind() -> ValueKind {
+        ValueKind::String
+    }
+}
+
+impl IntoValue for Cow<'_, str> {
+    fn into_value(self) -> Value {
+        Value::String(self.into_owned())
+    }
+
+    fn produced_kind() -> ValueKind {
+        ValueKind::String
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{BuildHasherDefault, DefaultHasher};
+
+    use super::*;
+
+    type CustomHasher = BuildHasherDefault<DefaultHasher>;
+
+    #[test]
+    fn bytes_convert_through_value_bytes() {
+        let value = Bytes(vec![1, 2, 3]).into_value();
+        assert_eq!(value, Value::Bytes(vec![1, 2, 3]));
+        assert_eq!(Value::from(Bytes::from(&[1, 2, 3][..])), value);
+        assert_eq!(Bytes::from_value(&value).unwrap(), Bytes(vec![1, 2, 3]));
+        assert!(Bytes::from_value(&Value::Array(vec![Value::Int(1)])).is_err());
+    }
+
+    #[test]
+    fn byte_vectors_are_integer_sequences() {
+        assert_eq!(
+            vec![1u8, 2].into_value(),
+            Value::Array(vec![Value::Int(1), Value::Int(2)])
+        );
+        assert_eq!(
+            Vec::<u8>::from_value(&Value::Bytes(vec![1, 2])).unwrap(),
+            [1, 2]
+        );
+        assert_eq!(
+            Vec::<i64>::from_value(&Value::Bytes(vec![255])).unwrap(),
+            [255]
+        );
+        assert!(Vec::<String>::from_value(&Value::Bytes(vec![1])).is_err());
+        assert!(<(u8, u8)>::from_value(&Value::Bytes(vec![1, 2])).is_err());
+    }
+
+    #[test]
+    fn host_functions_return_byte_vectors_as_arrays() {
+        let mut context = crate::HostContext::new();
+        context.register("vec", || vec![1u8, 2]);
+        context.register("bytes", || Bytes::from(&[1, 2][..]));
+
+        assert_eq!(
+            context.signature("vec").unwrap().returns(),
+            ValueKind::Array
+        );
+        assert_eq!(
+            context.call_function("vec", &[]).unwrap(),
+            Value::Array(vec![Value::Int(1), Value::Int(2)])
+        );
+        assert_eq!(
+            context.signature("bytes").unwrap().returns(),
+            ValueKind::Bytes
+        );
+        assert_eq!(
+            context.call_function("bytes", &[]).unwrap(),
+            Value::Bytes(vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn sequences_name_the_failing_item() {
+        let value = Value::Array(vec![Value::Int(1), Value::from("two")]);
+        let error = Vec::<i64>::from_value(&value).unwrap_err().to_string();
+        assert!(error.contains("invalid item 1 of `Vec<_>`"), "{error}");
+    }
+
+    #[test]
+    fn hash_containers_convert_in_sorted_order() {
+        let set: HashSet<i64> = (0..32).rev().collect();
+        let expected: Vec<Value> = (0..32).map(Value::Int).collect();
+        assert_eq!(set.into_value(), Value::Array(expected));
+
+        let map: HashMap<String, i64> = (0..32).map(|i| (format!("{i:02}"), i)).collect();
+        let value = map.into_value();
+        let keys: Vec<&String> = value.as_object().unwrap().keys().collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+    }
+
+    #[test]
+    fn hash_containers_accept_custom_hashers() {
+        let value = Value::Object(Map::from([("a".to_string(), Value::Int(1))]));
+        let map = HashMap::<String, i64, CustomHasher>::from_value(&value).unwrap();
+        assert_eq!(map["a"], 1);
+        assert_eq!(map.into_value(), value);
+
+        let set =
+            HashSet::<i64, CustomHasher>::from_value(&Value::Array(vec![Value::Int(1)])).unwrap();
+        assert!(set.contains(&1));
+    }
+
+    #[test]
+    fn maps_keep_their_order() {
+        let map: IndexMap<String, i64> = [("b".to_string(), 1), ("a".to_string(), 2)].into();
+        let value = map.clone().into_value();
+        assert_eq!(value.as_object().unwrap().keys().next().unwrap(), "b");
+        assert_eq!(IndexMap::<String, i64>::from_value(&value).unwrap(), map);
+
+        let map: BTreeMap<String, i64> = [("b".to_string(), 1), ("a".to_string(), 2)].into();
+        assert_eq!(
+            BTreeMap::<String, i64>::from_value(&map.clone().into_value()).unwrap(),
+            map
+        );
+        assert!(BTreeMap::<String, i64>::from_value(&Value::Array(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn arrays_tuples_and_options_convert() {
+        let value = Value::Array(vec![Value::Int(1), Value::Int(2)]);
+        assert_eq!(<[i64; 2]>::from_value(&value).unwrap(), [1, 2]);
+        assert!(<[i64; 3]>::from_value(&value).is_err());
+        let pair = Value::Array(vec![Value::Int(1), Value::from("a")]);
+        assert_eq!(
+            <(i64, String)>::from_value(&pair).unwrap(),
+            (1, "a".to_string())
+        );
+        assert_eq!(Option::<i64>::from_value(&Value::Null).unwrap(), None);
+        assert_eq!(Some(3i64).into_value(), Value::Int(3));
+    }
+}
@@ -1,19 +1,102 @@
 //! Host context for plugin function registration.
 
-use std::collections::HashMap;
 use crate::PluginResult;
-use crate::types::Value;
+use crate::types::{Caller, HostFunctionMetadata, Signature, State, Value};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
 
-use crate::traits::host_function::HostFunction;
+#[cfg(feature = "async")]
+use crate::traits::host_function::AsyncHostFunction;
+use crate::traits::host_function::{HostFunction, IntoHostResult};
+#[cfg(feature = "async")]
+use futures::future::BoxFuture;
 
 /// Type-erased host function that can be stored in the context.
-type BoxedHostFunction = Box<dyn Fn(&[Value]) -> PluginResult<Value> + Send + Sync>;
+type SharedHostFunction = Arc<dyn Fn(&mut HostCall<'_>) -> PluginResult<Value> + Send + Sync>;
+
+/// Type-erased async host function that can be stored in the context.
+#[cfg(feature = "async")]
+type SharedAsyncHostFunction =
+    Arc<dyn Fn(&mut HostCall<'_>) -> BoxFuture<'static, PluginResult<Value>> + Send + Sync>;
+
+/// Executor driving async host functions to completion for [`HostContext::call_function`].
+#[cfg(feature = "async")]
+type SharedBlockOn =
+    Arc<dyn Fn(BoxFuture<'static, PluginResult<Value>>) -> PluginResult<Value> + Send + Sync>;
+
+/// Application state shared with host functions, keyed by its type.
+type SharedState = Arc<dyn Any + Send + Sync>;
+
+/// A type-erased sync or async host function.
+#[derive(Clone)]
+enum RegisteredFunction {
+    Sync(SharedHostFunction),
+    #[cfg(feature = "async")]
+    Async(SharedAsyncHostFunction),
+}
+
+/// A host function registered in a [`HostContext`], with its signature and its metadata
+/// if any was given.
+#[derive(Clone)]
+struct Registration {
+    function: RegisteredFunction,
+    signature: Arc<Signature>,
+    metadata: Option<Arc<HostFunctionMetadata>>,
+}
+
+/// Builds the signature of a host function taking `Args` and returning `R`.
+fn signature<Args: ExtractArgs, R: IntoHostResult>() -> Signature {
+    let mut signature = Signature::new(R::return_kind());
+    Args::describe(&mut signature);
+    signature
+}
+
+impl RegisteredFunction {
+    fn sync<Args, F>(func: F) -> Self
+    where
+        F: HostFunction<Args> + 'static,
+        Args: ExtractArgs,
+    {
+        Self::Sync(Arc::new(
+            move |call: &mut HostCall<'_>| -> PluginResult<Value> {
+                let extracted_args = Args::extract_args(call)?;
+                func.call(extracted_args)
+            },
+        ))
+    }
+
+    #[cfg(feature = "async")]
+    fn r#async<Args, F>(func: F) -> Self
+    where
+        F: AsyncHostFunction<Args> + 'static,
+        Args: ExtractArgs,
+    {
+        Self::Async(Arc::new(
+            move |call: &mut HostCall<'_>| -> BoxFuture<'static, PluginResult<Value>> {
+                match Args::extract_args(call) {
+                    Ok(extracted_args) => func.call(extracted_args),
+                    Err(error) => Box::pin(std::future::ready(Err(error))),
+                }
+            },
+        ))
+    }
+}
 
 /// Context containing host functions that can be injected into plugin runtimes.
 /// Functions are identified by their string names and can be called from plugins.
-#[derive(Default)]
+///
+/// Cloning a context is cheap: registered functions are shared, which lets runtimes
+/// keep a copy alive for as long as a loaded plugin may call back into the host.
+/// A context also holds typed application state injected into host functions through
+/// [`State`] parameters, and may carry the [`Caller`] its plugin is identified by.
+#[derive(Default, Clone)]
 pub struct HostContext {
-    functions: HashMap<String, BoxedHostFunction>,
+    functions: HashMap<String, Registration>,
+    state: HashMap<TypeId, SharedState>,
+    caller: Option<Caller>,
+    #[cfg(feature = "async")]
+    block_on: Option<SharedBlockOn>,
 }
 
 impl HostContext {
@@ -21,29 +104,232 @@ impl HostContext {
     pub fn new() -> Self {
         Self {
             functions: HashMap::new(),
+            state: HashMap::new(),
+            caller: None,
+            #[cfg(feature = "async")]
+            block_on: None,
+        }
+    }
+
+    /// Returns a copy of this context that identifies its plugin as `caller`.
+    ///
+    /// Runtimes keep the context a plugin was loaded with, so host functions called by
+    /// that plugin can take a [`Caller`] parameter. [`DefaultPluginManager`](crate::DefaultPluginManager)
+    /// does this for every plugin it loads.
+    #[must_use]
+    pub fn with_caller(&self, caller: Caller) -> Self {
+        Self {
+            caller: Some(caller),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of this context that runs async host functions called through
+    /// [`call_function`](Self::call_function) with `block_on`, which drives a future to
+    /// completion on the calling thread.
+    ///
+    /// Without one, calling an async host function synchronously fails with
+    /// `PluginError::AsyncHostFunction`, since no executor fits every setup: the executor
+    /// must be able to drive the host function's futures, and must not block a thread its
+    /// async runtime depends on. With Tokio's multi-threaded runtime, for instance, a sync
+    /// runtime running on a runtime thread can inject
+    /// `|future| tokio::task::block_in_place(|| handle.block_on(future))`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> tosic_plugin_core::PluginResult<()> {
+    /// use tosic_plugin_core::*;
+    ///
+    /// let mut context = HostContext::new();
+    /// context.register_async("double", |n: i64| async move { n * 2 });
+    /// assert!(matches!(
+    ///     context.call_function("double", &[Value::Int(21)]),
+    ///     Err(PluginError::AsyncHostFunction(_))
+    /// ));
+    ///
+    /// let context = context.with_block_on(futures::executor::block_on);
+    /// assert_eq!(context.call_function("double", &[Value::Int(21)])?, Value::Int(42));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    #[must_use]
+    pub fn with_block_on<F>(&self, block_on: F) -> Self
+    where
+        F: Fn(BoxFuture<'static, PluginResult<Value>>) -> PluginResult<Value>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Self {
+            block_on: Some(Arc::new(block_on)),
+            ..self.clone()
         }
     }
 
+    /// Returns the caller this context identifies its plugin as, if any.
+    pub fn caller(&self) -> Option<&Caller> {
+        self.caller.as_ref()
+    }
+
+    /// Adds application state that host functions can take as a [`State<T>`] parameter,
+    /// replacing any state of the same type.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> tosic_plugin_core::PluginResult<()> {
+    /// use tosic_plugin_core::*;
+    ///
+    /// struct Config {
+    ///     greeting: String,
+    /// }
+    ///
+    /// let mut context = HostContext::new();
+    /// context.insert_state(Config { greeting: "Hello".to_string() });
+    /// context.register("greet", |config: State<Config>, name: String| {
+    ///     format!("{}, {}!", config.greeting, name)
+    /// });
+    ///
+    /// let result = context.call_function("greet", &[Value::from("World")])?;
+    /// assert_eq!(result, Value::from("Hello, World!"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn insert_state<T: Send + Sync + 'static>(&mut self, state: T) {
+        self.state.insert(TypeId::of::<T>(), Arc::new(state));
+    }
+
+    /// Returns the application state of type `T`, if any was added.
+    pub fn state<T: Send + Sync + 'static>(&self) -> Option<State<T>> {
+        let state = Arc::clone(self.state.get(&TypeId::of::<T>())?);
+        state.downcast().ok().map(State::new)
+    }
+
     /// Registers a host function with the given name.
-    /// The function can have any signature that implements HostFunction.
+    /// The function can have any signature that implements HostFunction; functions
+    /// returning `Result` report their errors to the calling plugin, a [`State<T>`]
+    /// parameter receives application state added with [`insert_state`](Self::insert_state),
+    /// and a [`Caller`] parameter gives access to the plugin making the call.
     pub fn register<Args, F>(&mut self, name: impl Into<String>, func: F)
     where
         F: HostFunction<Args> + 'static,
         Args: ExtractArgs,
     {
-        let boxed_func = Box::new(move |args: &[Value]| -> PluginResult<Value> {
-            let extracted_args = Args::extract_args(args)?;
-            func.call(extracted_args)
-        });
-        
-        self.functions.insert(name.into(), boxed_func);
+        self.insert(
+            name.into(),
+            RegisteredFunction::sync(func),
+            signature::<Args, F::Output>(),
+            None,
+        );
+    }
+
+    /// Registers a host function under the name of `metadata`, recording the metadata
+    /// for [`metadata`](Self::metadata) to return.
+    ///
+    /// This is what functions annotated with `#[host_function]` register themselves with.
+    pub fn register_with_metadata<Args, F>(&mut self, metadata: HostFunctionMetadata, func: F)
+    where
+        F: HostFunction<Args> + 'static,
+        Args: ExtractArgs,
+    {
+        let signature = signature::<Args, F::Output>();
+        self.insert(
+            metadata.name().to_string(),
+            RegisteredFunction::sync(func),
+            signature,
+            Some(metadata),
+        );
+    }
+
+    /// Registers an async host function with the given name.
+    ///
+    /// The function can have any signature that implements AsyncHostFunction, such as
+    /// an `async` closure or a closure returning an `async` block. Async runtimes await it
+    /// through [`call_function_async`](Self::call_function_async), while
+    /// [`call_function`](Self::call_function) only runs it on the executor injected with
+    /// [`with_block_on`](Self::with_block_on).
+    #[cfg(feature = "async")]
+    pub fn register_async<Args, F>(&mut self, name: impl Into<String>, func: F)
+    where
+        F: AsyncHostFunction<Args> + 'static,
+        Args: ExtractArgs,
+    {
+        self.insert(
+            name.into(),
+            RegisteredFunction::r#async(func),
+            signature::<Args, F::Output>(),
+            None,
+        );
+    }
+
+    /// Registers an async host function under the name of `metadata`, recording the
+    /// metadata for [`metadata`](Self::metadata) to return.
+    #[cfg(feature = "async")]
+    pub fn register_async_with_metadata<Args, F>(&mut self, metadata: HostFunctionMetadata, func: F)
+    where
+        F: AsyncHostFunction<Args> + 'static,
+        Args: ExtractArgs,
+    {
+        let signature = signature::<Args, F::Output>();
+        self.insert(
+            metadata.name().to_string(),
+            RegisteredFunction::r#async(func),
+            signature,
+            Some(metadata),
+        );
+    }
+
+    fn insert(
+        &mut self,
+        name: String,
+        function: RegisteredFunction,
+        signature: Signature,
+        metadata: Option<HostFunctionMetadata>,
+    ) {
+        let registration = Registration {
+            function,
+            signature: Arc::new(signature),
+            metadata: metadata.map(Arc::new),
+        };
+        self.functions.insert(name, registration);
     }
 
     /// Gets a host function by name and calls it with the provided arguments.
+    ///
+    /// Async host functions are driven to completion by the executor injected with
+    /// [`with_block_on`](Self::with_block_on), and fail with
+    /// `PluginError::AsyncHostFunction` without one; async runtimes should use
+    /// [`call_function_async`](Self::call_function_async) instead.
     pub fn call_function(&self, name: &str, args: &[Value]) -> PluginResult<Value> {
-        match self.functions.get(name) {
-            Some(func) => func(args),
-            None => Err(crate::PluginError::HostFunctionNotFound(name.to_string())),
+        let registration = self
+            .functions
+            .get(name)
+            .ok_or_else(|| crate::PluginError::HostFunctionNotFound(name.to_string()))?;
+        let mut call = HostCall::new(self, name, &registration.signature, args);
+        match &registration.function {
+            RegisteredFunction::Sync(func) => func(&mut call),
+            #[cfg(feature = "async")]
+            RegisteredFunction::Async(func) => match &self.block_on {
+                Some(block_on) => block_on(func(&mut call)),
+                None => Err(crate::PluginError::AsyncHostFunction(name.to_string())),
+            },
+        }
+    }
+
+    /// Gets a host function by name and calls it with the provided arguments, awaiting it
+    /// if it is async.
+    #[cfg(feature = "async")]
+    pub async fn call_function_async(&self, name: &str, args: &[Value]) -> PluginResult<Value> {
+        let registration = self
+            .functions
+            .get(name)
+            .ok_or_else(|| crate::PluginError::HostFunctionNotFound(name.to_string()))?;
+        let mut call = HostCall::new(self, name, &registration.signature, args);
+        match &registration.function {
+            RegisteredFunction::Sync(func) => func(&mut call),
+            RegisteredFunction::Async(func) => func(&mut call).await,
         }
     }
 
@@ -56,71 +342,267 @@ impl HostContext {
     pub fn has_function(&self, name: &str) -> bool {
         self.functions.contains_key(name)
     }
+
+    /// Returns the signature of the function with the given name, if it is registered.
+    ///
+    /// The signature is recorded when the function is registered, so runtimes can check a
+    /// plugin's imports against it at load time instead of failing on the first call.
+    pub fn signature(&self, name: &str) -> Option<&Signature> {
+        self.functions
+            .get(name)
+            .map(|registration| registration.signature.as_ref())
+    }
+
+    /// Returns the metadata the function with the given name was registered with, if any.
+    pub fn metadata(&self, name: &str) -> Option<&HostFunctionMetadata> {
+        self.functions.get(name)?.metadata.as_deref()
+    }
+}
+
+/// A single call of a host function, from which its parameters are extracted in order.
+///
+/// Parameters implementing [`FromValue`](crate::FromValue) consume the call's arguments
+/// one by one, while extractors such as [`Caller`] read the context the call was made
+/// through.
+pub struct HostCall<'a> {
+    context: &'a HostContext,
+    function: &'a str,
+    signature: &'a Signature,
+    args: &'a [Value],
+    position: usize,
+}
+
+impl<'a> HostCall<'a> {
+    /// Creates a call of the host function named `function`, whose parameters are described
+    /// by `signature`, with the given arguments, made through `context`.
+    pub fn new(
+        context: &'a HostContext,
+        function: &'a str,
+        signature: &'a Signature,
+        args: &'a [Value],
+    ) -> Self {
+        Self {
+            context,
+            function,
+            signature,
+            args,
+            position: 0,
+        }
+    }
+
+    /// Returns the context the call was made through.
+    pub fn context(&self) -> &'a HostContext {
+        self.context
+    }
+
+    /// Returns the name of the host function being called.
+    pub fn function(&self) -> &'a str {
+        self.function
+    }
+
+    /// Returns the signature of the host function being called.
+    pub fn signature(&self) -> &'a Signature {
+        self.signature
+    }
+
+    /// Returns the plugin making the call, if the context identifies one.
+    pub fn caller(&self) -> Option<&'a Caller> {
+        self.context.caller()
+    }
+
+    /// Consumes the next argument of the call.
+    pub fn next_arg(&mut self) -> Option<&'a Value> {
+        let (first, rest) = self.args.split_first()?;
+        self.args = rest;
+        self.position += 1;
+        Some(first)
+    }
+
+    /// Returns the index of the next argument, which is the number of arguments consumed so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns a [`PluginError::ArityMismatch`](crate::PluginError::ArityMismatch) for this call,
+    /// given the minimum and maximum number of arguments the host function takes, with no
+    /// maximum for variadic functions.
+    pub fn arity_mismatch(&self, min: usize, max: Option<usize>) -> crate::PluginError {
+        crate::PluginError::ArityMismatch {
+            function: self.function.to_string(),
+            min,
+            max,
+            received: self.position + self.args.len(),
+        }
+    }
+
+    /// Returns the arguments not consumed yet.
+    pub fn remaining(&self) -> &'a [Value] {
+        self.args
+    }
 }
 
-/// Trait for extracting arguments from a Value array into the appropriate tuple type.
-/// 
+/// Trait for extracting a host function's parameters from a [`HostCall`] into the appropriate tuple type.
+///
 /// # Errors
-/// Returns `PluginError::InvalidArgumentType` if argument extraction fails.
+/// Returns `PluginError::ArityMismatch` if the call has too few or too many arguments,
+/// `PluginError::InvalidArgument` naming the argument that could not be converted, or the
+/// error of an extractor that cannot be satisfied.
 pub trait ExtractArgs: Sized {
-    /// Extracts typed arguments from a Value slice.
-    fn extract_args(args: &[Value]) -> PluginResult<Self>;
+    /// Extracts typed parameters from a host function call.
+    fn extract_args(call: &mut HostCall<'_>) -> PluginResult<Self>;
+
+    /// Adds the arguments the parameters consume to the signature of their host function.
+    fn describe(signature: &mut Signature);
 }
 
 /// Macro to implement ExtractArgs for different tuple sizes.
 macro_rules! impl_extract_args {
     () => {
         impl ExtractArgs for () {
-            fn extract_args(args: &[Value]) -> PluginResult<Self> {
-                if args.is_empty() {
+            fn extract_args(call: &mut HostCall<'_>) -> PluginResult<Self> {
+                if call.remaining().is_empty() {
                     Ok(())
                 } else {
-                    Err(crate::PluginError::InvalidArgumentType)
+                    Err(call.arity_mismatch(0, Some(0)))
                 }
             }
+
+            fn describe(_signature: &mut Signature) {}
         }
     };
-    
+
     ($($arg:ident),+) => {
         impl<$($arg,)+> ExtractArgs for ($($arg,)+)
         where
-            $($arg: crate::traits::host_function::FromValue,)+
+            $($arg: crate::traits::host_function::FromHostCall,)+
         {
-            fn extract_args(args: &[Value]) -> PluginResult<Self> {
-                #[allow(unused)]
-                const ARG_COUNT: usize = {
-                    let mut count = 0;
-                    $( let _ = stringify!($arg); count += 1; )+
-                    count
-                };
-                if args.len() != ARG_COUNT {
-                    return Err(crate::PluginError::InvalidArgumentType);
+            fn extract_args(call: &mut HostCall<'_>) -> PluginResult<Self> {
+                let signature = call.signature();
+                if !signature.accepts_arity(call.position() + call.remaining().len()) {
+                    return Err(call.arity_mismatch(signature.required(), signature.max_arity()));
+                }
+
+                let extracted = (
+                    $($arg::from_host_call(call)?,)+
+                );
+                if !call.remaining().is_empty() {
+                    return Err(call.arity_mismatch(signature.required(), signature.max_arity()));
                 }
-                
-                let mut iter = args.iter();
-                Ok((
-                    $($arg::from_value(iter.next().unwrap())?,)+
-                ))
+                Ok(extracted)
+            }
+
+            fn describe(signature: &mut Signature) {
+                $(<$arg as crate::traits::host_function::FromHostCall>::describe(signature);)+
             }
         }
     };
 }
+// Generate implementations for 0 to 32 arguments
+for_each_arity!(impl_extract_args);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PluginError;
+    use crate::types::ValueKind;
+
+    fn arity(result: PluginResult<Value>) -> (usize, Option<usize>, usize) {
+        match result {
+            Err(PluginError::ArityMismatch {
+                min, max, received, ..
+            }) => (min, max, received),
+            other => panic!("expected an arity mismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn calls_see_the_registered_signature() {
+        let mut context = HostContext::new();
+        context.register("describe", |call_signature: CallSignature| call_signature.0);
+        context.register("add", |a: i64, b: Option<i64>| a + b.unwrap_or(0));
+
+        let signature = context.signature("describe").unwrap().to_string();
+        assert_eq!(
+            context.call_function("describe", &[]).unwrap(),
+            Value::String(signature)
+        );
+        assert_eq!(
+            arity(context.call_function("add", &[Value::Int(1), Value::Int(2), Value::Int(3)])),
+            (1, Some(2), 3)
+        );
+        assert_eq!(arity(context.call_function("add", &[])), (1, Some(2), 0));
+    }
+
+    #[test]
+    fn missing_arguments_report_the_signature_arity() {
+        use crate::traits::host_function::FromHostCall;
 
-// Generate implementations for 0 to 16 arguments
-impl_extract_args!();
-impl_extract_args!(A1);
-impl_extract_args!(A1, A2);
-impl_extract_args!(A1, A2, A3);
-impl_extract_args!(A1, A2, A3, A4);
-impl_extract_args!(A1, A2, A3, A4, A5);
-impl_extract_args!(A1, A2, A3, A4, A5, A6);
-impl_extract_args!(A1, A2, A3, A4, A5, A6, A7);
-impl_extract_args!(A1, A2, A3, A4, A5, A6, A7, A8);
-impl_extract_args!(A1, A2, A3, A4, A5, A6, A7, A8, A9);
-impl_extract_args!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10);
-impl_extract_args!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11);
-impl_extract_args!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12);
-impl_extract_args!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13);
-impl_extract_args!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14);
-impl_extract_args!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15);
-impl_extract_args!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15, A16);
\ No newline at end of file
+        let context = HostContext::new();
+        let mut signature = Signature::new(ValueKind::Int);
+        <(i64, i64, Option<i64>) as ExtractArgs>::describe(&mut signature);
+
+        let args = [Value::Int(1)];
+        let mut call = HostCall::new(&context, "add", &signature, &args);
+        assert_eq!(i64::from_host_call(&mut call).unwrap(), 1);
+        assert_eq!(Option::<i64>::from_host_call(&mut call).unwrap(), None);
+        assert_eq!(
+            arity(i64::from_host_call(&mut call).map(Value::Int)),
+            (2, Some(3), 1)
+        );
+    }
+
+    #[test]
+    fn optional_arguments_may_be_omitted_or_null() {
+        let mut context = HostContext::new();
+        context.register("add", |a: i64, b: Option<i64>, c: Option<i64>| {
+            a + b.unwrap_or(0) + c.unwrap_or(0)
+        });
+
+        assert_eq!(
+            context.call_function("add", &[Value::Int(1)]).unwrap(),
+            Value::Int(1)
+        );
+        assert_eq!(
+            context
+                .call_function("add", &[Value::Int(1), Value::Null, Value::Int(3)])
+                .unwrap(),
+            Value::Int(4)
+        );
+        assert!(matches!(
+            context.call_function("add", &[Value::Int(1), Value::from("two")]),
+            Err(PluginError::InvalidArgument { index: 1, .. })
+        ));
+    }
+
+    /// Extractor returning the signature of the function being called, as text.
+    struct CallSignature(String);
+
+    impl crate::traits::host_function::FromHostCall for CallSignature {
+        fn from_host_call(call: &mut HostCall<'_>) -> PluginResult<Self> {
+            Ok(CallSignature(call.signature().to_string()))
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_functions_need_an_executor_to_be_called_synchronously() {
+        let mut context = HostContext::new();
+        context.register_async("double", |n: i64| async move { n * 2 });
+        let args = [Value::Int(21)];
+
+        assert!(matches!(
+            context.call_function("double", &args),
+            Err(crate::PluginError::AsyncHostFunction(name)) if name == "double"
+        ));
+        assert_eq!(
+            futures::executor::block_on(context.call_function_async("double", &args)).unwrap(),
+            Value::Int(42)
+        );
+
+        let context = context.with_block_on(futures::executor::block_on);
+        assert_eq!(
+            context.call_function("double", &args).unwrap(),
+            Value::Int(42)
+        );
+    }
+}
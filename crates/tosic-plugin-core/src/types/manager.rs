@@ -0,0 +1,457 @@
+//! Default plugin manager built on top of a runtime.
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+
+use crate::traits::manager::PluginManager;
+use crate::traits::runtime::{Plugin, Runtime};
+#[cfg(feature = "async")]
+use crate::traits::{manager::AsyncPluginManager, runtime::AsyncRuntime};
+use crate::types::{Caller, HostContext, Value};
+use crate::{PluginError, PluginResult};
+
+/// Lifecycle state of a managed plugin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginState {
+    /// The plugin is loaded and accepting calls.
+    Loaded,
+    /// A call reported that the plugin crashed; the message describes the crash.
+    Crashed(String),
+}
+
+/// Summary of a plugin held by a [`PluginManager`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginInfo {
+    /// The id the plugin was loaded under.
+    pub id: String,
+    /// The name the plugin reports, if any.
+    pub name: Option<String>,
+    /// The plugin's current state.
+    pub state: PluginState,
+}
+
+/// A plugin and its state as tracked by a [`DefaultPluginManager`].
+struct ManagedPlugin<P> {
+    plugin: P,
+    state: Mutex<PluginState>,
+}
+
+/// [`PluginManager`] that loads every plugin through a single runtime and host context.
+///
+/// Plugins are keyed by id, and each is loaded with a context identifying it as a
+/// [`Caller`] to the host functions it calls. A call failing with `PluginError::PluginCrashed` marks the
+/// plugin as [`PluginState::Crashed`] until it is unloaded; further calls fail with the same
+/// error without reaching the runtime.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> tosic_plugin_core::PluginResult<()> {
+/// use tosic_plugin_core::*;
+///
+/// struct EchoPlugin;
+///
+/// impl Plugin for EchoPlugin {}
+///
+/// struct EchoRuntime;
+///
+/// impl Runtime for EchoRuntime {
+///     type Plugin = EchoPlugin;
+///
+///     fn load(&self, _bytes: &[u8], _context: &HostContext) -> PluginResult<EchoPlugin> {
+///         Ok(EchoPlugin)
+///     }
+///
+///     fn call(&self, _plugin: &EchoPlugin, _function_name: &str, args: &[Value]) -> PluginResult<Value> {
+///         Ok(Value::Array(args.to_vec()))
+///     }
+/// }
+///
+/// let mut manager = DefaultPluginManager::new(EchoRuntime, HostContext::new());
+/// manager.load("echo", b"")?;
+///
+/// let result = manager.call("echo", "echo", &[Value::Int(1)])?;
+/// assert_eq!(result, Value::Array(vec![Value::Int(1)]));
+///
+/// manager.unload("echo")?;
+/// assert!(manager.plugins().is_empty());
+/// # Ok(())
+/// # }
+/// ```
+pub struct DefaultPluginManager<R: Runtime> {
+    runtime: R,
+    context: HostContext,
+    plugins: PluginTable<R::Plugin>,
+}
+
+impl<R: Runtime> DefaultPluginManager<R> {
+    /// Creates an empty manager that loads plugins with `runtime`, giving them `context`.
+    pub fn new(runtime: R, context: HostContext) -> Self {
+        Self {
+            runtime,
+            context,
+            plugins: PluginTable::default(),
+        }
+    }
+
+    /// Returns the runtime plugins are loaded with.
+    pub fn runtime(&self) -> &R {
+        &self.runtime
+    }
+
+    /// Returns the host context given to loaded plugins.
+    pub fn context(&self) -> &HostContext {
+        &self.context
+    }
+
+    /// Returns the state of the plugin loaded under `id`.
+    pub fn state(&self, id: &str) -> Option<PluginState> {
+        self.plugins.state(id)
+    }
+}
+
+impl<R: Runtime> PluginManager for DefaultPluginManager<R> {
+    type Plugin = R::Plugin;
+
+    fn load(&mut self, id: &str, bytes: &[u8]) -> PluginResult<()> {
+        self.plugins.ensure_vacant(id)?;
+        let caller = Caller::new(id);
+        let plugin = self
+            .runtime
+            .load(bytes, &self.context.with_caller(caller.clone()))?;
+        self.plugins.insert(id, plugin, &caller);
+        Ok(())
+    }
+
+    fn get(&self, id: &str) -> Option<&Self::Plugin> {
+        self.plugins.get(id)
+    }
+
+    fn get_by_name(&self, name: &str) -> Option<&Self::Plugin> {
+        self.plugins.get_by_name(name)
+    }
+
+    fn call(&self, plugin_id: &str, function_name: &str, args: &[Value]) -> PluginResult<Value> {
+        let managed = self.plugins.callable(plugin_id)?;
+        managed.track(self.runtime.call(&managed.plugin, function_name, args))
+    }
+
+    fn unload(&mut self, id: &str) -> PluginResult<()> {
+        self.plugins.remove(id)
+    }
+
+    fn plugins(&self) -> Vec<PluginInfo> {
+        self.plugins.infos()
+    }
+}
+
+/// [`AsyncPluginManager`] that loads every plugin through a single async runtime and host
+/// context.
+///
+/// It behaves like [`DefaultPluginManager`], awaiting the runtime's loads and calls.
+#[cfg(feature = "async")]
+pub struct DefaultAsyncPluginManager<R: AsyncRuntime> {
+    runtime: R,
+    context: HostContext,
+    plugins: PluginTable<R::Plugin>,
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRuntime> DefaultAsyncPluginManager<R> {
+    /// Creates an empty manager that loads plugins with `runtime`, giving them `context`.
+    pub fn new(runtime: R, context: HostContext) -> Self {
+        Self {
+            runtime,
+            context,
+            plugins: PluginTable::default(),
+        }
+    }
+
+    /// Returns the runtime plugins are loaded with.
+    pub fn runtime(&self) -> &R {
+        &self.runtime
+    }
+
+    /// Returns the host context given to loaded plugins.
+    pub fn context(&self) -> &HostContext {
+        &self.context
+    }
+
+    /// Returns the state of the plugin loaded under `id`.
+    pub fn state(&self, id: &str) -> Option<PluginState> {
+        self.plugins.state(id)
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<R: AsyncRuntime> AsyncPluginManager for DefaultAsyncPluginManager<R> {
+    type Plugin = R::Plugin;
+
+    async fn load_async(&mut self, id: &str, bytes: &[u8]) -> PluginResult<()> {
+        self.plugins.ensure_vacant(id)?;
+        let caller = Caller::new(id);
+        let plugin = self
+            .runtime
+            .load_async(bytes, &self.context.with_caller(caller.clone()))
+            .await?;
+        self.plugins.insert(id, plugin, &caller);
+        Ok(())
+    }
+
+    fn get(&self, id: &str) -> Option<&Self::Plugin> {
+        self.plugins.get(id)
+    }
+
+    fn get_by_name(&self, name: &str) -> Option<&Self::Plugin> {
+        self.plugins.get_by_name(name)
+    }
+
+    async fn call_async(
+        &self,
+        plugin_id: &str,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        let managed = self.plugins.callable(plugin_id)?;
+        managed.track(
+            self.runtime
+                .call_async(&managed.plugin, function_name, args)
+                .await,
+        )
+    }
+
+    fn unload(&mut self, id: &str) -> PluginResult<()> {
+        self.plugins.remove(id)
+    }
+
+    fn plugins(&self) -> Vec<PluginInfo> {
+        self.plugins.infos()
+    }
+}
+
+/// Plugins keyed by id, shared by the default sync and async managers.
+struct PluginTable<P> {
+    plugins: BTreeMap<String, ManagedPlugin<P>>,
+}
+
+impl<P> Default for PluginTable<P> {
+    fn default() -> Self {
+        Self {
+            plugins: BTreeMap::new(),
+        }
+    }
+}
+
+impl<P: Plugin> PluginTable<P> {
+    fn ensure_vacant(&self, id: &str) -> PluginResult<()> {
+        if self.plugins.contains_key(id) {
+            return Err(PluginError::PluginAlreadyLoaded(id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Inserts a freshly loaded plugin, recording its name on the caller it was loaded with.
+    fn insert(&mut self, id: &str, plugin: P, caller: &Caller) {
+        if let Some(name) = plugin.name() {
+            caller.set_name(name);
+        }
+        self.plugins.insert(
+            id.to_string(),
+            ManagedPlugin {
+                plugin,
+                state: Mutex::new(PluginState::Loaded),
+            },
+        );
+    }
+
+    /// Returns the plugin loaded under `id`, unless it crashed.
+    fn callable(&self, id: &str) -> PluginResult<&ManagedPlugin<P>> {
+        let managed = self
+            .plugins
+            .get(id)
+            .ok_or_else(|| PluginError::PluginNotFound(id.to_string()))?;
+        match managed.state() {
+            PluginState::Loaded => Ok(managed),
+            PluginState::Crashed(message) => Err(PluginError::PluginCrashed(message)),
+        }
+    }
+
+    fn get(&self, id: &str) -> Option<&P> {
+        self.plugins.get(id).map(|managed| &managed.plugin)
+    }
+
+    fn get_by_name(&self, name: &str) -> Option<&P> {
+        self.plugins
+            .values()
+            .map(|managed| &managed.plugin)
+            .find(|plugin| plugin.name() == Some(name))
+    }
+
+    fn state(&self, id: &str) -> Option<PluginState> {
+        self.plugins.get(id).map(ManagedPlugin::state)
+    }
+
+    fn remove(&mut self, id: &str) -> PluginResult<()> {
+        self.plugins
+            .remove(id)
+            .map(drop)
+            .ok_or_else(|| PluginError::PluginNotFound(id.to_string()))
+    }
+
+    fn infos(&self) -> Vec<PluginInfo> {
+        self.plugins
+            .iter()
+            .map(|(id, managed)| PluginInfo {
+                id: id.clone(),
+                name: managed.plugin.name().map(str::to_string),
+                state: managed.state(),
+            })
+            .collect()
+    }
+}
+
+impl<P> ManagedPlugin<P> {
+    fn state(&self) -> PluginState {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Records a crash reported by a call before handing the result back.
+    fn track(&self, result: PluginResult<Value>) -> PluginResult<Value> {
+        if let Err(PluginError::PluginCrashed(message)) = &result {
+            *self.state.lock().unwrap_or_else(PoisonError::into_inner) =
+                PluginState::Crashed(message.clone());
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    struct TestPlugin;
+
+    impl Plugin for TestPlugin {}
+
+    /// Runtime whose `crash` function reports a crash, counting the calls it receives.
+    #[derive(Default)]
+    struct CrashingRuntime {
+        calls: AtomicUsize,
+    }
+
+    impl CrashingRuntime {
+        fn respond(&self, function_name: &str) -> PluginResult<Value> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match function_name {
+                "crash" => Err(PluginError::PluginCrashed(
+                    "exited with status 1".to_string(),
+                )),
+                _ => Ok(Value::Null),
+            }
+        }
+    }
+
+    impl Runtime for CrashingRuntime {
+        type Plugin = TestPlugin;
+
+        fn load(&self, _bytes: &[u8], _context: &HostContext) -> PluginResult<TestPlugin> {
+            Ok(TestPlugin)
+        }
+
+        fn call(
+            &self,
+            _plugin: &TestPlugin,
+            function_name: &str,
+            _args: &[Value],
+        ) -> PluginResult<Value> {
+            self.respond(function_name)
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[async_trait::async_trait]
+    impl AsyncRuntime for CrashingRuntime {
+        type Plugin = TestPlugin;
+
+        async fn load_async(
+            &self,
+            _bytes: &[u8],
+            _context: &HostContext,
+        ) -> PluginResult<TestPlugin> {
+            Ok(TestPlugin)
+        }
+
+        async fn call_async(
+            &self,
+            _plugin: &TestPlugin,
+            function_name: &str,
+            _args: &[Value],
+        ) -> PluginResult<Value> {
+            self.respond(function_name)
+        }
+    }
+
+    fn crashed(result: PluginResult<Value>) -> String {
+        match result {
+            Err(PluginError::PluginCrashed(message)) => message,
+            other => panic!("expected a crash, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn crashed_plugins_refuse_calls_until_unloaded() {
+        let mut manager = DefaultPluginManager::new(CrashingRuntime::default(), HostContext::new());
+        manager.load("plugin", b"").unwrap();
+        manager.call("plugin", "work", &[]).unwrap();
+        assert_eq!(manager.state("plugin"), Some(PluginState::Loaded));
+
+        assert_eq!(
+            crashed(manager.call("plugin", "crash", &[])),
+            "exited with status 1"
+        );
+        let state = PluginState::Crashed("exited with status 1".to_string());
+        assert_eq!(manager.state("plugin"), Some(state.clone()));
+        assert_eq!(manager.plugins()[0].state, state);
+
+        assert_eq!(
+            crashed(manager.call("plugin", "work", &[])),
+            "exited with status 1"
+        );
+        assert_eq!(manager.runtime().calls.load(Ordering::SeqCst), 2);
+
+        manager.unload("plugin").unwrap();
+        assert_eq!(manager.state("plugin"), None);
+        assert!(matches!(
+            manager.call("plugin", "work", &[]),
+            Err(PluginError::PluginNotFound(_))
+        ));
+
+        manager.load("plugin", b"").unwrap();
+        assert_eq!(manager.state("plugin"), Some(PluginState::Loaded));
+        manager.call("plugin", "work", &[]).unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn crashed_plugins_refuse_async_calls_until_unloaded() {
+        futures::executor::block_on(async {
+            let mut manager =
+                DefaultAsyncPluginManager::new(CrashingRuntime::default(), HostContext::new());
+            manager.load_async("plugin", b"").await.unwrap();
+
+            crashed(manager.call_async("plugin", "crash", &[]).await);
+            crashed(manager.call_async("plugin", "work", &[]).await);
+            assert_eq!(manager.runtime().calls.load(Ordering::SeqCst), 1);
+
+            manager.unload("plugin").unwrap();
+            manager.load_async("plugin", b"").await.unwrap();
+            manager.call_async("plugin", "work", &[]).await.unwrap();
+        });
+    }
+}
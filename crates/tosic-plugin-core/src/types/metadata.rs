@@ -0,0 +1,111 @@
+//! Descriptive metadata of registered host functions.
+
+/// Documentation and Rust signature of a host function, recorded when it is registered
+/// with [`HostContext::register_with_metadata`](crate::HostContext::register_with_metadata).
+///
+/// The `#[host_function]` attribute of the `derive` feature generates this metadata from
+/// the function's signature and doc comment, for tooling such as stub generators to query
+/// through [`HostContext::metadata`](crate::HostContext::metadata).
+///
+/// # Example
+///
+/// ```rust
+/// use tosic_plugin_core::*;
+///
+/// let metadata = HostFunctionMetadata::new("add")
+///     .with_doc("Adds two numbers.")
+///     .with_param("a", "i64")
+///     .with_param("b", "i64")
+///     .with_returns("i64");
+///
+/// let mut context = HostContext::new();
+/// context.register_with_metadata(metadata, |a: i64, b: i64| a + b);
+///
+/// let metadata = context.metadata("add").unwrap();
+/// assert_eq!(metadata.doc(), Some("Adds two numbers."));
+/// assert_eq!(metadata.params()[1].name(), "b");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostFunctionMetadata {
+    name: String,
+    doc: Option<String>,
+    params: Vec<ParamMetadata>,
+    returns: Option<String>,
+}
+
+/// Name and Rust type of a host function parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamMetadata {
+    name: String,
+    ty: String,
+}
+
+impl HostFunctionMetadata {
+    /// Creates metadata for the host function registered under `name`, without
+    /// documentation, parameters or return type.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            doc: None,
+            params: Vec::new(),
+            returns: None,
+        }
+    }
+
+    /// Sets the documentation of the function.
+    #[must_use]
+    pub fn with_doc(mut self, doc: impl Into<String>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
+
+    /// Appends a parameter with the given name and Rust type.
+    #[must_use]
+    pub fn with_param(mut self, name: impl Into<String>, ty: impl Into<String>) -> Self {
+        self.params.push(ParamMetadata {
+            name: name.into(),
+            ty: ty.into(),
+        });
+        self
+    }
+
+    /// Sets the Rust return type of the function.
+    #[must_use]
+    pub fn with_returns(mut self, ty: impl Into<String>) -> Self {
+        self.returns = Some(ty.into());
+        self
+    }
+
+    /// Returns the name the function is registered under.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the documentation of the function, if any.
+    pub fn doc(&self) -> Option<&str> {
+        self.doc.as_deref()
+    }
+
+    /// Returns the parameters of the function in order, including extractors such as
+    /// [`Caller`](crate::Caller) that plugins do not pass.
+    pub fn params(&self) -> &[ParamMetadata] {
+        &self.params
+    }
+
+    /// Returns the Rust return type of the function, or `None` if it returns `()`.
+    pub fn returns(&self) -> Option<&str> {
+        self.returns.as_deref()
+    }
+}
+
+impl ParamMetadata {
+    /// Returns the name of the parameter.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the Rust type of the parameter.
+    pub fn ty(&self) -> &str {
+        &self.ty
+    }
+}
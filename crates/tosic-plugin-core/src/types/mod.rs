@@ -1,7 +1,32 @@
 //! Core types for plugin system data exchange.
 
-mod value;
+#[cfg(feature = "async")]
+mod adapter;
+mod caller;
+mod codec;
+mod collections;
 mod context;
+mod manager;
+mod metadata;
+mod numeric;
+#[cfg(feature = "serde")]
+mod serde_bridge;
+mod signature;
+mod state;
+mod value;
+mod variadic;
 
+#[cfg(feature = "async")]
+pub use adapter::*;
+pub use caller::*;
+pub use collections::*;
+pub use context::*;
+pub use manager::*;
+pub use metadata::*;
+pub use numeric::*;
+#[cfg(feature = "serde")]
+pub use serde_bridge::*;
+pub use signature::*;
+pub use state::*;
 pub use value::*;
-pub use context::*;
\ No newline at end of file
+pub use variadic::*;
@@ -0,0 +1,312 @@
+//! Conversions between numeric Rust types and plugin Values.
+//!
+//! Integers are carried by [`Value::Int`] and floats by [`Value::Float`]. Converting from a
+//! value checks that the number fits in the target type, returning
+//! [`PluginError::OutOfRange`] instead of truncating it; [`Lenient`] additionally accepts
+//! the other kind of number when the conversion is exact.
+//!
+//! Integer types that always fit in an `i64` implement [`IntoValue`]. Those that may not,
+//! `u64`, `u128`, `i128`, `usize` and `isize`, only implement [`IntoHostResult`]: a host
+//! function returning one fails with [`PluginError::OutOfRange`] if the number does not
+//! fit, instead of silently changing it. Elsewhere, convert them with `i64::try_from`.
+
+use std::fmt::Display;
+
+use crate::traits::host_function::{FromValue, IntoHostResult, IntoValue};
+use crate::types::{Value, ValueKind};
+use crate::{PluginError, PluginResult};
+
+/// Numeric host function argument that also accepts the other kind of number, as long as
+/// the conversion is exact.
+///
+/// Integer types accept a [`Value::Float`] without a fractional part, and float types
+/// accept a [`Value::Int`] they represent exactly. This suits plugins written in languages
+/// that do not distinguish integers from floats, such as JavaScript or Lua.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> tosic_plugin_core::PluginResult<()> {
+/// use tosic_plugin_core::*;
+///
+/// let mut context = HostContext::new();
+/// context.register("double", |Lenient(n): Lenient<u32>| n * 2);
+///
+/// assert_eq!(context.call_function("double", &[Value::Float(21.0)])?, Value::Int(42));
+/// assert!(context.call_function("double", &[Value::Float(1.5)]).is_err());
+/// assert!(context.call_function("double", &[Value::Int(-1)]).is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lenient<T>(pub T);
+
+impl<T: IntoValue> IntoValue for Lenient<T> {
+    fn into_value(self) -> Value {
+        self.0.into_value()
+    }
+
+    fn produced_kind() -> ValueKind {
+        T::produced_kind()
+    }
+}
+
+fn out_of_range(expected: &str, value: impl Display) -> PluginError {
+    PluginError::OutOfRange {
+        expected: expected.to_string(),
+        value: value.to_string(),
+    }
+}
+
+/// Converts a float without a fractional part into an integer.
+fn exact_integer(float: f64, expected: &str) -> PluginResult<i128> {
+    if float.is_finite() && float.fract() != 0.0 {
+        return Err(PluginError::ConversionError(format!(
+            "{float} is not an exact integer, as `{expected}` requires"
+        )));
+    }
+    // `i128::MIN` is a power of two, so both bounds are exact.
+    let bound = -(i128::MIN as f64);
+    if !(-bound..bound).contains(&float) {
+        return Err(out_of_range(expected, float));
+    }
+    Ok(float as i128)
+}
+
+/// Implements the conversions of integer types, which are carried by `Value::Int`.
+macro_rules! impl_integer {
+    ($($ty:ident),*) => {$(
+        impl FromValue for $ty {
+            fn from_value(value: &Value) -> PluginResult<Self> {
+                match value {
+                    Value::Int(i) => $ty::try_from(*i).map_err(|_| out_of_range(stringify!($ty), i)),
+                    _ => Err(PluginError::type_mismatch(stringify!($ty), value)),
+                }
+            }
+
+            fn expected_kind() -> ValueKind {
+                ValueKind::Int
+            }
+        }
+
+        impl FromValue for Lenient<$ty> {
+            fn from_value(value: &Value) -> PluginResult<Self> {
+                match value {
+                    Value::Float(f) => {
+                        let integer = exact_integer(*f, stringify!($ty))?;
+                        $ty::try_from(integer).map(Lenient).map_err(|_| out_of_range(stringify!($ty), f))
+                    }
+                    other => $ty::from_value(other).map(Lenient),
+                }
+            }
+        }
+    )*};
+}
+
+/// Implements `IntoValue` for integer types that always fit in `Value::Int`.
+macro_rules! impl_into_int {
+    ($($ty:ident),*) => {$(
+        impl IntoValue for $ty {
+            fn into_value(self) -> Value {
+                Value::Int(i64::from(self))
+            }
+
+            fn produced_kind() -> ValueKind {
+                ValueKind::Int
+            }
+        }
+    )*};
+}
+
+/// Implements `IntoHostResult` for integer types that may not fit in `Value::Int`, failing
+/// with `PluginError::OutOfRange` when they do not.
+macro_rules! impl_into_int_checked {
+    ($($ty:ident),*) => {$(
+        impl IntoHostResult for $ty {
+            fn into_host_result(self) -> PluginResult<Value> {
+                i64::try_from(self).map(Value::Int).map_err(|_| out_of_range("i64", self))
+            }
+
+            fn return_kind() -> ValueKind {
+                ValueKind::Int
+            }
+        }
+    )*};
+}
+
+impl_integer!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+impl_into_int!(i8, i16, i32, i64, u8, u16, u32);
+impl_into_int_checked!(i128, isize, u64, u128, usize);
+
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> PluginResult<Self> {
+        match value {
+            Value::Float(f) => Ok(*f),
+            _ => Err(PluginError::type_mismatch("f64", value)),
+        }
+    }
+
+    fn expected_kind() -> ValueKind {
+        ValueKind::Float
+    }
+}
+
+/// Converts to the nearest `f32`, failing for finite values beyond its range.
+impl FromValue for f32 {
+    fn from_value(value: &Value) -> PluginResult<Self> {
+        match value {
+            Value::Float(f) if f.is_finite() && (*f as f32).is_infinite() => {
+                Err(out_of_range("f32", f))
+            }
+            Value::Float(f) => Ok(*f as f32),
+            _ => Err(PluginError::type_mismatch("f32", value)),
+        }
+    }
+
+    fn expected_kind() -> ValueKind {
+        ValueKind::Float
+    }
+}
+
+/// Implements `FromValue` for `Lenient` floats, which accept integers they represent exactly.
+macro_rules! impl_lenient_float {
+    ($($ty:ident),*) => {$(
+        impl FromValue for Lenient<$ty> {
+            fn from_value(value: &Value) -> PluginResult<Self> {
+                match value {
+                    Value::Int(i) if i128::from(*i) == *i as $ty as i128 => Ok(Lenient(*i as $ty)),
+                    Value::Int(i) => Err(PluginError::ConversionError(format!(
+                        "{i} cannot be represented exactly as `{}`",
+                        stringify!($ty)
+                    ))),
+                    other => $ty::from_value(other).map(Lenient),
+                }
+            }
+        }
+    )*};
+}
+
+impl_lenient_float!(f32, f64);
+
+impl IntoValue for f64 {
+    fn into_value(self) -> Value {
+        Value::Float(self)
+    }
+
+    fn produced_kind() -> ValueKind {
+        ValueKind::Float
+    }
+}
+
+impl IntoValue for f32 {
+    fn into_value(self) -> Value {
+        Value::Float(f64::from(self))
+    }
+
+    fn produced_kind() -> ValueKind {
+        ValueKind::Float
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_are_range_checked() {
+        assert_eq!(u8::from_value(&Value::Int(255)).unwrap(), 255);
+        assert!(matches!(
+            u8::from_value(&Value::Int(256)),
+            Err(PluginError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            u64::from_value(&Value::Int(-1)),
+            Err(PluginError::OutOfRange { .. })
+        ));
+        assert_eq!(
+            i128::from_value(&Value::Int(i64::MIN)).unwrap(),
+            i128::from(i64::MIN)
+        );
+        assert!(matches!(
+            i64::from_value(&Value::Float(1.0)),
+            Err(PluginError::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn floats_are_range_checked() {
+        assert_eq!(f32::from_value(&Value::Float(1.5)).unwrap(), 1.5);
+        assert!(
+            f32::from_value(&Value::Float(f64::INFINITY))
+                .unwrap()
+                .is_infinite()
+        );
+        assert!(matches!(
+            f32::from_value(&Value::Float(1e300)),
+            Err(PluginError::OutOfRange { .. })
+        ));
+        assert!(f64::from_value(&Value::Int(1)).is_err());
+    }
+
+    #[test]
+    fn lenient_accepts_exact_conversions() {
+        assert_eq!(
+            Lenient::<u32>::from_value(&Value::Float(42.0)).unwrap(),
+            Lenient(42)
+        );
+        assert_eq!(
+            Lenient::<u32>::from_value(&Value::Int(42)).unwrap(),
+            Lenient(42)
+        );
+        assert!(matches!(
+            Lenient::<u32>::from_value(&Value::Float(1.5)),
+            Err(PluginError::ConversionError(_))
+        ));
+        assert!(matches!(
+            Lenient::<u8>::from_value(&Value::Float(256.0)),
+            Err(PluginError::OutOfRange { .. })
+        ));
+        assert!(Lenient::<i64>::from_value(&Value::Float(f64::NAN)).is_err());
+
+        assert_eq!(
+            Lenient::<f64>::from_value(&Value::Int(1 << 53)).unwrap(),
+            Lenient(9_007_199_254_740_992.0)
+        );
+        assert!(Lenient::<f64>::from_value(&Value::Int((1 << 53) + 1)).is_err());
+        assert!(Lenient::<f32>::from_value(&Value::Int((1 << 24) + 1)).is_err());
+    }
+
+    #[test]
+    fn wide_integers_are_range_checked_when_returned() {
+        assert_eq!(42usize.into_host_result().unwrap(), Value::Int(42));
+        assert_eq!((-5isize).into_host_result().unwrap(), Value::Int(-5));
+        assert!(matches!(
+            u64::MAX.into_host_result(),
+            Err(PluginError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            i128::MIN.into_host_result(),
+            Err(PluginError::OutOfRange { .. })
+        ));
+
+        let mut context = crate::HostContext::new();
+        context.register("len", |items: Vec<Value>| items.len());
+        context.register("big", || u64::MAX);
+        assert_eq!(
+            context
+                .call_function("len", &[Value::Array(vec![Value::Null])])
+                .unwrap(),
+            Value::Int(1)
+        );
+        assert_eq!(
+            context.signature("len").unwrap().to_string(),
+            "fn(array) -> int"
+        );
+        assert_eq!(
+            context.call_function("big", &[]).unwrap_err().to_string(),
+            "Value 18446744073709551615 is out of range for `i64`"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Conversions between [`Value`] and any type implementing serde's traits.
+
+mod de;
+mod ser;
+
+use std::fmt::Display;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::traits::host_function::{FromValue, IntoHostResult};
+use crate::types::Value;
+use crate::{PluginError, PluginResult};
+
+/// Converts any [`Serialize`] type into a [`Value`].
+///
+/// Structs and maps become [`Value::Object`]s, sequences and tuples become
+/// [`Value::Array`]s, and `None` and unit values become [`Value::Null`]. Enum variants
+/// follow serde's externally tagged representation: unit variants become their name as a
+/// [`Value::String`], and other variants an object with the variant name as its only key.
+///
+/// # Errors
+/// Returns `PluginError::ConversionError` if the type cannot be represented as a value,
+/// such as a map with non-scalar keys or an integer outside the range of `i64`.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> tosic_plugin_core::PluginResult<()> {
+/// use serde::{Deserialize, Serialize};
+/// use tosic_plugin_core::*;
+///
+/// #[derive(Serialize, Deserialize, Debug, PartialEq)]
+/// struct Point {
+///     x: i64,
+///     y: i64,
+/// }
+///
+/// let value = to_value(&Point { x: 1, y: 2 })?;
+/// assert_eq!(value.as_object().and_then(|point| point.get("x")), Some(&Value::Int(1)));
+///
+/// let point: Point = from_value(value)?;
+/// assert_eq!(point, Point { x: 1, y: 2 });
+/// # Ok(())
+/// # }
+/// ```
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> PluginResult<Value> {
+    value.serialize(ser::ValueSerializer)
+}
+
+/// Converts a [`Value`] into any [`DeserializeOwned`] type.
+///
+/// This is the inverse of [`to_value`]. Integers are accepted wherever floats are
+/// expected, and [`Value::Bytes`] wherever a sequence of bytes is.
+///
+/// # Errors
+/// Returns `PluginError::ConversionError` if the value does not match the shape of `T`.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> PluginResult<T> {
+    T::deserialize(value)
+}
+
+/// Host function argument or return type converted with serde.
+///
+/// As a parameter, the plugin's argument is converted with [`from_value`]; as a return
+/// type, the result is converted with [`to_value`], and a failed conversion is reported to
+/// the plugin as an error.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> tosic_plugin_core::PluginResult<()> {
+/// use serde::{Deserialize, Serialize};
+/// use tosic_plugin_core::*;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Point {
+///     x: i64,
+///     y: i64,
+/// }
+///
+/// let mut context = HostContext::new();
+/// context.register("swap", |Serde(point): Serde<Point>| Serde(Point { x: point.y, y: point.x }));
+///
+/// let result = context.call_function("swap", &[to_value(&Point { x: 1, y: 2 })?])?;
+/// assert_eq!(result.as_object().and_then(|point| point.get("x")), Some(&Value::Int(2)));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Serde<T>(pub T);
+
+impl<T: DeserializeOwned> FromValue for Serde<T> {
+    fn from_value(value: &Value) -> PluginResult<Self> {
+        from_value(value.clone()).map(Serde)
+    }
+}
+
+impl<T: Serialize> IntoHostResult for Serde<T> {
+    fn into_host_result(self) -> PluginResult<Value> {
+        to_value(&self.0)
+    }
+}
+
+impl serde::ser::Error for PluginError {
+    fn custom<T: Display>(msg: T) -> Self {
+        PluginError::ConversionError(msg.to_string())
+    }
+}
+
+impl serde::de::Error for PluginError {
+    fn custom<T: Display>(msg: T) -> Self {
+        PluginError::ConversionError(msg.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::types::Map;
+
+    fn object<const N: usize>(entries: [(&str, Value); N]) -> Value {
+        Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Line(i64, i64),
+        Rect { width: i64, height: i64 },
+    }
+
+    #[test]
+    fn enums_are_externally_tagged() {
+        let cases = [
+            (Shape::Empty, Value::from("Empty")),
+            (Shape::Circle(1.5), object([("Circle", Value::Float(1.5))])),
+            (
+                Shape::Line(1, 2),
+                object([("Line", Value::Array(vec![Value::Int(1), Value::Int(2)]))]),
+            ),
+            (
+                Shape::Rect {
+                    width: 3,
+                    height: 4,
+                },
+                object([(
+                    "Rect",
+                    object([("width", Value::Int(3)), ("height", Value::Int(4))]),
+                )]),
+            ),
+        ];
+        for (shape, value) in cases {
+            assert_eq!(to_value(&shape).unwrap(), value);
+            assert_eq!(from_value::<Shape>(value).unwrap(), shape);
+        }
+
+        assert_eq!(
+            from_value::<Shape>(object([("Empty", Value::Null)])).unwrap(),
+            Shape::Empty
+        );
+        assert!(from_value::<Shape>(object([("Empty", Value::Int(1))])).is_err());
+        assert!(from_value::<Shape>(Value::from("Triangle")).is_err());
+        assert!(from_value::<Shape>(Value::Int(0)).is_err());
+        assert!(
+            from_value::<Shape>(object([
+                ("Circle", Value::Float(1.0)),
+                ("Empty", Value::Null)
+            ]))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn bytes_deserialize_as_sequences() {
+        assert_eq!(
+            from_value::<Vec<u8>>(Value::Bytes(vec![1, 2, 255])).unwrap(),
+            vec![1, 2, 255]
+        );
+        assert_eq!(
+            from_value::<Vec<u16>>(Value::Bytes(vec![7])).unwrap(),
+            vec![7]
+        );
+        assert_eq!(
+            from_value::<Shape>(object([("Line", Value::Bytes(vec![1, 2]))])).unwrap(),
+            Shape::Line(1, 2)
+        );
+        assert!(from_value::<Shape>(object([("Line", Value::Bytes(vec![1, 2, 3]))])).is_err());
+
+        // A `Value` keeps its bytes rather than turning them into an array
+        assert_eq!(
+            from_value::<Value>(Value::Bytes(vec![1])).unwrap(),
+            Value::Bytes(vec![1])
+        );
+        assert_eq!(
+            to_value(&Value::Bytes(vec![1])).unwrap(),
+            Value::Bytes(vec![1])
+        );
+    }
+
+    #[test]
+    fn integers_outside_the_target_range_are_errors() {
+        assert_eq!(to_value(&(i64::MAX as u64)).unwrap(), Value::Int(i64::MAX));
+        assert_eq!(
+            to_value(&i128::from(i64::MIN)).unwrap(),
+            Value::Int(i64::MIN)
+        );
+        for value in [
+            to_value(&u64::MAX),
+            to_value(&(i128::from(i64::MIN) - 1)),
+            to_value(&u128::MAX),
+        ] {
+            assert!(matches!(value, Err(PluginError::ConversionError(_))));
+        }
+
+        assert_eq!(from_value::<u8>(Value::Int(255)).unwrap(), 255);
+        assert!(matches!(
+            from_value::<u8>(Value::Int(256)),
+            Err(PluginError::ConversionError(_))
+        ));
+        assert!(matches!(
+            from_value::<i8>(Value::Int(-129)),
+            Err(PluginError::ConversionError(_))
+        ));
+        assert!(matches!(
+            from_value::<u64>(Value::Int(-1)),
+            Err(PluginError::ConversionError(_))
+        ));
+        assert_eq!(from_value::<f64>(Value::Int(2)).unwrap(), 2.0);
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Marker;
+
+    #[test]
+    fn options_and_units_are_null() {
+        assert_eq!(to_value(&None::<i64>).unwrap(), Value::Null);
+        assert_eq!(to_value(&Some(1)).unwrap(), Value::Int(1));
+        assert_eq!(to_value(&()).unwrap(), Value::Null);
+        assert_eq!(to_value(&Marker).unwrap(), Value::Null);
+
+        assert_eq!(from_value::<Option<i64>>(Value::Null).unwrap(), None);
+        assert_eq!(from_value::<Option<i64>>(Value::Int(1)).unwrap(), Some(1));
+        from_value::<()>(Value::Null).unwrap();
+        assert_eq!(from_value::<Marker>(Value::Null).unwrap(), Marker);
+        assert!(from_value::<()>(Value::Int(0)).is_err());
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Document {
+        title: String,
+        tags: Vec<String>,
+        author: Option<Author>,
+        shapes: Vec<Shape>,
+        counts: BTreeMap<String, u32>,
+        checksum: (u8, u8),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Author {
+        name: String,
+        age: Option<u8>,
+    }
+
+    #[test]
+    fn nested_structs_round_trip() {
+        let document = Document {
+            title: "Plugins".to_string(),
+            tags: vec!["wasm".to_string(), "lua".to_string()],
+            author: Some(Author {
+                name: "Ada".to_string(),
+                age: None,
+            }),
+            shapes: vec![
+                Shape::Empty,
+                Shape::Rect {
+                    width: 1,
+                    height: 2,
+                },
+            ],
+            counts: BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)]),
+            checksum: (0xab, 0xcd),
+        };
+
+        let value = to_value(&document).unwrap();
+        let Value::Object(fields) = &value else {
+            panic!("expected an object, got {value:?}");
+        };
+        assert_eq!(
+            fields.keys().collect::<Vec<_>>(),
+            ["title", "tags", "author", "shapes", "counts", "checksum"]
+        );
+        assert_eq!(
+            fields["author"],
+            object([("name", Value::from("Ada")), ("age", Value::Null)])
+        );
+        assert_eq!(from_value::<Document>(value).unwrap(), document);
+    }
+
+    #[test]
+    fn missing_fields_are_reported_unless_optional() {
+        let mut fields: Map = [("name".to_string(), Value::from("Ada"))]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            from_value::<Author>(Value::Object(fields.clone()))
+                .unwrap()
+                .age,
+            None
+        );
+
+        fields.shift_remove("name");
+        let Err(PluginError::ConversionError(message)) =
+            from_value::<Author>(Value::Object(fields))
+        else {
+            panic!("expected a conversion error");
+        };
+        assert!(message.contains("name"), "{message}");
+    }
+
+    #[test]
+    fn serde_arguments_convert_through_the_bridge() {
+        let Serde(author) =
+            Serde::<Author>::from_value(&object([("name", Value::from("Ada"))])).unwrap();
+        assert_eq!(
+            author,
+            Author {
+                name: "Ada".to_string(),
+                age: None
+            }
+        );
+        assert!(matches!(
+            Serde(u64::MAX).into_host_result(),
+            Err(PluginError::ConversionError(_))
+        ));
+    }
+}
@@ -0,0 +1,140 @@
+//! Value-level signatures of registered host functions.
+
+use std::fmt;
+
+use crate::types::ValueKind;
+
+/// The arguments a host function takes from plugins and the value it returns, recorded
+/// when the function is registered.
+///
+/// Parameters that plugins may omit, such as trailing `Option<T>` parameters, are marked
+/// with a `?` when the signature is displayed, and the kind of the extra arguments taken by
+/// a variadic function, such as one ending with a [`Rest<T>`](crate::Rest) parameter, is
+/// prefixed with `...`.
+///
+/// Parameter and return kinds come from the [`FromValue`](crate::FromValue) and
+/// [`IntoValue`](crate::IntoValue) impls of the function's Rust types. Extractors such as
+/// [`Caller`](crate::Caller) and [`State`](crate::State) are not passed by plugins, so they
+/// are left out. Runtimes can use [`HostContext::signature`](crate::HostContext::signature)
+/// to reject mismatched imports when a plugin is loaded.
+///
+/// # Example
+///
+/// ```rust
+/// use tosic_plugin_core::*;
+///
+/// let mut context = HostContext::new();
+/// context.register("repeat", |caller: Caller, text: String, times: Option<usize>| {
+///     text.repeat(times.unwrap_or(2))
+/// });
+///
+/// let signature = context.signature("repeat").unwrap();
+/// assert_eq!(signature.arity(), 2);
+/// assert_eq!(signature.required(), 1);
+/// assert_eq!(signature.params(), &[ValueKind::String, ValueKind::Int]);
+/// assert_eq!(signature.returns(), ValueKind::String);
+/// assert_eq!(signature.to_string(), "fn(string, int?) -> string");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Signature {
+    params: Vec<ValueKind>,
+    required: usize,
+    rest: Option<ValueKind>,
+    returns: ValueKind,
+}
+
+impl Signature {
+    /// Creates a signature without parameters, returning values of kind `returns`.
+    pub fn new(returns: ValueKind) -> Self {
+        Self {
+            params: Vec::new(),
+            required: 0,
+            rest: None,
+            returns,
+        }
+    }
+
+    /// Appends a required parameter of the given kind, which also makes every parameter
+    /// before it required.
+    pub fn push_param(&mut self, kind: ValueKind) {
+        self.params.push(kind);
+        self.required = self.params.len();
+    }
+
+    /// Appends a parameter of the given kind that plugins may omit, as long as they omit
+    /// every parameter after it too.
+    pub fn push_optional_param(&mut self, kind: ValueKind) {
+        self.params.push(kind);
+    }
+
+    /// Makes the function variadic, taking any number of arguments of the given kind after
+    /// its parameters.
+    pub fn push_rest(&mut self, kind: ValueKind) {
+        self.rest = Some(kind);
+    }
+
+    /// Returns the kinds of the arguments plugins pass, in order, without the variadic ones.
+    pub fn params(&self) -> &[ValueKind] {
+        &self.params
+    }
+
+    /// Returns the kind of the argument at `index`, which is the kind of the variadic
+    /// arguments past the parameters, or `None` if the function takes no argument there.
+    pub fn param(&self, index: usize) -> Option<ValueKind> {
+        self.params.get(index).copied().or(self.rest)
+    }
+
+    /// Returns the kind of the variadic arguments, or `None` if the function is not variadic.
+    pub fn rest(&self) -> Option<ValueKind> {
+        self.rest
+    }
+
+    /// Returns the number of parameters, including optional ones but not variadic arguments.
+    pub fn arity(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Returns the maximum number of arguments plugins pass, or `None` if the function is
+    /// variadic.
+    pub fn max_arity(&self) -> Option<usize> {
+        self.rest.is_none().then_some(self.params.len())
+    }
+
+    /// Returns the number of arguments plugins must pass; the remaining trailing
+    /// parameters are optional.
+    pub fn required(&self) -> usize {
+        self.required
+    }
+
+    /// Returns true if plugins may call the function with `count` arguments.
+    pub fn accepts_arity(&self, count: usize) -> bool {
+        count >= self.required && self.max_arity().is_none_or(|max| count <= max)
+    }
+
+    /// Returns the kind of value the function returns.
+    pub fn returns(&self) -> ValueKind {
+        self.returns
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("fn(")?;
+        for (index, param) in self.params.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{param}")?;
+            if index >= self.required {
+                f.write_str("?")?;
+            }
+        }
+        if let Some(rest) = self.rest {
+            if !self.params.is_empty() {
+                f.write_str(", ")?;
+            }
+            write!(f, "...{rest}")?;
+        }
+        write!(f, ") -> {}", self.returns)
+    }
+}
@@ -0,0 +1,62 @@
+//! Typed application state injected into host functions.
+
+use std::any::type_name;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::traits::host_function::FromHostCall;
+use crate::types::HostCall;
+use crate::{PluginError, PluginResult};
+
+/// Application state of type `T` held by the [`HostContext`](crate::HostContext).
+///
+/// Taking a `State<T>` parameter injects the state added with
+/// [`HostContext::insert_state`](crate::HostContext::insert_state) instead of extracting it
+/// from the plugin's arguments, so shared resources such as connection pools or
+/// configuration need not be cloned into every host function. It dereferences to `T` and
+/// may appear anywhere in the parameter list. Calling a host function taking a state that
+/// was never added fails with `PluginError::HostError`.
+pub struct State<T>(Arc<T>);
+
+impl<T> State<T> {
+    pub(crate) fn new(state: Arc<T>) -> Self {
+        Self(state)
+    }
+
+    /// Returns the shared state.
+    pub fn into_inner(self) -> Arc<T> {
+        self.0
+    }
+}
+
+impl<T> Clone for State<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Deref for State<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for State<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("State").field(&self.0).finish()
+    }
+}
+
+impl<T: Send + Sync + 'static> FromHostCall for State<T> {
+    fn from_host_call(call: &mut HostCall<'_>) -> PluginResult<Self> {
+        call.context().state().ok_or_else(|| {
+            PluginError::HostError(format!(
+                "no state of type `{}` was added to the host context",
+                type_name::<T>()
+            ))
+        })
+    }
+}
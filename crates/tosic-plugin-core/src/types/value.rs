@@ -1,11 +1,57 @@
 //! Value type for plugin data exchange.
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use crate::{PluginResult, PluginError};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use indexmap::IndexMap;
+
+use crate::{PluginError, PluginResult};
+
+/// Map held by [`Value::Object`], which keeps its keys in insertion order.
+///
+/// Entries are removed with `shift_remove`, which preserves the order of the remaining keys,
+/// or with the faster `swap_remove`, which moves the last entry into the removed one's place.
+pub type Map = IndexMap<String, Value>;
 
 /// Boundary type for passing values between the host and plugin runtime.
 /// This enum represents all possible values that can cross the plugin boundary.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// # Equality and ordering
+///
+/// Values are totally ordered, so they can be used as keys of hash maps and ordered maps:
+///
+/// - Values of different variants are ordered by variant, from `Null` to `Object`, so an
+///   `Int` never equals a `Float`
+/// - Floats are compared with [`f64::total_cmp`], so they are equal only if their bits are:
+///   `NaN` equals itself, `-0.0` is less than `0.0`, and negative `NaN`s sort before all
+///   other floats while positive ones sort after
+/// - Objects keep the insertion order of their keys, which runtimes preserve when passing
+///   them to plugins, but two objects with the same entries are equal regardless of order,
+///   and compare as their entries sorted by key
+///
+/// Equal values have the same [`canonical encoding`](Value::encode_canonical).
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::HashSet;
+/// use tosic_plugin_core::*;
+///
+/// let a = Value::Object(Map::from([("x".to_string(), Value::Int(1)), ("y".to_string(), Value::Int(2))]));
+/// let b = Value::Object(Map::from([("y".to_string(), Value::Int(2)), ("x".to_string(), Value::Int(1))]));
+/// assert_eq!(a, b);
+/// assert_eq!(a.as_object().unwrap().keys().next().unwrap(), "x");
+///
+/// assert_eq!(Value::Float(f64::NAN), Value::Float(f64::NAN));
+/// assert!(Value::Float(-0.0) < Value::Float(0.0));
+/// assert!(Value::Null < Value::Bool(false));
+///
+/// let set: HashSet<Value> = [a, b, Value::Float(f64::NAN)].into_iter().collect();
+/// assert_eq!(set.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
 pub enum Value {
     /// Represents a null/none value.
     Null,
@@ -21,11 +67,72 @@ pub enum Value {
     Bytes(Vec<u8>),
     /// Array of values.
     Array(Vec<Value>),
-    /// Object/map with string keys and Value values.
-    Object(HashMap<String, Value>),
+    /// Object/map with string keys and Value values, in insertion order.
+    Object(Map),
+}
+
+/// The kind of a [`Value`], used to describe the values host functions accept and return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    /// Any kind of value, for types that accept or produce more than one kind.
+    Any,
+    /// [`Value::Null`].
+    Null,
+    /// [`Value::Bool`].
+    Bool,
+    /// [`Value::Int`].
+    Int,
+    /// [`Value::Float`].
+    Float,
+    /// [`Value::String`].
+    String,
+    /// [`Value::Bytes`].
+    Bytes,
+    /// [`Value::Array`].
+    Array,
+    /// [`Value::Object`].
+    Object,
+}
+
+impl ValueKind {
+    /// Returns true if values of kind `other` are of this kind. [`ValueKind::Any`] matches
+    /// every kind, on either side.
+    pub fn matches(self, other: ValueKind) -> bool {
+        self == other || self == ValueKind::Any || other == ValueKind::Any
+    }
+}
+
+impl fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ValueKind::Any => "any",
+            ValueKind::Null => "null",
+            ValueKind::Bool => "bool",
+            ValueKind::Int => "int",
+            ValueKind::Float => "float",
+            ValueKind::String => "string",
+            ValueKind::Bytes => "bytes",
+            ValueKind::Array => "array",
+            ValueKind::Object => "object",
+        })
+    }
 }
 
 impl Value {
+    /// Returns the kind of the value.
+    pub fn kind(&self) -> ValueKind {
+        match self {
+            Value::Null => ValueKind::Null,
+            Value::Bool(_) => ValueKind::Bool,
+            Value::Int(_) => ValueKind::Int,
+            Value::Float(_) => ValueKind::Float,
+            Value::String(_) => ValueKind::String,
+            Value::Bytes(_) => ValueKind::Bytes,
+            Value::Array(_) => ValueKind::Array,
+            Value::Object(_) => ValueKind::Object,
+        }
+    }
+
     /// Returns true if the value is null.
     pub fn is_null(&self) -> bool {
         matches!(self, Value::Null)
@@ -80,12 +187,91 @@ impl Value {
     }
 
     /// Attempts to extract an object map.
-    pub fn as_object(&self) -> Option<&HashMap<String, Value>> {
+    pub fn as_object(&self) -> Option<&Map> {
         match self {
             Value::Object(o) => Some(o),
             _ => None,
         }
     }
+
+    /// Returns the position of the value's variant in the order of [`Value`]s.
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Int(_) => 2,
+            Value::Float(_) => 3,
+            Value::String(_) => 4,
+            Value::Bytes(_) => 5,
+            Value::Array(_) => 6,
+            Value::Object(_) => 7,
+        }
+    }
+}
+
+/// Returns the entries of an object sorted by key, which is how objects are compared,
+/// hashed and canonically encoded regardless of their insertion order.
+pub(crate) fn sorted_entries(map: &Map) -> Vec<(&String, &Value)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_unstable_by_key(|(key, _)| *key);
+    entries
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a.to_bits() == b.to_bits(),
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Bytes(a), Value::Bytes(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) => a == b,
+            // `IndexMap` equality ignores the order of the keys.
+            (Value::Object(a), Value::Object(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (Value::Float(a), Value::Float(b)) => a.total_cmp(b),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Bytes(a), Value::Bytes(b)) => a.cmp(b),
+            (Value::Array(a), Value::Array(b)) => a.cmp(b),
+            (Value::Object(a), Value::Object(b)) => sorted_entries(a).cmp(&sorted_entries(b)),
+            (a, b) => a.rank().cmp(&b.rank()),
+        }
+    }
+}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            Value::Null => {}
+            Value::Bool(b) => b.hash(state),
+            Value::Int(i) => i.hash(state),
+            Value::Float(f) => f.to_bits().hash(state),
+            Value::String(s) => s.hash(state),
+            Value::Bytes(b) => b.hash(state),
+            Value::Array(items) => items.hash(state),
+            Value::Object(map) => sorted_entries(map).hash(state),
+        }
+    }
 }
 
 impl From<bool> for Value {
@@ -130,75 +316,47 @@ impl From<&str> for Value {
     }
 }
 
-impl From<Vec<u8>> for Value {
-    fn from(value: Vec<u8>) -> Self {
-        Value::Bytes(value)
-    }
-}
-
-impl From<&[u8]> for Value {
-    fn from(value: &[u8]) -> Self {
-        Value::Bytes(value.to_vec())
-    }
-}
-
 impl From<Vec<Value>> for Value {
     fn from(value: Vec<Value>) -> Self {
         Value::Array(value)
     }
 }
 
-impl From<HashMap<String, Value>> for Value {
-    fn from(value: HashMap<String, Value>) -> Self {
+impl From<Map> for Value {
+    fn from(value: Map) -> Self {
         Value::Object(value)
     }
 }
 
-// FromValue trait implementations for extracting Rust types from plugin Values
-use crate::traits::host_function::{FromValue, IntoValue};
-
-impl FromValue for bool {
-    fn from_value(value: &Value) -> PluginResult<Self> {
-        match value {
-            Value::Bool(b) => Ok(*b),
-            _ => Err(PluginError::InvalidArgumentType),
-        }
+/// Sorts the entries by key, since a `HashMap` has no meaningful order.
+impl<S> From<HashMap<String, Value, S>> for Value {
+    fn from(value: HashMap<String, Value, S>) -> Self {
+        let mut map: Map = value.into_iter().collect();
+        map.sort_unstable_keys();
+        Value::Object(map)
     }
 }
 
-impl FromValue for i64 {
-    fn from_value(value: &Value) -> PluginResult<Self> {
-        match value {
-            Value::Int(i) => Ok(*i),
-            _ => Err(PluginError::InvalidArgumentType),
-        }
-    }
-}
+// FromValue trait implementations for extracting Rust types from plugin Values.
+// Numeric types are implemented in `numeric.rs`, and containers in `collections.rs`.
+use crate::traits::host_function::{FromValue, IntoValue};
 
-impl FromValue for i32 {
+impl FromValue for Value {
     fn from_value(value: &Value) -> PluginResult<Self> {
-        match value {
-            Value::Int(i) => Ok(*i as i32),
-            _ => Err(PluginError::InvalidArgumentType),
-        }
+        Ok(value.clone())
     }
 }
 
-impl FromValue for f64 {
+impl FromValue for bool {
     fn from_value(value: &Value) -> PluginResult<Self> {
         match value {
-            Value::Float(f) => Ok(*f),
-            _ => Err(PluginError::InvalidArgumentType),
+            Value::Bool(b) => Ok(*b),
+            _ => Err(PluginError::type_mismatch("bool", value)),
         }
     }
-}
 
-impl FromValue for f32 {
-    fn from_value(value: &Value) -> PluginResult<Self> {
-        match value {
-            Value::Float(f) => Ok(*f as f32),
-            _ => Err(PluginError::InvalidArgumentType),
-        }
+    fn expected_kind() -> ValueKind {
+        ValueKind::Bool
     }
 }
 
@@ -206,35 +364,12 @@ impl FromValue for String {
     fn from_value(value: &Value) -> PluginResult<Self> {
         match value {
             Value::String(s) => Ok(s.clone()),
-            _ => Err(PluginError::InvalidArgumentType),
-        }
-    }
-}
-
-impl FromValue for Vec<u8> {
-    fn from_value(value: &Value) -> PluginResult<Self> {
-        match value {
-            Value::Bytes(b) => Ok(b.clone()),
-            _ => Err(PluginError::InvalidArgumentType),
+            _ => Err(PluginError::type_mismatch("String", value)),
         }
     }
-}
 
-impl FromValue for Vec<Value> {
-    fn from_value(value: &Value) -> PluginResult<Self> {
-        match value {
-            Value::Array(a) => Ok(a.clone()),
-            _ => Err(PluginError::InvalidArgumentType),
-        }
-    }
-}
-
-impl FromValue for HashMap<String, Value> {
-    fn from_value(value: &Value) -> PluginResult<Self> {
-        match value {
-            Value::Object(o) => Ok(o.clone()),
-            _ => Err(PluginError::InvalidArgumentType),
-        }
+    fn expected_kind() -> ValueKind {
+        ValueKind::String
     }
 }
 
@@ -243,29 +378,9 @@ impl IntoValue for bool {
     fn into_value(self) -> Value {
         Value::Bool(self)
     }
-}
-
-impl IntoValue for i64 {
-    fn into_value(self) -> Value {
-        Value::Int(self)
-    }
-}
-
-impl IntoValue for i32 {
-    fn into_value(self) -> Value {
-        Value::Int(self as i64)
-    }
-}
 
-impl IntoValue for f64 {
-    fn into_value(self) -> Value {
-        Value::Float(self)
-    }
-}
-
-impl IntoValue for f32 {
-    fn into_value(self) -> Value {
-        Value::Float(self as f64)
+    fn produced_kind() -> ValueKind {
+        ValueKind::Bool
     }
 }
 
@@ -273,35 +388,19 @@ impl IntoValue for String {
     fn into_value(self) -> Value {
         Value::String(self)
     }
-}
 
-impl IntoValue for &str {
-    fn into_value(self) -> Value {
-        Value::String(self.to_string())
+    fn produced_kind() -> ValueKind {
+        ValueKind::String
     }
 }
 
-impl IntoValue for Vec<u8> {
-    fn into_value(self) -> Value {
-        Value::Bytes(self)
-    }
-}
-
-impl IntoValue for &[u8] {
+impl IntoValue for &str {
     fn into_value(self) -> Value {
-        Value::Bytes(self.to_vec())
+        Value::String(self.to_string())
     }
-}
 
-impl IntoValue for Vec<Value> {
-    fn into_value(self) -> Value {
-        Value::Array(self)
-    }
-}
-
-impl IntoValue for HashMap<String, Value> {
-    fn into_value(self) -> Value {
-        Value::Object(self)
+    fn produced_kind() -> ValueKind {
+        ValueKind::String
     }
 }
 
@@ -315,4 +414,100 @@ impl IntoValue for () {
     fn into_value(self) -> Value {
         Value::Null
     }
-}
\ No newline at end of file
+
+    fn produced_kind() -> ValueKind {
+        ValueKind::Null
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash(value: &Value) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn object(entries: &[(&str, Value)]) -> Value {
+        Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn variants_are_ordered_by_rank_then_contents() {
+        let ordered = [
+            Value::Null,
+            Value::Bool(false),
+            Value::Bool(true),
+            Value::Int(i64::MIN),
+            Value::Int(0),
+            Value::Float(f64::NEG_INFINITY),
+            Value::Float(-0.0),
+            Value::Float(0.0),
+            Value::Float(f64::NAN),
+            Value::from(""),
+            Value::from("a"),
+            Value::Bytes(vec![0]),
+            Value::Array(vec![Value::Int(1)]),
+            Value::Array(vec![Value::Int(1), Value::Null]),
+            object(&[("a", Value::Int(1))]),
+            object(&[("a", Value::Int(2))]),
+            object(&[("b", Value::Int(0))]),
+        ];
+        for pair in ordered.windows(2) {
+            assert!(
+                pair[0] < pair[1],
+                "{:?} should sort before {:?}",
+                pair[0],
+                pair[1]
+            );
+        }
+        // Ints and floats never compare equal, even when they hold the same number.
+        assert!(Value::Int(i64::MAX) < Value::Float(-1.0));
+        assert_ne!(Value::Int(1), Value::Float(1.0));
+    }
+
+    #[test]
+    fn floats_compare_by_bits() {
+        assert_eq!(Value::Float(f64::NAN), Value::Float(f64::NAN));
+        assert_eq!(hash(&Value::Float(f64::NAN)), hash(&Value::Float(f64::NAN)));
+        assert_ne!(Value::Float(0.0), Value::Float(-0.0));
+    }
+
+    #[test]
+    fn objects_ignore_key_order() {
+        let forward = object(&[("a", Value::Int(1)), ("b", Value::Null)]);
+        let backward = object(&[("b", Value::Null), ("a", Value::Int(1))]);
+        assert_eq!(forward, backward);
+        assert_eq!(forward.cmp(&backward), Ordering::Equal);
+        assert_eq!(hash(&forward), hash(&backward));
+
+        assert_ne!(forward, object(&[("a", Value::Int(1))]));
+        assert_ne!(
+            forward,
+            object(&[("a", Value::Int(1)), ("b", Value::Bool(false))])
+        );
+    }
+
+    #[test]
+    fn values_can_be_used_as_set_elements() {
+        let values = [
+            Value::Int(1),
+            Value::Float(1.0),
+            object(&[("x", Value::Int(1)), ("y", Value::Int(2))]),
+            object(&[("y", Value::Int(2)), ("x", Value::Int(1))]),
+            Value::Int(1),
+        ];
+        let hashed: std::collections::HashSet<_> = values.iter().collect();
+        let sorted: std::collections::BTreeSet<_> = values.iter().collect();
+        assert_eq!(hashed.len(), 3);
+        assert_eq!(sorted.len(), 3);
+    }
+}
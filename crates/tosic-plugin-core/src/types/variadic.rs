@@ -0,0 +1,183 @@
+//! Variadic and keyword arguments of host functions.
+
+use crate::traits::host_function::{FromHostCall, FromValue};
+use crate::types::{HostCall, Map, Signature, Value, ValueKind};
+use crate::{PluginError, PluginResult};
+
+/// Host function parameter collecting every remaining argument, converting each into `T`.
+///
+/// A function taking `Rest<T>` as its last parameter is variadic: plugins may pass any
+/// number of arguments after the ones its other parameters consume, including none.
+/// Parameters after it receive no argument, so it should come last, possibly followed by
+/// extractors such as [`Caller`](crate::Caller).
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> tosic_plugin_core::PluginResult<()> {
+/// use tosic_plugin_core::*;
+///
+/// let mut context = HostContext::new();
+/// context.register("join", |separator: String, Rest(parts): Rest<String>| parts.join(&separator));
+///
+/// let args = [Value::from("-"), Value::from("a"), Value::from("b"), Value::from("c")];
+/// assert_eq!(context.call_function("join", &args)?, Value::from("a-b-c"));
+/// assert_eq!(context.signature("join").unwrap().to_string(), "fn(string, ...string) -> string");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Rest<T>(pub Vec<T>);
+
+impl<T: FromValue> FromHostCall for Rest<T> {
+    fn from_host_call(call: &mut HostCall<'_>) -> PluginResult<Self> {
+        let mut items = Vec::with_capacity(call.remaining().len());
+        while let Some(value) = call.remaining().first() {
+            let index = call.position();
+            let item = T::from_value(value).map_err(|error| PluginError::InvalidArgument {
+                function: call.function().to_string(),
+                index,
+                source: Box::new(error),
+            })?;
+            items.push(item);
+            call.next_arg();
+        }
+        Ok(Rest(items))
+    }
+
+    fn describe(signature: &mut Signature) {
+        signature.push_rest(T::expected_kind());
+    }
+}
+
+/// Host function parameter destructuring an object of keyword arguments into `T`, usually
+/// a struct deriving [`FromValue`].
+///
+/// The argument must be an object. If `T` can be built from an empty object, for instance
+/// because all its fields have defaults, plugins may omit the argument or pass `Null`
+/// when the parameter is last, and `T` is built from an empty object instead.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "derive")]
+/// # fn main() -> tosic_plugin_core::PluginResult<()> {
+/// use tosic_plugin_core::*;
+///
+/// #[derive(FromValue)]
+/// struct Options {
+///     #[value(default)]
+///     uppercase: bool,
+///     #[value(default)]
+///     suffix: String,
+/// }
+///
+/// let mut context = HostContext::new();
+/// context.register("format", |text: String, Kwargs(options): Kwargs<Options>| {
+///     let text = if options.uppercase { text.to_uppercase() } else { text };
+///     text + &options.suffix
+/// });
+///
+/// let options = Value::Object([("uppercase".to_string(), Value::Bool(true))].into());
+/// assert_eq!(context.call_function("format", &[Value::from("hi"), options])?, Value::from("HI"));
+/// assert_eq!(context.call_function("format", &[Value::from("hi")])?, Value::from("hi"));
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "derive"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Kwargs<T>(pub T);
+
+impl<T: FromValue> FromValue for Kwargs<T> {
+    fn from_value(value: &Value) -> PluginResult<Self> {
+        match value {
+            Value::Object(_) => T::from_value(value).map(Kwargs),
+            Value::Null => T::from_value(&Value::Object(Map::new())).map(Kwargs),
+            other => Err(PluginError::type_mismatch("Kwargs<_>", other)),
+        }
+    }
+
+    fn expected_kind() -> ValueKind {
+        ValueKind::Object
+    }
+
+    fn default_value() -> Option<Self> {
+        T::from_value(&Value::Object(Map::new())).ok().map(Kwargs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HostContext;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn rest_collects_the_remaining_arguments() {
+        let mut context = HostContext::new();
+        context.register("sum", |first: i64, Rest(rest): Rest<i64>| {
+            first + rest.iter().sum::<i64>()
+        });
+        let signature = context.signature("sum").unwrap();
+        assert_eq!((signature.required(), signature.max_arity()), (1, None));
+
+        assert_eq!(
+            context.call_function("sum", &[Value::Int(1)]).unwrap(),
+            Value::Int(1)
+        );
+        let args: Vec<Value> = (1..=40).map(Value::Int).collect();
+        assert_eq!(
+            context.call_function("sum", &args).unwrap(),
+            Value::Int(820)
+        );
+        assert!(matches!(
+            context.call_function("sum", &[]),
+            Err(PluginError::ArityMismatch {
+                min: 1,
+                max: None,
+                received: 0,
+                ..
+            })
+        ));
+        assert!(matches!(
+            context.call_function("sum", &[Value::Int(1), Value::Int(2), Value::from("three")]),
+            Err(PluginError::InvalidArgument { index: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn kwargs_accept_an_object_null_or_nothing() {
+        let mut context = HostContext::new();
+        context.register(
+            "options",
+            |Kwargs(options): Kwargs<BTreeMap<String, i64>>| options.len() as i64,
+        );
+        let signature = context.signature("options").unwrap();
+        assert_eq!((signature.required(), signature.max_arity()), (0, Some(1)));
+
+        let options = Value::Object(
+            [
+                ("a".to_string(), Value::Int(1)),
+                ("b".to_string(), Value::Int(2)),
+            ]
+            .into(),
+        );
+        assert_eq!(
+            context.call_function("options", &[options]).unwrap(),
+            Value::Int(2)
+        );
+        assert_eq!(
+            context.call_function("options", &[Value::Null]).unwrap(),
+            Value::Int(0)
+        );
+        assert_eq!(
+            context.call_function("options", &[]).unwrap(),
+            Value::Int(0)
+        );
+        assert!(matches!(
+            context.call_function("options", &[Value::Array(vec![])]),
+            Err(PluginError::InvalidArgument { index: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,205 @@
+//! Parsing of `#[value(...)]` attributes.
+
+use heck::{ToKebabCase, ToLowerCamelCase, ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+use syn::{Attribute, Expr, ExprPath, LitStr, Path, Result};
+
+/// Renaming rule applied by `rename_all`.
+#[derive(Clone, Copy)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    Camel,
+    Pascal,
+}
+
+impl RenameRule {
+    fn parse(lit: &LitStr) -> Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "camelCase" => Self::Camel,
+            "PascalCase" => Self::Pascal,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    "unknown rename rule, expected one of \"lowercase\", \"UPPERCASE\", \"snake_case\", \
+                     \"SCREAMING_SNAKE_CASE\", \"kebab-case\", \"camelCase\" or \"PascalCase\"",
+                ));
+            }
+        })
+    }
+
+    pub fn apply(self, name: &str) -> String {
+        match self {
+            Self::Lower => name.to_lowercase(),
+            Self::Upper => name.to_uppercase(),
+            Self::Snake => name.to_snake_case(),
+            Self::ScreamingSnake => name.to_shouty_snake_case(),
+            Self::Kebab => name.to_kebab_case(),
+            Self::Camel => name.to_lower_camel_case(),
+            Self::Pascal => name.to_upper_camel_case(),
+        }
+    }
+}
+
+/// How enum variants are represented as values.
+pub enum Tagging {
+    /// `{"Variant": content}`, or `"Variant"` for unit variants.
+    External,
+    /// `{"tag": "Variant", ...fields}`.
+    Internal { tag: String },
+    /// `{"tag": "Variant", "content": content}`.
+    Adjacent { tag: String, content: String },
+    /// The content alone.
+    Untagged,
+}
+
+/// Attributes on the deriving struct or enum.
+pub struct ContainerAttrs {
+    pub krate: Path,
+    pub rename_all: Option<RenameRule>,
+    pub tagging: Tagging,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut krate = None;
+        let mut rename_all = None;
+        let mut tag = None;
+        let mut content = None;
+        let mut untagged = None;
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("value")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    krate = Some(meta.value()?.parse::<LitStr>()?.parse::<Path>()?);
+                } else if meta.path.is_ident("rename_all") {
+                    rename_all = Some(RenameRule::parse(&meta.value()?.parse()?)?);
+                } else if meta.path.is_ident("tag") {
+                    tag = Some(meta.value()?.parse::<LitStr>()?);
+                } else if meta.path.is_ident("content") {
+                    content = Some(meta.value()?.parse::<LitStr>()?);
+                } else if meta.path.is_ident("untagged") {
+                    untagged = Some(meta.path.clone());
+                } else {
+                    return Err(meta.error("unknown container attribute, expected `crate`, `rename_all`, `tag`, `content` or `untagged`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        let tagging = match (tag, content, untagged) {
+            (None, None, None) => Tagging::External,
+            (Some(tag), None, None) => Tagging::Internal { tag: tag.value() },
+            (Some(tag), Some(content), None) => Tagging::Adjacent {
+                tag: tag.value(),
+                content: content.value(),
+            },
+            (None, Some(content), _) => {
+                return Err(syn::Error::new_spanned(content, "`content` requires `tag`"));
+            }
+            (None, None, Some(_)) => Tagging::Untagged,
+            (Some(tag), _, Some(_)) => {
+                return Err(syn::Error::new_spanned(
+                    tag,
+                    "`tag` cannot be combined with `untagged`",
+                ));
+            }
+        };
+
+        Ok(Self {
+            krate: krate.unwrap_or_else(|| syn::parse_quote!(::tosic_plugin_core)),
+            rename_all,
+            tagging,
+        })
+    }
+}
+
+/// Attributes on an enum variant.
+pub struct VariantAttrs {
+    pub rename: Option<String>,
+    pub rename_all: Option<RenameRule>,
+}
+
+impl VariantAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut rename = None;
+        let mut rename_all = None;
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("value")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("rename_all") {
+                    rename_all = Some(RenameRule::parse(&meta.value()?.parse()?)?);
+                } else {
+                    return Err(
+                        meta.error("unknown variant attribute, expected `rename` or `rename_all`")
+                    );
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(Self { rename, rename_all })
+    }
+}
+
+/// How a field missing from the object is filled in.
+pub enum FieldDefault {
+    /// With `Default::default()`.
+    Default,
+    /// By calling the given function.
+    Path(ExprPath),
+}
+
+/// Attributes on a struct or variant field.
+pub struct FieldAttrs {
+    pub rename: Option<String>,
+    pub default: Option<FieldDefault>,
+    pub skip: bool,
+    pub flatten: bool,
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut field = Self {
+            rename: None,
+            default: None,
+            skip: false,
+            flatten: false,
+        };
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("value")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    field.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("default") {
+                    field.default = Some(if meta.input.peek(syn::Token![=]) {
+                        match meta.value()?.parse::<LitStr>()?.parse::<Expr>()? {
+                            Expr::Path(path) => FieldDefault::Path(path),
+                            other => return Err(syn::Error::new_spanned(other, "expected a path to a function")),
+                        }
+                    } else {
+                        FieldDefault::Default
+                    });
+                } else if meta.path.is_ident("skip") {
+                    field.skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    field.flatten = true;
+                } else {
+                    return Err(meta.error("unknown field attribute, expected `rename`, `default`, `skip` or `flatten`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(field)
+    }
+}
@@ -0,0 +1,207 @@
+//! Code generation for `#[derive(FromValue)]`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Path, Result};
+
+use crate::attr::{FieldDefault, Tagging};
+use crate::model::{Body, Container, Field, Shape, Variant};
+
+pub fn expand(container: &Container<'_>) -> Result<TokenStream> {
+    let krate = &container.attrs.krate;
+    let ident = container.ident;
+    let type_name = ident.to_string();
+
+    let body = match &container.body {
+        Body::Struct(shape) => {
+            let construct = construct(krate, &quote!(Self), shape, &type_name, &quote!(value));
+            quote!(::core::result::Result::Ok(#construct))
+        }
+        Body::Enum(variants) => enum_body(container, variants),
+    };
+
+    let kind = crate::value_kind(
+        container,
+        |ty| quote!(<#ty as #krate::FromValue>::expected_kind()),
+    );
+    let generics = crate::bound_params(container.generics, &quote!(#krate::FromValue));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #krate::FromValue for #ident #ty_generics #where_clause {
+            fn from_value(value: &#krate::Value) -> #krate::PluginResult<Self> {
+                #body
+            }
+
+            fn expected_kind() -> #krate::ValueKind {
+                #kind
+            }
+        }
+    })
+}
+
+/// Builds `path` with the given shape out of `value`, evaluating to the constructed value
+/// or returning early with an error.
+fn construct(
+    krate: &Path,
+    path: &TokenStream,
+    shape: &Shape<'_>,
+    type_name: &str,
+    value: &TokenStream,
+) -> TokenStream {
+    match shape {
+        Shape::Named(fields) => {
+            let fields = fields
+                .iter()
+                .map(|field| named_field(krate, field, type_name, value));
+            quote! {{
+                let object = #krate::__private::object(#value, #type_name)?;
+                #path { #(#fields,)* }
+            }}
+        }
+        Shape::Tuple(types) if types.len() == 1 => {
+            let ty = types[0];
+            quote! {
+                #path(<#ty as #krate::FromValue>::from_value(#value)
+                    .map_err(|error| #krate::__private::field_error(#type_name, "0", &error))?)
+            }
+        }
+        Shape::Tuple(types) => {
+            let len = types.len();
+            let items =
+                (0..len).map(|index| quote!(#krate::__private::item(items, #type_name, #index)?));
+            quote! {{
+                let items = #krate::__private::array(#value, #type_name, #len)?;
+                #path(#(#items),*)
+            }}
+        }
+        Shape::Unit => quote! {{
+            #krate::__private::unit(#value, #type_name)?;
+            #path
+        }},
+    }
+}
+
+fn named_field(
+    krate: &Path,
+    field: &Field<'_>,
+    type_name: &str,
+    value: &TokenStream,
+) -> TokenStream {
+    let member = &field.member;
+    let ty = field.ty;
+    let key = &field.key;
+
+    let default = match &field.attrs.default {
+        Some(FieldDefault::Path(path)) => quote!(#path()),
+        Some(FieldDefault::Default) => quote!(::core::default::Default::default()),
+        None if field.attrs.skip => quote!(::core::default::Default::default()),
+        None => quote!(#krate::__private::missing::<#ty>(#type_name, #key)?),
+    };
+
+    if field.attrs.skip {
+        quote!(#member: #default)
+    } else if field.attrs.flatten {
+        quote! {
+            #member: <#ty as #krate::FromValue>::from_value(#value)
+                .map_err(|error| #krate::__private::field_error(#type_name, #key, &error))?
+        }
+    } else {
+        quote! {
+            #member: match #krate::__private::field::<#ty>(object, #type_name, #key)? {
+                ::core::option::Option::Some(field) => field,
+                ::core::option::Option::None => #default,
+            }
+        }
+    }
+}
+
+/// Builds a variant out of its content, ignoring the content of unit variants.
+fn construct_variant(
+    krate: &Path,
+    container: &Container<'_>,
+    variant: &Variant<'_>,
+    content: &TokenStream,
+) -> TokenStream {
+    let ident = variant.ident;
+    let type_name = format!("{}::{}", container.ident, ident);
+    match &variant.shape {
+        Shape::Unit => quote!(Self::#ident),
+        shape => construct(krate, &quote!(Self::#ident), shape, &type_name, content),
+    }
+}
+
+fn enum_body(container: &Container<'_>, variants: &[Variant<'_>]) -> TokenStream {
+    let krate = &container.attrs.krate;
+    let type_name = container.ident.to_string();
+
+    match &container.attrs.tagging {
+        Tagging::External => {
+            let arms = variants.iter().map(|variant| {
+                let name = &variant.name;
+                let construct = construct_variant(krate, container, variant, &quote!(content));
+                quote!((#name, content) => ::core::result::Result::Ok(#construct))
+            });
+            quote! {
+                match #krate::__private::external_variant(value, #type_name)? {
+                    #(#arms,)*
+                    (name, _) => ::core::result::Result::Err(#krate::__private::unknown_variant(#type_name, name)),
+                }
+            }
+        }
+        Tagging::Internal { tag } => {
+            let arms = variants.iter().map(|variant| {
+                let name = &variant.name;
+                let construct = construct_variant(krate, container, variant, &quote!(value));
+                quote!(#name => ::core::result::Result::Ok(#construct))
+            });
+            quote! {
+                let object = #krate::__private::object(value, #type_name)?;
+                match #krate::__private::tag(object, #type_name, #tag)? {
+                    #(#arms,)*
+                    name => ::core::result::Result::Err(#krate::__private::unknown_variant(#type_name, name)),
+                }
+            }
+        }
+        Tagging::Adjacent { tag, content } => {
+            let arms = variants.iter().map(|variant| {
+                let name = &variant.name;
+                let construct = construct_variant(krate, container, variant, &quote!(content));
+                quote!(#name => ::core::result::Result::Ok(#construct))
+            });
+            quote! {
+                let object = #krate::__private::object(value, #type_name)?;
+                let content = #krate::__private::content(object, #content);
+                match #krate::__private::tag(object, #type_name, #tag)? {
+                    #(#arms,)*
+                    name => ::core::result::Result::Err(#krate::__private::unknown_variant(#type_name, name)),
+                }
+            }
+        }
+        Tagging::Untagged => {
+            let attempts = variants.iter().map(|variant| {
+                let construct = match &variant.shape {
+                    Shape::Unit => {
+                        let ident = variant.ident;
+                        quote!({
+                            #krate::__private::unit(value, #type_name)?;
+                            Self::#ident
+                        })
+                    }
+                    _ => construct_variant(krate, container, variant, &quote!(value)),
+                };
+                quote! {
+                    let attempt = || -> #krate::PluginResult<Self> { ::core::result::Result::Ok(#construct) };
+                    if let ::core::result::Result::Ok(variant) = attempt() {
+                        return ::core::result::Result::Ok(variant);
+                    }
+                }
+            });
+            quote! {
+                #(#attempts)*
+                ::core::result::Result::Err(#krate::__private::no_variant(#type_name, value))
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Code generation for the `#[host_function]` attribute.
+
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::meta::ParseNestedMeta;
+use syn::{Expr, FnArg, ItemFn, Lit, LitStr, Meta, Pat, Path, Result, ReturnType, Type};
+
+/// Arguments of the attribute.
+pub struct HostFunctionArgs {
+    krate: Path,
+    name: Option<LitStr>,
+}
+
+impl Default for HostFunctionArgs {
+    fn default() -> Self {
+        Self {
+            krate: syn::parse_quote!(::tosic_plugin_core),
+            name: None,
+        }
+    }
+}
+
+impl HostFunctionArgs {
+    pub fn parse(&mut self, meta: &ParseNestedMeta<'_>) -> Result<()> {
+        if meta.path.is_ident("crate") {
+            self.krate = meta.value()?.parse::<LitStr>()?.parse()?;
+        } else if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("unknown host function attribute, expected `name` or `crate`"));
+        }
+        Ok(())
+    }
+}
+
+pub fn expand(args: &HostFunctionArgs, function: &ItemFn) -> Result<TokenStream> {
+    let sig = &function.sig;
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "host functions cannot be generic",
+        ));
+    }
+    if let Some(receiver) = sig.receiver() {
+        return Err(syn::Error::new_spanned(
+            receiver,
+            "host functions cannot take `self`",
+        ));
+    }
+
+    let krate = &args.krate;
+    let ident = &sig.ident;
+    let vis = &function.vis;
+    let name = args
+        .name
+        .as_ref()
+        .map_or_else(|| ident.to_string(), LitStr::value);
+
+    let doc = doc_comment(&function.attrs).map(|doc| quote!(.with_doc(#doc)));
+    let params = sig.inputs.iter().filter_map(|input| match input {
+        FnArg::Typed(param) => {
+            let param_name = param_name(&param.pat);
+            let ty = type_name(&param.ty);
+            Some(quote!(.with_param(#param_name, #ty)))
+        }
+        FnArg::Receiver(_) => None,
+    });
+    let returns = match &sig.output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ty) => {
+            let ty = type_name(ty);
+            Some(quote!(.with_returns(#ty)))
+        }
+    };
+    let register = if sig.asyncness.is_some() {
+        quote!(register_async_with_metadata)
+    } else {
+        quote!(register_with_metadata)
+    };
+
+    let module_doc =
+        format!("Metadata and registration of the [`{ident}`](fn@{ident}) host function.");
+    let register_doc =
+        format!("Registers the function into `context` as `{name}`, along with its metadata.");
+
+    Ok(quote! {
+        #function
+
+        #[doc = #module_doc]
+        #vis mod #ident {
+            /// Returns the metadata describing the function.
+            pub fn metadata() -> #krate::HostFunctionMetadata {
+                #krate::HostFunctionMetadata::new(#name) #doc #(#params)* #returns
+            }
+
+            #[doc = #register_doc]
+            pub fn register(context: &mut #krate::HostContext) {
+                context.#register(metadata(), super::#ident);
+            }
+        }
+    })
+}
+
+/// Joins the lines of the doc comment, dropping the space that follows `///`.
+fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(lit) => Some(lit.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| {
+            line.strip_prefix(' ')
+                .map_or_else(|| line.clone(), str::to_string)
+        })
+        .collect();
+
+    (!lines.is_empty()).then(|| lines.join("\n").trim().to_string())
+}
+
+/// Names a parameter after its binding, or after its pattern when it destructures, except
+/// for wrappers such as `Rest(items)`, which are named after the single binding they unwrap.
+fn param_name(pat: &Pat) -> String {
+    match pat {
+        Pat::Ident(pat) => pat.ident.to_string().trim_start_matches("r#").to_string(),
+        Pat::TupleStruct(pat) if pat.elems.len() == 1 && matches!(pat.elems[0], Pat::Ident(_)) => {
+            param_name(&pat.elems[0])
+        }
+        other => tokens_to_string(other),
+    }
+}
+
+/// Renders a type as written, with the spacing `quote` inserts removed.
+fn type_name(ty: &Type) -> String {
+    tokens_to_string(ty)
+}
+
+fn tokens_to_string(tokens: &impl ToTokens) -> String {
+    let mut rendered = tokens.to_token_stream().to_string();
+    for (spaced, tight) in [
+        (" :: ", "::"),
+        (":: ", "::"),
+        (" < ", "<"),
+        ("< ", "<"),
+        (" <", "<"),
+        (" >", ">"),
+        (" ,", ","),
+        ("& ", "&"),
+        ("( ", "("),
+        (" )", ")"),
+        ("[ ", "["),
+        (" ]", "]"),
+        (" ;", ";"),
+    ] {
+        rendered = rendered.replace(spaced, tight);
+    }
+    rendered
+}
@@ -0,0 +1,168 @@
+//! Code generation for `#[derive(IntoValue)]`.
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Path, Result};
+
+use crate::attr::Tagging;
+use crate::model::{Body, Container, Field, Shape, Variant};
+
+pub fn expand(container: &Container<'_>) -> Result<TokenStream> {
+    let krate = &container.attrs.krate;
+    let ident = container.ident;
+
+    let body = match &container.body {
+        Body::Struct(shape) => {
+            let (pattern, bindings) = destructure(&quote!(Self), shape);
+            let content = content(krate, shape, &bindings);
+            quote! {
+                let #pattern = self;
+                #content
+            }
+        }
+        Body::Enum(variants) => {
+            let arms = variants
+                .iter()
+                .map(|variant| variant_arm(container, variant));
+            quote! {
+                match self {
+                    #(#arms,)*
+                }
+            }
+        }
+    };
+
+    let kind = crate::value_kind(
+        container,
+        |ty| quote!(<#ty as #krate::IntoValue>::produced_kind()),
+    );
+    let generics = crate::bound_params(container.generics, &quote!(#krate::IntoValue));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #krate::IntoValue for #ident #ty_generics #where_clause {
+            fn into_value(self) -> #krate::Value {
+                #body
+            }
+
+            fn produced_kind() -> #krate::ValueKind {
+                #kind
+            }
+        }
+    })
+}
+
+/// Returns a pattern destructuring `path` with the given shape, and the identifiers bound
+/// to each field. Skipped fields are not bound.
+fn destructure(path: &TokenStream, shape: &Shape<'_>) -> (TokenStream, Vec<Ident>) {
+    match shape {
+        Shape::Named(fields) => {
+            let bound: Vec<_> = fields.iter().filter(|field| !field.attrs.skip).collect();
+            let bindings: Vec<_> = (0..bound.len())
+                .map(|index| format_ident!("__field{}", index))
+                .collect();
+            let members = bound.iter().map(|field| &field.member);
+            (quote!(#path { #(#members: #bindings,)* .. }), bindings)
+        }
+        Shape::Tuple(types) => {
+            let bindings: Vec<_> = (0..types.len())
+                .map(|index| format_ident!("__field{}", index))
+                .collect();
+            (quote!(#path(#(#bindings),*)), bindings)
+        }
+        Shape::Unit => (quote!(#path), Vec::new()),
+    }
+}
+
+/// Converts the bound fields of a shape into a value.
+fn content(krate: &Path, shape: &Shape<'_>, bindings: &[Ident]) -> TokenStream {
+    match shape {
+        Shape::Named(fields) => {
+            let object = object(krate, fields, bindings, None);
+            quote!(#krate::Value::Object(#object))
+        }
+        Shape::Tuple(_) if bindings.len() == 1 => {
+            let binding = &bindings[0];
+            quote!(#krate::IntoValue::into_value(#binding))
+        }
+        Shape::Tuple(_) => quote! {
+            #krate::Value::Array(::std::vec![#(#krate::IntoValue::into_value(#bindings)),*])
+        },
+        Shape::Unit => quote!(#krate::Value::Null),
+    }
+}
+
+/// Builds the object holding named fields, starting with `tag` when given.
+fn object(
+    krate: &Path,
+    fields: &[Field<'_>],
+    bindings: &[Ident],
+    tag: Option<(&str, &str)>,
+) -> TokenStream {
+    let tag = tag.map(|(tag, name)| {
+        quote! {
+            object.insert(::std::string::String::from(#tag), #krate::Value::String(::std::string::String::from(#name)));
+        }
+    });
+    let inserts = fields.iter().filter(|field| !field.attrs.skip).zip(bindings).map(|(field, binding)| {
+        let key = &field.key;
+        if field.attrs.flatten {
+            quote!(#krate::__private::flatten(&mut object, #key, #krate::IntoValue::into_value(#binding));)
+        } else {
+            quote!(object.insert(::std::string::String::from(#key), #krate::IntoValue::into_value(#binding));)
+        }
+    });
+    quote! {{
+        let mut object = #krate::__private::Object::new();
+        #tag
+        #(#inserts)*
+        object
+    }}
+}
+
+fn variant_arm(container: &Container<'_>, variant: &Variant<'_>) -> TokenStream {
+    let krate = &container.attrs.krate;
+    let ident = variant.ident;
+    let name = &variant.name;
+    let (pattern, bindings) = destructure(&quote!(Self::#ident), &variant.shape);
+
+    let value = match (&container.attrs.tagging, &variant.shape) {
+        (Tagging::External, Shape::Unit) => {
+            quote!(#krate::Value::String(::std::string::String::from(#name)))
+        }
+        (Tagging::External, shape) => {
+            let content = content(krate, shape, &bindings);
+            quote!(#krate::__private::tagged(#name, #content))
+        }
+        (Tagging::Internal { tag }, Shape::Named(fields)) => {
+            let object = object(krate, fields, &bindings, Some((tag, name)));
+            quote!(#krate::Value::Object(#object))
+        }
+        (Tagging::Internal { tag }, _) => {
+            let object = object(krate, &[], &[], Some((tag, name)));
+            quote!(#krate::Value::Object(#object))
+        }
+        (
+            Tagging::Adjacent {
+                tag,
+                content: content_key,
+            },
+            shape,
+        ) => {
+            let content = (!matches!(shape, Shape::Unit)).then(|| {
+                let content = content(krate, shape, &bindings);
+                quote!(object.insert(::std::string::String::from(#content_key), #content);)
+            });
+            quote! {{
+                let mut object = #krate::__private::Object::new();
+                object.insert(::std::string::String::from(#tag), #krate::Value::String(::std::string::String::from(#name)));
+                #content
+                #krate::Value::Object(object)
+            }}
+        }
+        (Tagging::Untagged, shape) => content(krate, shape, &bindings),
+    };
+
+    quote!(#pattern => #value)
+}
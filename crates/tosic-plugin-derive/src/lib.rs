@@ -0,0 +1,240 @@
+//! Procedural macros for the tosic-plugin system.
+//!
+//! `#[derive(FromValue)]` and `#[derive(IntoValue)]` implement the traits of the same names
+//! from `tosic-plugin-core`, so the type can be passed to and returned from host functions
+//! and plugins without hand-written conversions. The [`macro@host_function`] attribute
+//! turns a Rust function into a host function registered along with its metadata. All of
+//! them are re-exported by `tosic-plugin-core` behind its `derive` feature.
+//!
+//! # Representation
+//!
+//! - Structs with named fields become a `Value::Object` keyed by field name, in
+//!   declaration order.
+//! - Newtype structs are represented by their single field.
+//! - Tuple structs become a `Value::Array` of their fields.
+//! - Unit structs become `Value::Null`.
+//! - Enums are externally tagged by default: unit variants become their name as a
+//!   `Value::String`, and other variants an object with the variant name as its only key.
+//!
+//! # Attributes
+//!
+//! Container attributes:
+//!
+//! - `#[value(rename_all = "...")]`: Rename all fields, or all variants of an enum, with one
+//!   of `"lowercase"`, `"UPPERCASE"`, `"snake_case"`, `"SCREAMING_SNAKE_CASE"`,
+//!   `"kebab-case"`, `"camelCase"` or `"PascalCase"`
+//! - `#[value(tag = "type")]`: Internally tag an enum, storing the variant name under the
+//!   given key next to the fields of struct variants
+//! - `#[value(tag = "type", content = "data")]`: Adjacently tag an enum, storing the
+//!   variant's content under a separate key
+//! - `#[value(untagged)]`: Represent an enum by its content alone, trying each variant in
+//!   order when converting from a value
+//! - `#[value(crate = "tosic_plugin")]`: Path of the crate providing the traits, for crates
+//!   depending on `tosic-plugin` rather than `tosic-plugin-core`
+//!
+//! Variant attributes:
+//!
+//! - `#[value(rename = "...")]`: Use the given name for the variant
+//! - `#[value(rename_all = "...")]`: Rename all fields of a struct variant
+//!
+//! Field attributes:
+//!
+//! - `#[value(rename = "...")]`: Use the given key for the field
+//! - `#[value(default)]`: Use `Default::default()` if the field is missing
+//! - `#[value(default = "path")]`: Call the given function if the field is missing
+//! - `#[value(skip)]`: Leave the field out of the value, and fill it in with its default
+//! - `#[value(flatten)]`: Merge the field's own object into the parent object
+//!
+//! Missing fields without a default are only accepted if their type converts from
+//! `Value::Null`. Conversion errors name the type and field that failed.
+//!
+//! # Example
+//!
+//! ```rust
+//! # fn main() -> tosic_plugin_core::PluginResult<()> {
+//! use tosic_plugin_core::*;
+//! use tosic_plugin_derive::{FromValue, IntoValue};
+//!
+//! #[derive(FromValue, IntoValue, Debug, PartialEq)]
+//! #[value(rename_all = "camelCase")]
+//! struct Job {
+//!     job_name: String,
+//!     #[value(default)]
+//!     retries: i64,
+//!     schedule: Schedule,
+//! }
+//!
+//! #[derive(FromValue, IntoValue, Debug, PartialEq)]
+//! #[value(tag = "kind")]
+//! enum Schedule {
+//!     Once,
+//!     Every { seconds: i64 },
+//! }
+//!
+//! let job = Job {
+//!     job_name: "backup".to_string(),
+//!     retries: 3,
+//!     schedule: Schedule::Every { seconds: 60 },
+//! };
+//! let value = job.into_value();
+//! assert_eq!(value.as_object().and_then(|job| job.get("jobName")), Some(&Value::from("backup")));
+//!
+//! let job = Job::from_value(&value)?;
+//! assert_eq!(job.schedule, Schedule::Every { seconds: 60 });
+//! # Ok(())
+//! # }
+//! ```
+
+// Strict linting for release builds
+#![cfg_attr(not(debug_assertions), deny(missing_docs))]
+#![cfg_attr(not(debug_assertions), deny(clippy::all))]
+#![cfg_attr(not(debug_assertions), deny(unsafe_code))]
+#![cfg_attr(not(debug_assertions), deny(unused))]
+
+mod attr;
+mod from_value;
+mod host_function;
+mod into_value;
+mod model;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    DeriveInput, GenericParam, Generics, ItemFn, Path, Type, parse_macro_input, parse_quote,
+};
+
+use crate::attr::Tagging;
+use crate::host_function::HostFunctionArgs;
+use crate::model::{Body, Container, Shape};
+
+/// Derives `FromValue`, converting a `Value` into the type.
+///
+/// See the [crate documentation](crate) for the representation and supported attributes.
+#[proc_macro_derive(FromValue, attributes(value))]
+pub fn derive_from_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    Container::parse(&input)
+        .and_then(|container| from_value::expand(&container))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `IntoValue`, converting the type into a `Value`.
+///
+/// See the [crate documentation](crate) for the representation and supported attributes.
+#[proc_macro_derive(IntoValue, attributes(value))]
+pub fn derive_into_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    Container::parse(&input)
+        .and_then(|container| into_value::expand(&container))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Turns a function into a host function that registers itself along with its metadata.
+///
+/// The function is left as is, and a module of the same name is generated next to it with:
+///
+/// - `metadata()`: the `HostFunctionMetadata` holding the function's name, doc comment,
+///   parameter names and types, and return type
+/// - `register(&mut HostContext)`: registers the function with
+///   `HostContext::register_with_metadata`, or `register_async_with_metadata` for
+///   `async fn`s
+///
+/// The function must be a free function declared at module level, and must not be generic.
+///
+/// # Attributes
+///
+/// - `#[host_function(name = "...")]`: Register the function under the given name instead
+///   of its own
+/// - `#[host_function(crate = "tosic_plugin")]`: Path of the crate providing
+///   `HostContext`, for crates depending on `tosic-plugin` rather than `tosic-plugin-core`
+///
+/// # Example
+///
+/// ```rust
+/// use tosic_plugin_core::HostContext;
+/// use tosic_plugin_derive::host_function;
+///
+/// /// Adds two numbers.
+/// #[host_function]
+/// fn add(a: i64, b: i64) -> i64 {
+///     a + b
+/// }
+///
+/// fn main() {
+///     let mut context = HostContext::new();
+///     add::register(&mut context);
+///
+///     let metadata = context.metadata("add").unwrap();
+///     assert_eq!(metadata.doc(), Some("Adds two numbers."));
+///     assert_eq!(metadata.params()[0].ty(), "i64");
+///     assert_eq!(add(1, 2), 3);
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn host_function(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut parsed = HostFunctionArgs::default();
+    let parser = syn::meta::parser(|meta| parsed.parse(&meta));
+    parse_macro_input!(args with parser);
+    let function = parse_macro_input!(input as ItemFn);
+
+    host_function::expand(&parsed, &function)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Adds `bound` to every type parameter of `generics`.
+fn bound_params(generics: &Generics, bound: &TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    for param in &mut generics.params {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(#bound));
+        }
+    }
+    generics
+}
+
+/// Returns an expression evaluating to the `ValueKind` the container is represented as,
+/// using `inner` for the kind of a newtype's field.
+fn value_kind(container: &Container<'_>, inner: impl Fn(&Type) -> TokenStream2) -> TokenStream2 {
+    let krate: &Path = &container.attrs.krate;
+    let shape_kind = |shape: &Shape<'_>| match shape {
+        Shape::Named(_) => Some(quote!(Object)),
+        Shape::Tuple(types) if types.len() == 1 => None,
+        Shape::Tuple(_) => Some(quote!(Array)),
+        Shape::Unit => Some(quote!(Null)),
+    };
+
+    let kind = match &container.body {
+        Body::Struct(Shape::Tuple(types)) if types.len() == 1 => return inner(types[0]),
+        Body::Struct(shape) => shape_kind(shape),
+        Body::Enum(_)
+            if matches!(
+                container.attrs.tagging,
+                Tagging::Internal { .. } | Tagging::Adjacent { .. }
+            ) =>
+        {
+            Some(quote!(Object))
+        }
+        Body::Enum(variants)
+            if matches!(container.attrs.tagging, Tagging::External) && !variants.is_empty() =>
+        {
+            let units = variants
+                .iter()
+                .filter(|variant| matches!(variant.shape, Shape::Unit))
+                .count();
+            if units == variants.len() {
+                Some(quote!(String))
+            } else if units == 0 {
+                Some(quote!(Object))
+            } else {
+                None
+            }
+        }
+        Body::Enum(_) => None,
+    };
+    let kind = kind.unwrap_or_else(|| quote!(Any));
+    quote!(#krate::ValueKind::#kind)
+}
@@ -0,0 +1,143 @@
+//! Validated view of the type a derive is applied to.
+
+use proc_macro2::Span;
+use syn::{Data, DeriveInput, Fields, Generics, Ident, Member, Result, Type};
+
+use crate::attr::{ContainerAttrs, FieldAttrs, RenameRule, Tagging, VariantAttrs};
+
+/// The deriving struct or enum.
+pub struct Container<'a> {
+    pub ident: &'a Ident,
+    pub generics: &'a Generics,
+    pub attrs: ContainerAttrs,
+    pub body: Body<'a>,
+}
+
+pub enum Body<'a> {
+    Struct(Shape<'a>),
+    Enum(Vec<Variant<'a>>),
+}
+
+/// The fields of a struct or variant.
+pub enum Shape<'a> {
+    Named(Vec<Field<'a>>),
+    Tuple(Vec<&'a Type>),
+    Unit,
+}
+
+pub struct Variant<'a> {
+    pub ident: &'a Ident,
+    /// Name of the variant in values.
+    pub name: String,
+    pub shape: Shape<'a>,
+}
+
+pub struct Field<'a> {
+    pub member: Member,
+    pub ty: &'a Type,
+    /// Key of the field in objects.
+    pub key: String,
+    pub attrs: FieldAttrs,
+}
+
+impl<'a> Container<'a> {
+    pub fn parse(input: &'a DeriveInput) -> Result<Self> {
+        let attrs = ContainerAttrs::parse(&input.attrs)?;
+
+        let body = match &input.data {
+            Data::Struct(data) => {
+                if !matches!(attrs.tagging, Tagging::External) {
+                    return Err(syn::Error::new_spanned(
+                        &input.ident,
+                        "`tag`, `content` and `untagged` only apply to enums",
+                    ));
+                }
+                Body::Struct(Shape::parse(&data.fields, attrs.rename_all)?)
+            }
+            Data::Enum(data) => {
+                let variants = data
+                    .variants
+                    .iter()
+                    .map(|variant| {
+                        let variant_attrs = VariantAttrs::parse(&variant.attrs)?;
+                        let name = variant_attrs.rename.unwrap_or_else(|| {
+                            let name = variant.ident.to_string();
+                            attrs.rename_all.map_or_else(|| name.clone(), |rule| rule.apply(&name))
+                        });
+                        let shape = Shape::parse(&variant.fields, variant_attrs.rename_all)?;
+
+                        if matches!(attrs.tagging, Tagging::Internal { .. }) && matches!(shape, Shape::Tuple(_)) {
+                            return Err(syn::Error::new_spanned(
+                                variant,
+                                "internally tagged enums support only unit and struct variants; use `content` for adjacent tagging",
+                            ));
+                        }
+
+                        Ok(Variant {
+                            ident: &variant.ident,
+                            name,
+                            shape,
+                        })
+                    })
+                    .collect::<Result<_>>()?;
+                Body::Enum(variants)
+            }
+            Data::Union(_) => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    "unions cannot be converted to or from values",
+                ));
+            }
+        };
+
+        Ok(Self {
+            ident: &input.ident,
+            generics: &input.generics,
+            attrs,
+            body,
+        })
+    }
+}
+
+impl<'a> Shape<'a> {
+    fn parse(fields: &'a Fields, rename_all: Option<RenameRule>) -> Result<Self> {
+        match fields {
+            Fields::Named(fields) => fields
+                .named
+                .iter()
+                .map(|field| {
+                    let ident = field.ident.as_ref().expect("named field has an ident");
+                    let attrs = FieldAttrs::parse(&field.attrs)?;
+                    let name = ident.to_string().trim_start_matches("r#").to_string();
+                    let key = attrs.rename.clone().unwrap_or_else(|| {
+                        rename_all.map_or_else(|| name.clone(), |rule| rule.apply(&name))
+                    });
+                    Ok(Field {
+                        member: Member::Named(ident.clone()),
+                        ty: &field.ty,
+                        key,
+                        attrs,
+                    })
+                })
+                .collect::<Result<_>>()
+                .map(Shape::Named),
+            Fields::Unnamed(fields) => {
+                if let Some(attr) = fields
+                    .unnamed
+                    .iter()
+                    .flat_map(|field| &field.attrs)
+                    .find(|attr| attr.path().is_ident("value"))
+                {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "field attributes only apply to named fields",
+                    ));
+                }
+                Ok(Shape::Tuple(
+                    fields.unnamed.iter().map(|field| &field.ty).collect(),
+                ))
+            }
+            Fields::Unit => Ok(Shape::Unit),
+        }
+    }
+}
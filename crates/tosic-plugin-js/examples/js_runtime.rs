@@ -0,0 +1,89 @@
+//! Example demonstrating the JavaScript runtime with an ES module plugin.
+//!
+//! This example shows how to:
+//! - Load an ES module as a plugin and call its exported functions
+//! - Pass objects and bytes between the host and JavaScript objects/`Uint8Array`s
+//! - Call host functions from JavaScript through the `host` object, including error handling
+//! - Call an `async` export, which is awaited before returning
+//!
+//! Run with: `cargo run -p tosic-plugin-js --example js_runtime`
+
+use tosic_plugin_core::*;
+use tosic_plugin_js::JsRuntime;
+
+const PLUGIN: &str = r#"
+export function describe(user) {
+    host.log(`describing ${user.name}`);
+    return { ...user, greeting: `Hello, ${user.name}!`, tags: ["js", "plugin"] };
+}
+
+export function checksum(bytes) {
+    return bytes.reduce((sum, byte) => (sum + byte) % 256, 0);
+}
+
+export function reverse_bytes(bytes) {
+    return Uint8Array.from(bytes).reverse();
+}
+
+export function checked_multiply(a, b) {
+    try {
+        return host.multiply(a, b);
+    } catch (e) {
+        return `host error: ${e.message}`;
+    }
+}
+
+export async function delayed_sum(a, b) {
+    return await Promise.resolve(a + b);
+}
+"#;
+
+fn main() -> PluginResult<()> {
+    println!("=== JavaScript Runtime Example ===\n");
+
+    let mut host_context = HostContext::new();
+    host_context.register("log", |message: String| {
+        println!("[HOST LOG] {}", message);
+    });
+    host_context.register("multiply", |a: i64, b: i64| -> i64 { a * b });
+
+    let runtime = JsRuntime::new();
+
+    println!("Loading plugin...");
+    let plugin = runtime.load(PLUGIN.as_bytes(), &host_context)?;
+
+    println!("1. Calling describe({{ name: \"Ada\" }}):");
+    let user = Map::from([("name".to_string(), Value::String("Ada".to_string()))]);
+    let result = runtime.call(&plugin, "describe", &[Value::Object(user)])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("2. Calling checksum and reverse_bytes with bytes [1, 2, 3]:");
+    let bytes = Value::Bytes(vec![1, 2, 3]);
+    let result = runtime.call(&plugin, "checksum", std::slice::from_ref(&bytes))?;
+    println!("   checksum: {:?}", result);
+    let result = runtime.call(&plugin, "reverse_bytes", &[bytes])?;
+    println!("   reverse_bytes: {:?}\n", result);
+
+    println!("3. Calling async delayed_sum(2, 3):");
+    let result = runtime.call(&plugin, "delayed_sum", &[Value::Int(2), Value::Int(3)])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("=== Testing Error Cases ===\n");
+
+    println!("4. Host function error caught by the plugin with try/catch:");
+    let result = runtime.call(
+        &plugin,
+        "checked_multiply",
+        &[Value::Int(2), Value::String("x".to_string())],
+    )?;
+    println!("   Result: {:?}\n", result);
+
+    println!("5. Calling non-existent function:");
+    match runtime.call(&plugin, "nonexistent", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n=== Example completed successfully! ===");
+    Ok(())
+}
@@ -0,0 +1,169 @@
+//! Conversions between plugin [`Value`]s and JavaScript values.
+//!
+//! | `Value`  | JavaScript                                                        |
+//! |----------|-------------------------------------------------------------------|
+//! | `Null`   | `null` (`undefined` also converts back to `Null`)                 |
+//! | `Bool`   | boolean                                                           |
+//! | `Int`    | number, or `BigInt` outside the safe integer range                |
+//! | `Float`  | number (an integral `Float` comes back as `Int`, except `-0.0`)   |
+//! | `String` | string                                                            |
+//! | `Bytes`  | `Uint8Array`                                                      |
+//! | `Array`  | array                                                             |
+//! | `Object` | plain object, in property order                                   |
+//!
+//! JavaScript has a single number type, so numbers with an integral value within the
+//! safe integer range convert back to `Value::Int` and all others, including `-0`, to
+//! `Value::Float`. A `Value::Float` holding an integral value such as `2.0` therefore
+//! round-trips as `Value::Int(2)`; every other value round-trips unchanged.
+
+use rquickjs::{Array, BigInt, Ctx, Object, Type, TypedArray};
+use tosic_plugin_core::{Map, PluginError, PluginResult, Value};
+
+/// Maximum nesting accepted when converting from JavaScript, guarding against cycles.
+const MAX_DEPTH: usize = 128;
+
+/// Largest integer a JavaScript number represents exactly (`Number.MAX_SAFE_INTEGER`).
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// Converts a plugin value into a JavaScript value.
+///
+/// # Errors
+/// Returns an error if the engine fails to allocate the value.
+pub fn to_js<'js>(ctx: &Ctx<'js>, value: &Value) -> rquickjs::Result<rquickjs::Value<'js>> {
+    Ok(match value {
+        Value::Null => rquickjs::Value::new_null(ctx.clone()),
+        Value::Bool(b) => rquickjs::Value::new_bool(ctx.clone(), *b),
+        Value::Int(i) => match i32::try_from(*i) {
+            Ok(i) => rquickjs::Value::new_int(ctx.clone(), i),
+            Err(_) if i.unsigned_abs() <= MAX_SAFE_INTEGER as u64 => {
+                rquickjs::Value::new_float(ctx.clone(), *i as f64)
+            }
+            Err(_) => BigInt::from_i64(ctx.clone(), *i)?.into_value(),
+        },
+        Value::Float(f) => rquickjs::Value::new_float(ctx.clone(), *f),
+        Value::String(s) => rquickjs::String::from_str(ctx.clone(), s)?.into_value(),
+        Value::Bytes(b) => TypedArray::<u8>::new_copy(ctx.clone(), b)?.into_value(),
+        Value::Array(items) => {
+            let array = Array::new(ctx.clone())?;
+            for (index, item) in items.iter().enumerate() {
+                array.set(index, to_js(ctx, item)?)?;
+            }
+            array.into_value()
+        }
+        Value::Object(map) => {
+            let object = Object::new(ctx.clone())?;
+            for (key, item) in map {
+                object.set(key.as_str(), to_js(ctx, item)?)?;
+            }
+            object.into_value()
+        }
+    })
+}
+
+/// Converts a JavaScript value into a plugin value.
+///
+/// # Errors
+/// Returns `PluginError::RuntimeError` for JavaScript values without a `Value`
+/// counterpart, such as functions, symbols, or `BigInt`s outside the `i64` range.
+pub fn from_js(value: rquickjs::Value<'_>) -> PluginResult<Value> {
+    from_js_at(value, 0)
+}
+
+fn from_js_at(value: rquickjs::Value<'_>, depth: usize) -> PluginResult<Value> {
+    if depth >= MAX_DEPTH {
+        return Err(PluginError::RuntimeError(format!(
+            "JavaScript value nesting exceeds {MAX_DEPTH} levels"
+        )));
+    }
+
+    match value.type_of() {
+        Type::Uninitialized | Type::Undefined | Type::Null => Ok(Value::Null),
+        Type::Bool => Ok(Value::Bool(value.as_bool().unwrap_or_default())),
+        Type::Int => Ok(Value::Int(i64::from(value.as_int().unwrap_or_default()))),
+        Type::Float => {
+            let f = value.as_float().unwrap_or_default();
+            let negative_zero = f == 0.0 && f.is_sign_negative();
+            if f.fract() == 0.0 && f.abs() <= MAX_SAFE_INTEGER as f64 && !negative_zero {
+                Ok(Value::Int(f as i64))
+            } else {
+                Ok(Value::Float(f))
+            }
+        }
+        Type::BigInt => {
+            let big_int = value.into_big_int().expect("value is a BigInt");
+            big_int.to_i64().map(Value::Int).map_err(js_error)
+        }
+        Type::String => {
+            let string = value.into_string().expect("value is a string");
+            string.to_string().map(Value::String).map_err(js_error)
+        }
+        Type::Array => {
+            let array = value.into_array().expect("value is an array");
+            array
+                .iter::<rquickjs::Value>()
+                .map(|item| from_js_at(item.map_err(js_error)?, depth + 1))
+                .collect::<PluginResult<Vec<_>>>()
+                .map(Value::Array)
+        }
+        Type::Object => {
+            let object = value.into_object().expect("value is an object");
+            if let Some(bytes) = object.as_typed_array::<u8>() {
+                if bytes.as_raw().is_none() {
+                    return Err(PluginError::RuntimeError(
+                        "Uint8Array buffer is detached".to_string(),
+                    ));
+                }
+                // SAFETY: the buffer is attached and no JavaScript runs while the slice is alive.
+                return Ok(Value::Bytes(unsafe { bytes.as_slice() }.to_vec()));
+            }
+
+            let mut map = Map::new();
+            for prop in object.props::<String, rquickjs::Value>() {
+                let (key, item) = prop.map_err(js_error)?;
+                map.insert(key, from_js_at(item, depth + 1)?);
+            }
+            Ok(Value::Object(map))
+        }
+        other => Err(PluginError::RuntimeError(format!(
+            "cannot convert JavaScript {other} to a plugin value"
+        ))),
+    }
+}
+
+pub(crate) fn js_error(error: rquickjs::Error) -> PluginError {
+    PluginError::RuntimeError(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(value: &Value) -> Value {
+        let runtime = rquickjs::Runtime::new().unwrap();
+        let context = rquickjs::Context::full(&runtime).unwrap();
+        context.with(|ctx| from_js(to_js(&ctx, value).unwrap()).unwrap())
+    }
+
+    #[test]
+    fn integers_round_trip_across_the_i64_range() {
+        for i in [
+            0,
+            -1,
+            i64::from(i32::MAX) + 1,
+            MAX_SAFE_INTEGER,
+            -MAX_SAFE_INTEGER,
+            i64::MAX,
+            i64::MIN,
+        ] {
+            assert_eq!(round_trip(&Value::Int(i)), Value::Int(i));
+        }
+    }
+
+    #[test]
+    fn floats_round_trip_unless_integral() {
+        for f in [2.5, -0.0, f64::INFINITY, 1e300] {
+            assert_eq!(round_trip(&Value::Float(f)), Value::Float(f));
+        }
+        assert_eq!(round_trip(&Value::Float(2.0)), Value::Int(2));
+    }
+}
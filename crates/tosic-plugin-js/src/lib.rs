@@ -0,0 +1,40 @@
+//! JavaScript runtime for the tosic-plugin system.
+//!
+//! This crate implements the [`Runtime`](tosic_plugin_core::Runtime) trait on top of the
+//! embedded [QuickJS](https://bellard.org/quickjs/) engine via
+//! [rquickjs](https://github.com/DelSkayn/rquickjs), which builds offline with no system
+//! dependencies. A plugin is an ES module: loading it evaluates the module, and calling a
+//! function invokes the export of that name, awaiting it if it returns a promise. Host
+//! functions live in the global [`HOST_OBJECT`] object. See the [`convert`] module for how
+//! [`Value`](tosic_plugin_core::Value)s map to JavaScript values.
+//!
+//! # Features
+//!
+//! - **async**: Also implement `AsyncRuntime`; modules still execute synchronously inside the returned future
+//!
+//! # Example
+//!
+//! ```rust
+//! # fn main() -> tosic_plugin_core::PluginResult<()> {
+//! use tosic_plugin_core::*;
+//! use tosic_plugin_js::JsRuntime;
+//!
+//! let mut context = HostContext::new();
+//! context.register("add", |a: i64, b: i64| a + b);
+//!
+//! let runtime = JsRuntime::new();
+//! let plugin = runtime.load(b"export function run(x) { return host.add(x, 1); }", &context)?;
+//! assert_eq!(runtime.call(&plugin, "run", &[Value::Int(41)])?, Value::Int(42));
+//! # Ok(())
+//! # }
+//! ```
+
+// Strict linting for release builds
+#![cfg_attr(not(debug_assertions), deny(missing_docs))]
+#![cfg_attr(not(debug_assertions), deny(clippy::all))]
+#![cfg_attr(not(debug_assertions), deny(unused))]
+
+pub mod convert;
+mod runtime;
+
+pub use runtime::*;
@@ -0,0 +1,208 @@
+//! QuickJS implementation of the [`Runtime`] and [`Plugin`] traits.
+
+use std::sync::Arc;
+
+use rquickjs::function::Rest;
+use rquickjs::{Context, Ctx, Exception, Function, Module, Object};
+#[cfg(feature = "async")]
+use tosic_plugin_core::AsyncRuntime;
+use tosic_plugin_core::{HostContext, Plugin, PluginError, PluginResult, Runtime, Value};
+
+use crate::convert::{from_js, js_error, to_js};
+
+/// Name of the global object holding the host functions.
+pub const HOST_OBJECT: &str = "host";
+
+/// Global under which the evaluated module's namespace is kept between calls.
+const EXPORTS_GLOBAL: &str = "__tosic_plugin_exports";
+
+/// Runtime that evaluates ES modules as plugins with the embedded QuickJS engine.
+///
+/// Loading a plugin evaluates its source as an ES module in a fresh JavaScript context;
+/// the functions it exports become the plugin's callable functions. Host functions are
+/// available through the [`HOST_OBJECT`] global, e.g. `host.log("hello")`.
+#[derive(Clone, Default)]
+pub struct JsRuntime {}
+
+impl JsRuntime {
+    /// Creates a new JavaScript runtime.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn load_sync(&self, bytes: &[u8], context: &HostContext) -> PluginResult<JsPlugin> {
+        let runtime =
+            rquickjs::Runtime::new().map_err(|e| PluginError::RuntimeError(e.to_string()))?;
+        let js_context =
+            Context::full(&runtime).map_err(|e| PluginError::RuntimeError(e.to_string()))?;
+
+        js_context.with(|ctx| {
+            install_host_object(&ctx, context).map_err(js_error)?;
+
+            let load_error = |e| PluginError::LoadError(exception_message(&ctx, e));
+            let module =
+                Module::declare(ctx.clone(), "plugin", bytes.to_vec()).map_err(load_error)?;
+            let (module, promise) = module.eval().map_err(load_error)?;
+            promise.finish::<()>().map_err(load_error)?;
+
+            let exports = module.namespace().map_err(load_error)?;
+            ctx.globals().set(EXPORTS_GLOBAL, exports).map_err(js_error)
+        })?;
+
+        Ok(JsPlugin {
+            context: js_context,
+        })
+    }
+
+    fn call_sync(
+        &self,
+        plugin: &JsPlugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        plugin.context.with(|ctx| {
+            let to_call_error = |e| PluginError::CallError {
+                function: function_name.to_string(),
+                message: exception_message(&ctx, e),
+            };
+
+            let exports: Object = ctx.globals().get(EXPORTS_GLOBAL).map_err(js_error)?;
+            let export: rquickjs::Value = exports.get(function_name).map_err(js_error)?;
+            if export.is_undefined() {
+                return Err(PluginError::FunctionNotFound(function_name.to_string()));
+            }
+            let Some(function) = export.as_function() else {
+                return Err(PluginError::CallError {
+                    function: function_name.to_string(),
+                    message: format!("export is a {}, not a function", export.type_name()),
+                });
+            };
+
+            let args = args
+                .iter()
+                .map(|arg| to_js(&ctx, arg))
+                .collect::<rquickjs::Result<Vec<_>>>()
+                .map_err(js_error)?;
+            let mut result: rquickjs::Value =
+                function.call((Rest(args),)).map_err(to_call_error)?;
+
+            // Async exports return a promise, drive the job queue until it settles.
+            if let Some(promise) = result.as_promise() {
+                result = promise.finish().map_err(to_call_error)?;
+            }
+
+            from_js(result)
+        })
+    }
+}
+
+/// An ES module loaded by a [`JsRuntime`], together with its JavaScript context.
+pub struct JsPlugin {
+    context: Context,
+}
+
+impl Plugin for JsPlugin {}
+
+impl JsPlugin {
+    /// Returns the JavaScript context the plugin runs in.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+}
+
+impl Runtime for JsRuntime {
+    type Plugin = JsPlugin;
+
+    fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        self.load_sync(bytes, context)
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+/// Modules run to completion within the returned future.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncRuntime for JsRuntime {
+    type Plugin = JsPlugin;
+
+    async fn load_async(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        self.load_sync(bytes, context)
+    }
+
+    async fn call_async(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+/// Creates the [`HOST_OBJECT`] global with a JavaScript function for every host function.
+///
+/// Host function errors are thrown as JavaScript exceptions, which plugins can catch.
+fn install_host_object(ctx: &Ctx<'_>, context: &HostContext) -> rquickjs::Result<()> {
+    let context = Arc::new(context.clone());
+    let host = Object::new(ctx.clone())?;
+
+    for name in context.function_names() {
+        host.set(
+            name.as_str(),
+            host_function(ctx, Arc::clone(&context), name.clone())?,
+        )?;
+    }
+
+    ctx.globals().set(HOST_OBJECT, host)
+}
+
+fn host_function<'js>(
+    ctx: &Ctx<'js>,
+    context: Arc<HostContext>,
+    name: String,
+) -> rquickjs::Result<Function<'js>> {
+    Function::new(
+        ctx.clone(),
+        move |ctx: Ctx<'js>, args: Rest<rquickjs::Value<'js>>| {
+            let result = args
+                .0
+                .into_iter()
+                .map(from_js)
+                .collect::<PluginResult<Vec<_>>>()
+                .and_then(|args| context.call_function(&name, &args));
+
+            match result {
+                Ok(value) => to_js(&ctx, &value),
+                Err(error) => Err(Exception::throw_message(&ctx, &error.to_string())),
+            }
+        },
+    )
+}
+
+/// Describes an engine error, taking the pending exception out of the context if there is one.
+fn exception_message(ctx: &Ctx<'_>, error: rquickjs::Error) -> String {
+    if !error.is_exception() {
+        return error.to_string();
+    }
+
+    let thrown = ctx.catch();
+    if let Some(exception) = thrown.as_exception() {
+        match (exception.message(), exception.stack()) {
+            (Some(message), Some(stack)) if !stack.is_empty() => format!("{message}\n{stack}"),
+            (Some(message), _) => message,
+            _ => "uncaught exception".to_string(),
+        }
+    } else if let Some(string) = thrown.as_string() {
+        string.to_string().unwrap_or_else(|e| e.to_string())
+    } else {
+        format!("uncaught {}", thrown.type_name())
+    }
+}
@@ -0,0 +1,93 @@
+//! Example demonstrating the Lua runtime with a configuration-style plugin.
+//!
+//! This example shows how to:
+//! - Load a Lua chunk as a plugin and call its global functions
+//! - Pass arrays, objects and bytes between the host and Lua tables/strings
+//! - Call host functions from Lua through the `host` table, including error handling
+//!
+//! Run with: `cargo run -p tosic-plugin-lua --example lua_runtime`
+
+use tosic_plugin_core::*;
+use tosic_plugin_lua::LuaRuntime;
+
+const PLUGIN: &str = r#"
+local defaults = { retries = 3, verbose = false }
+
+function configure(overrides)
+    local config = {}
+    for key, value in pairs(defaults) do config[key] = value end
+    for key, value in pairs(overrides) do config[key] = value end
+    host.log("configured with " .. config.retries .. " retries")
+    return config
+end
+
+function double_all(items)
+    local out = {}
+    for i, item in ipairs(items) do out[i] = host.multiply(item, 2) end
+    return out
+end
+
+function checked_multiply(a, b)
+    local ok, err = pcall(host.multiply, a, b)
+    if ok then return err end
+    return "host error: " .. tostring(err)
+end
+
+function ratio(a, b)
+    local ok, result = pcall(host.divide, a, b)
+    if ok then return result end
+    return "host error: " .. tostring(result)
+end
+"#;
+
+fn main() -> PluginResult<()> {
+    println!("=== Lua Runtime Example ===\n");
+
+    let mut host_context = HostContext::new();
+    host_context.register("log", |message: String| {
+        println!("[HOST LOG] {}", message);
+    });
+    host_context.register("multiply", |a: i64, b: i64| -> i64 { a * b });
+    host_context.register("divide", |a: i64, b: i64| -> Result<i64, String> {
+        a.checked_div(b)
+            .ok_or_else(|| format!("cannot divide {} by {}", a, b))
+    });
+
+    let runtime = LuaRuntime::new();
+
+    println!("Loading plugin...");
+    let plugin = runtime.load(PLUGIN.as_bytes(), &host_context)?;
+
+    println!("1. Calling configure({{ retries = 5 }}):");
+    let overrides = Map::from([("retries".to_string(), Value::Int(5))]);
+    let result = runtime.call(&plugin, "configure", &[Value::Object(overrides)])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("2. Calling double_all([1, 2, 3]):");
+    let items = Value::Array(vec![Value::Int(1), Value::Int(2), Value::Int(3)]);
+    let result = runtime.call(&plugin, "double_all", &[items])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("=== Testing Error Cases ===\n");
+
+    println!("3. Host function error caught by the plugin with pcall:");
+    let result = runtime.call(
+        &plugin,
+        "checked_multiply",
+        &[Value::Int(2), Value::String("x".to_string())],
+    )?;
+    println!("   Result: {:?}\n", result);
+
+    println!("4. Failing host function caught by the plugin with pcall:");
+    let result = runtime.call(&plugin, "ratio", &[Value::Int(1), Value::Int(0)])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("5. Calling non-existent function:");
+    match runtime.call(&plugin, "nonexistent", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n=== Example completed successfully! ===");
+    Ok(())
+}
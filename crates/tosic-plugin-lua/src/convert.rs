@@ -0,0 +1,241 @@
+//! Conversions between plugin [`Value`]s and Lua values.
+//!
+//! | `Value`           | Lua                                                        |
+//! |-------------------|------------------------------------------------------------|
+//! | `Null`            | `nil`, or the [`NULL_GLOBAL`] sentinel inside a table      |
+//! | `Bool`            | boolean                                                    |
+//! | `Int`             | integer                                                    |
+//! | `Float`           | number                                                     |
+//! | `String`, `Bytes` | string                                                     |
+//! | `Array`           | sequence table (`1..=n`)                                   |
+//! | `Object`          | table with string keys and the [`OBJECT_METATABLE`]        |
+//!
+//! Storing `nil` in a table removes the entry, so nulls inside arrays and objects are
+//! represented by `mlua::Value::NULL` instead, which plugins can compare against the
+//! [`NULL_GLOBAL`] global: `if items[2] == null then ... end`. Objects carry a metatable
+//! named [`OBJECT_METATABLE`], which tells an empty object apart from an empty array.
+//!
+//! Going back, both `nil` and the sentinel become `Value::Null`, and a Lua string becomes
+//! `Value::String` when it is valid UTF-8 and `Value::Bytes` otherwise. A table carrying
+//! the object metatable becomes an object. Otherwise, a table whose keys are positive
+//! integers becomes an array, including the empty table, with `Null` filling any holes
+//! left by `nil`s; to guard against sparse tables, its largest key may be at most twice
+//! its number of entries. Any other table must have string keys only, and becomes an
+//! object with its keys sorted, since Lua tables have no order of their own.
+
+use mlua::Lua;
+use tosic_plugin_core::{Map, PluginError, PluginResult, Value};
+
+/// Maximum table nesting accepted when converting from Lua, guarding against cycles.
+const MAX_DEPTH: usize = 128;
+
+/// Name of the global holding the sentinel that represents `Null` inside tables.
+pub const NULL_GLOBAL: &str = "null";
+
+/// `__name` of the metatable marking tables converted from objects, which is also the key
+/// it is stored under in the Lua registry.
+pub const OBJECT_METATABLE: &str = "tosic_plugin.object";
+
+/// Converts a plugin value into a Lua value owned by `lua`.
+///
+/// # Errors
+/// Returns an error if Lua fails to allocate a string or table.
+pub fn to_lua(lua: &Lua, value: &Value) -> mlua::Result<mlua::Value> {
+    Ok(match value {
+        Value::Null => mlua::Value::Nil,
+        other => to_lua_item(lua, other)?,
+    })
+}
+
+/// Converts a value stored in a table, where `nil` would remove the entry.
+fn to_lua_item(lua: &Lua, value: &Value) -> mlua::Result<mlua::Value> {
+    Ok(match value {
+        Value::Null => mlua::Value::NULL,
+        Value::Bool(b) => mlua::Value::Boolean(*b),
+        Value::Int(i) => mlua::Value::Integer(*i),
+        Value::Float(f) => mlua::Value::Number(*f),
+        Value::String(s) => mlua::Value::String(lua.create_string(s)?),
+        Value::Bytes(b) => mlua::Value::String(lua.create_string(b)?),
+        Value::Array(items) => {
+            let table = lua.create_table_with_capacity(items.len(), 0)?;
+            for (index, item) in items.iter().enumerate() {
+                table.raw_set(index + 1, to_lua_item(lua, item)?)?;
+            }
+            mlua::Value::Table(table)
+        }
+        Value::Object(map) => {
+            let table = lua.create_table_with_capacity(0, map.len())?;
+            for (key, item) in map {
+                table.raw_set(key.as_str(), to_lua_item(lua, item)?)?;
+            }
+            table.set_metatable(Some(object_metatable(lua)?))?;
+            mlua::Value::Table(table)
+        }
+    })
+}
+
+/// Returns the metatable marking objects, creating it on first use.
+fn object_metatable(lua: &Lua) -> mlua::Result<mlua::Table> {
+    if let Some(metatable) = lua.named_registry_value::<Option<mlua::Table>>(OBJECT_METATABLE)? {
+        return Ok(metatable);
+    }
+    let metatable = lua.create_table()?;
+    metatable.raw_set("__name", OBJECT_METATABLE)?;
+    lua.set_named_registry_value(OBJECT_METATABLE, &metatable)?;
+    Ok(metatable)
+}
+
+/// Returns true if the table carries the metatable marking objects.
+fn is_object(table: &mlua::Table) -> bool {
+    table.metatable().is_some_and(|metatable| {
+        matches!(metatable.raw_get::<mlua::Value>("__name"), Ok(mlua::Value::String(name)) if name == OBJECT_METATABLE)
+    })
+}
+
+/// Converts a Lua value into a plugin value.
+///
+/// # Errors
+/// Returns `PluginError::RuntimeError` for Lua values without a `Value` counterpart,
+/// such as functions, userdata, or tables with non-string keys.
+pub fn from_lua(value: mlua::Value) -> PluginResult<Value> {
+    from_lua_at(value, 0)
+}
+
+fn from_lua_at(value: mlua::Value, depth: usize) -> PluginResult<Value> {
+    match value {
+        mlua::Value::Nil => Ok(Value::Null),
+        value if value.is_null() => Ok(Value::Null),
+        mlua::Value::Boolean(b) => Ok(Value::Bool(b)),
+        mlua::Value::Integer(i) => Ok(Value::Int(i)),
+        mlua::Value::Number(f) => Ok(Value::Float(f)),
+        mlua::Value::String(s) => {
+            let bytes = s.as_bytes().to_vec();
+            Ok(String::from_utf8(bytes)
+                .map_or_else(|e| Value::Bytes(e.into_bytes()), Value::String))
+        }
+        mlua::Value::Table(table) => {
+            if depth >= MAX_DEPTH {
+                return Err(PluginError::RuntimeError(format!(
+                    "Lua table nesting exceeds {MAX_DEPTH} levels"
+                )));
+            }
+
+            let entries = table
+                .pairs::<mlua::Value, mlua::Value>()
+                .collect::<mlua::Result<Vec<_>>>()
+                .map_err(lua_error)?;
+
+            if is_object(&table) {
+                return object_from_entries(entries, depth);
+            }
+            let len = entries
+                .iter()
+                .try_fold(0, |len, (key, _)| match key {
+                    mlua::Value::Integer(i) if *i >= 1 => Some(len.max(*i as usize)),
+                    _ => None,
+                })
+                .filter(|len| *len <= entries.len() * 2);
+            let Some(len) = len else {
+                return object_from_entries(entries, depth);
+            };
+
+            let mut items = vec![Value::Null; len];
+            for (key, item) in entries {
+                if let mlua::Value::Integer(i) = key {
+                    items[i as usize - 1] = from_lua_at(item, depth + 1)?;
+                }
+            }
+            Ok(Value::Array(items))
+        }
+        other => Err(PluginError::RuntimeError(format!(
+            "cannot convert Lua {} to a plugin value",
+            other.type_name()
+        ))),
+    }
+}
+
+fn object_from_entries(
+    entries: Vec<(mlua::Value, mlua::Value)>,
+    depth: usize,
+) -> PluginResult<Value> {
+    let mut map = Map::with_capacity(entries.len());
+    for (key, item) in entries {
+        let key = match key {
+            mlua::Value::String(s) => s.to_str().map_err(lua_error)?.to_string(),
+            other => {
+                return Err(PluginError::RuntimeError(format!(
+                    "cannot convert Lua table with {} keys to a plugin object",
+                    other.type_name()
+                )));
+            }
+        };
+        map.insert(key, from_lua_at(item, depth + 1)?);
+    }
+    map.sort_unstable_keys();
+    Ok(Value::Object(map))
+}
+
+pub(crate) fn lua_error(error: mlua::Error) -> PluginError {
+    PluginError::RuntimeError(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(value: &Value) -> Value {
+        let lua = Lua::new();
+        from_lua(to_lua(&lua, value).unwrap()).unwrap()
+    }
+
+    fn eval(code: &str) -> PluginResult<Value> {
+        let lua = Lua::new();
+        lua.globals()
+            .raw_set(NULL_GLOBAL, mlua::Value::NULL)
+            .unwrap();
+        from_lua(lua.load(code).eval().unwrap())
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let values = [
+            Value::Null,
+            Value::Array(vec![Value::Int(1), Value::Null, Value::Int(2), Value::Null]),
+            Value::Array(vec![]),
+            Value::Object(Map::new()),
+            Value::Object(Map::from([
+                ("a".to_string(), Value::Null),
+                (
+                    "b".to_string(),
+                    Value::Array(vec![Value::Object(Map::new())]),
+                ),
+                ("c".to_string(), Value::Float(1.5)),
+            ])),
+        ];
+        for value in values {
+            assert_eq!(round_trip(&value), value);
+        }
+    }
+
+    #[test]
+    fn tables_from_lua_become_arrays_or_objects() {
+        assert_eq!(
+            eval("return {1, nil, 3}").unwrap(),
+            Value::Array(vec![Value::Int(1), Value::Null, Value::Int(3)])
+        );
+        assert_eq!(
+            eval("return {1, null, 3}").unwrap(),
+            Value::Array(vec![Value::Int(1), Value::Null, Value::Int(3)])
+        );
+        assert_eq!(eval("return {}").unwrap(), Value::Array(vec![]));
+        assert_eq!(
+            eval("return {b = 1, a = null}").unwrap(),
+            Value::Object(Map::from([
+                ("a".to_string(), Value::Null),
+                ("b".to_string(), Value::Int(1))
+            ]))
+        );
+        assert!(eval("return {[1000000] = true}").is_err());
+        assert!(eval("return {1, x = 2}").is_err());
+    }
+}
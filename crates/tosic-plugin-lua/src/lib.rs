@@ -0,0 +1,39 @@
+//! Lua runtime for the tosic-plugin system.
+//!
+//! This crate implements the [`Runtime`](tosic_plugin_core::Runtime) trait on top of
+//! [mlua](https://github.com/mlua-rs/mlua) with a vendored Lua 5.4, which makes it a good
+//! fit for lightweight, configuration-style plugins. A plugin is a Lua chunk: loading it
+//! runs the chunk, and calling a function invokes the global Lua function of that name.
+//! Host functions live in the global [`HOST_TABLE`] table. See the [`convert`] module for
+//! how [`Value`](tosic_plugin_core::Value)s map to Lua values.
+//!
+//! # Features
+//!
+//! - **async**: Also implement `AsyncRuntime`; chunks still execute synchronously inside the returned future
+//!
+//! # Example
+//!
+//! ```rust
+//! # fn main() -> tosic_plugin_core::PluginResult<()> {
+//! use tosic_plugin_core::*;
+//! use tosic_plugin_lua::LuaRuntime;
+//!
+//! let mut context = HostContext::new();
+//! context.register("add", |a: i64, b: i64| a + b);
+//!
+//! let runtime = LuaRuntime::new();
+//! let plugin = runtime.load(b"function run(x) return host.add(x, 1) end", &context)?;
+//! assert_eq!(runtime.call(&plugin, "run", &[Value::Int(41)])?, Value::Int(42));
+//! # Ok(())
+//! # }
+//! ```
+
+// Strict linting for release builds
+#![cfg_attr(not(debug_assertions), deny(missing_docs))]
+#![cfg_attr(not(debug_assertions), deny(clippy::all))]
+#![cfg_attr(not(debug_assertions), deny(unused))]
+
+pub mod convert;
+mod runtime;
+
+pub use runtime::*;
@@ -0,0 +1,165 @@
+//! Lua implementation of the [`Runtime`] and [`Plugin`] traits.
+
+use std::sync::Arc;
+
+use mlua::{Lua, MultiValue};
+#[cfg(feature = "async")]
+use tosic_plugin_core::AsyncRuntime;
+use tosic_plugin_core::{HostContext, Plugin, PluginError, PluginResult, Runtime, Value};
+
+use crate::convert::{NULL_GLOBAL, from_lua, lua_error, to_lua};
+
+/// Name of the global table holding the host functions.
+pub const HOST_TABLE: &str = "host";
+
+/// Runtime that executes Lua chunks as plugins.
+///
+/// Loading a plugin runs its chunk once in a fresh Lua state; the global functions it
+/// defines become the plugin's callable functions. Host functions are available to the
+/// chunk through the [`HOST_TABLE`] global, e.g. `host.log("hello")`, and the
+/// [`NULL_GLOBAL`] global holds the sentinel standing for nulls inside tables.
+#[derive(Clone, Default)]
+pub struct LuaRuntime {}
+
+impl LuaRuntime {
+    /// Creates a new Lua runtime.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn load_sync(&self, bytes: &[u8], context: &HostContext) -> PluginResult<LuaPlugin> {
+        let lua = Lua::new();
+        install_host_table(&lua, context).map_err(lua_error)?;
+        lua.globals()
+            .raw_set(NULL_GLOBAL, mlua::Value::NULL)
+            .map_err(lua_error)?;
+
+        lua.load(bytes)
+            .set_name("=plugin")
+            .exec()
+            .map_err(|e| PluginError::LoadError(e.to_string()))?;
+
+        Ok(LuaPlugin { lua })
+    }
+
+    fn call_sync(
+        &self,
+        plugin: &LuaPlugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        let lua = &plugin.lua;
+        let to_call_error = |e: mlua::Error| PluginError::CallError {
+            function: function_name.to_string(),
+            message: e.to_string(),
+        };
+
+        let function = match lua.globals().raw_get(function_name).map_err(lua_error)? {
+            mlua::Value::Function(function) => function,
+            mlua::Value::Nil => {
+                return Err(PluginError::FunctionNotFound(function_name.to_string()));
+            }
+            other => {
+                return Err(PluginError::CallError {
+                    function: function_name.to_string(),
+                    message: format!("global is a {}, not a function", other.type_name()),
+                });
+            }
+        };
+
+        let args = args
+            .iter()
+            .map(|arg| to_lua(lua, arg))
+            .collect::<mlua::Result<MultiValue>>()
+            .map_err(lua_error)?;
+        let results = function.call::<MultiValue>(args).map_err(to_call_error)?;
+
+        let mut results = results
+            .into_iter()
+            .map(from_lua)
+            .collect::<PluginResult<Vec<_>>>()?;
+        match results.len() {
+            0 => Ok(Value::Null),
+            1 => Ok(results.remove(0)),
+            _ => Ok(Value::Array(results)),
+        }
+    }
+}
+
+/// A Lua chunk loaded by a [`LuaRuntime`], together with its Lua state.
+pub struct LuaPlugin {
+    lua: Lua,
+}
+
+impl Plugin for LuaPlugin {}
+
+impl LuaPlugin {
+    /// Returns the Lua state the plugin runs in.
+    pub fn lua(&self) -> &Lua {
+        &self.lua
+    }
+}
+
+impl Runtime for LuaRuntime {
+    type Plugin = LuaPlugin;
+
+    fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        self.load_sync(bytes, context)
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+/// Lua chunks run to completion within the returned future.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncRuntime for LuaRuntime {
+    type Plugin = LuaPlugin;
+
+    async fn load_async(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        self.load_sync(bytes, context)
+    }
+
+    async fn call_async(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+/// Creates the [`HOST_TABLE`] global with a Lua function for every host function.
+///
+/// Host function errors are raised as Lua errors, which plugins can catch with `pcall`.
+fn install_host_table(lua: &Lua, context: &HostContext) -> mlua::Result<()> {
+    let context = Arc::new(context.clone());
+    let host = lua.create_table()?;
+
+    for name in context.function_names() {
+        let context = Arc::clone(&context);
+        let function_name = name.clone();
+        let function = lua.create_function(move |lua, args: MultiValue| {
+            let args = args
+                .into_iter()
+                .map(from_lua)
+                .collect::<PluginResult<Vec<_>>>()
+                .map_err(mlua::Error::external)?;
+            let result = context
+                .call_function(&function_name, &args)
+                .map_err(mlua::Error::external)?;
+            to_lua(lua, &result)
+        })?;
+        host.raw_set(name.as_str(), function)?;
+    }
+
+    lua.globals().raw_set(HOST_TABLE, host)
+}
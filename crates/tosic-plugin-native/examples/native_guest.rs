@@ -0,0 +1,21 @@
+//! Example native plugin, built as a `cdylib` and loaded by the `native_runtime` example.
+//!
+//! Build with: `cargo build -p tosic-plugin-native --example native_guest`
+
+use tosic_plugin_core::{FromValue, PluginError, PluginResult, Value};
+use tosic_plugin_native::guest::Host;
+
+fn handle(host: &Host<'_>, function: &str, args: Vec<Value>) -> PluginResult<Value> {
+    match function {
+        "echo" => Ok(Value::Array(args)),
+        "greet" => {
+            let name = String::from_value(args.first().unwrap_or(&Value::Null))?;
+            host.call("log", &[Value::from(format!("greeting {name}"))])?;
+            Ok(Value::from(format!("Hello from a native plugin, {name}!")))
+        }
+        "add" => host.call("add", &args),
+        _ => Err(PluginError::FunctionNotFound(function.to_string())),
+    }
+}
+
+tosic_plugin_native::export_plugin!(handle, name = "native-example");
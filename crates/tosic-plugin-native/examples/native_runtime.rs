@@ -0,0 +1,84 @@
+//! Example demonstrating the native runtime loading a shared library plugin.
+//!
+//! This example shows how to:
+//! - Load a native plugin from bytes, which passes the ABI version handshake
+//! - Call plugin functions that call back into host functions
+//! - Handle errors reported across the C ABI
+//!
+//! Run with:
+//! `cargo build -p tosic-plugin-native --example native_guest && cargo run -p tosic-plugin-native --example native_runtime`
+
+use std::path::PathBuf;
+use tosic_plugin_core::*;
+use tosic_plugin_native::NativeRuntime;
+
+/// Path of the `native_guest` example library next to this example's binary.
+fn guest_library() -> PathBuf {
+    let examples_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(PathBuf::from))
+        .expect("example binary has a parent directory");
+    examples_dir.join(format!(
+        "{}native_guest{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    ))
+}
+
+fn main() -> PluginResult<()> {
+    println!("=== Native Runtime Example ===\n");
+
+    let mut host_context = HostContext::new();
+    host_context.register("log", |message: String| {
+        println!("[HOST LOG] {}", message);
+    });
+    host_context.register("add", |a: i64, b: i64| -> i64 { a + b });
+
+    let path = guest_library();
+    let bytes = std::fs::read(&path).map_err(|e| {
+        PluginError::LoadError(format!(
+            "{}: {e} (build the native_guest example first)",
+            path.display()
+        ))
+    })?;
+
+    let runtime = NativeRuntime::new();
+
+    println!("Loading plugin from {} bytes...", bytes.len());
+    let plugin = runtime.load(&bytes, &host_context)?;
+    println!("Loaded plugin: {:?}\n", plugin.name());
+
+    println!("1. Calling echo(1, \"two\", [3.0]):");
+    let args = [
+        Value::Int(1),
+        Value::from("two"),
+        Value::Array(vec![Value::Float(3.0)]),
+    ];
+    let result = runtime.call(&plugin, "echo", &args)?;
+    println!("   Result: {:?}\n", result);
+
+    println!("2. Calling greet(\"World\"):");
+    let result = runtime.call(&plugin, "greet", &[Value::from("World")])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("3. Calling add(5, 3), which calls back into the host:");
+    let result = runtime.call(&plugin, "add", &[Value::Int(5), Value::Int(3)])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("=== Testing Error Cases ===\n");
+
+    println!("4. Calling add with wrong argument types:");
+    match runtime.call(&plugin, "add", &[Value::from("five"), Value::Int(3)]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n5. Calling non-existent function:");
+    match runtime.call(&plugin, "nonexistent", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n=== Example completed successfully! ===");
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! Versioned C ABI between the host and native plugin libraries.
+//!
+//! Values cross the boundary as [`Value::encode`]d byte buffers. Each buffer is freed by
+//! the side that allocated it: the host releases plugin buffers through
+//! [`PLUGIN_FREE_SYMBOL`], and the plugin releases host buffers through [`HostApi::free`].
+//!
+//! # Plugin exports
+//!
+//! - [`ABI_VERSION_SYMBOL`]: [`AbiVersionFn`], must return [`ABI_VERSION`].
+//! - [`PLUGIN_CALL_SYMBOL`]: [`PluginCallFn`], dispatches a call by function name.
+//! - [`PLUGIN_FREE_SYMBOL`]: [`PluginFreeFn`], releases a buffer returned by the plugin.
+//! - [`PLUGIN_NAME_SYMBOL`] (optional): [`PluginNameFn`], returns a static NUL-terminated name.
+//!
+//! Plugins may be called concurrently from several threads and must be thread-safe.
+
+use std::ffi::{c_char, c_void};
+use std::mem::ManuallyDrop;
+
+use tosic_plugin_core::{PluginError, PluginResult, Value};
+
+/// Version of this ABI. Bumped on every incompatible change.
+pub const ABI_VERSION: u32 = 1;
+
+/// Symbol of the plugin's [`AbiVersionFn`].
+pub const ABI_VERSION_SYMBOL: &str = "tosic_plugin_abi_version";
+
+/// Symbol of the plugin's [`PluginCallFn`].
+pub const PLUGIN_CALL_SYMBOL: &str = "tosic_plugin_call";
+
+/// Symbol of the plugin's [`PluginFreeFn`].
+pub const PLUGIN_FREE_SYMBOL: &str = "tosic_plugin_free";
+
+/// Symbol of the plugin's optional [`PluginNameFn`].
+pub const PLUGIN_NAME_SYMBOL: &str = "tosic_plugin_name";
+
+/// The call succeeded and the output buffer holds the encoded result value.
+pub const STATUS_OK: i32 = 0;
+
+/// The call failed and the output buffer holds a UTF-8 error message.
+pub const STATUS_ERROR: i32 = 1;
+
+/// The requested function does not exist; the output buffer is left empty.
+pub const STATUS_NOT_FOUND: i32 = 2;
+
+/// Returns the ABI version the plugin was built against.
+pub type AbiVersionFn = unsafe extern "C" fn() -> u32;
+
+/// Calls a plugin function.
+///
+/// Takes the host function table, the function name as UTF-8, the encoded
+/// `Value::Array` of arguments, and an output buffer. Returns one of the `STATUS_*` codes.
+pub type PluginCallFn = unsafe extern "C" fn(
+    host: *const HostApi,
+    name_ptr: *const u8,
+    name_len: usize,
+    args_ptr: *const u8,
+    args_len: usize,
+    out: *mut FfiBuffer,
+) -> i32;
+
+/// Releases a buffer allocated by the plugin.
+pub type PluginFreeFn = unsafe extern "C" fn(buffer: FfiBuffer);
+
+/// Returns the plugin's name as a static NUL-terminated string.
+pub type PluginNameFn = unsafe extern "C" fn() -> *const c_char;
+
+/// Calls a host function with the same conventions as [`PluginCallFn`].
+pub type HostCallFn = unsafe extern "C" fn(
+    data: *const c_void,
+    name_ptr: *const u8,
+    name_len: usize,
+    args_ptr: *const u8,
+    args_len: usize,
+    out: *mut FfiBuffer,
+) -> i32;
+
+/// Releases a buffer allocated by the host.
+pub type HostFreeFn = unsafe extern "C" fn(buffer: FfiBuffer);
+
+/// Function table through which a plugin calls back into the host.
+#[repr(C)]
+pub struct HostApi {
+    /// The [`ABI_VERSION`] of the host.
+    pub abi_version: u32,
+    /// Opaque host data to pass back to [`HostApi::call`].
+    pub data: *const c_void,
+    /// Calls a host function.
+    pub call: HostCallFn,
+    /// Releases a buffer returned by [`HostApi::call`].
+    pub free: HostFreeFn,
+}
+
+/// An owned byte buffer passed across the boundary.
+///
+/// The layout mirrors a Rust `Vec<u8>` so either side can hand over its allocation
+/// without copying; the buffer must be released by the side that allocated it.
+#[repr(C)]
+#[derive(Debug)]
+pub struct FfiBuffer {
+    /// Pointer to the first byte, dangling when `capacity` is zero.
+    pub ptr: *mut u8,
+    /// Number of initialized bytes.
+    pub len: usize,
+    /// Allocated capacity.
+    pub capacity: usize,
+}
+
+impl FfiBuffer {
+    /// Returns an empty buffer that owns no allocation.
+    pub const fn empty() -> Self {
+        Self {
+            ptr: std::ptr::NonNull::dangling().as_ptr(),
+            len: 0,
+            capacity: 0,
+        }
+    }
+
+    /// Transfers ownership of a vector into a buffer.
+    pub fn from_vec(bytes: Vec<u8>) -> Self {
+        let mut bytes = ManuallyDrop::new(bytes);
+        Self {
+            ptr: bytes.as_mut_ptr(),
+            len: bytes.len(),
+            capacity: bytes.capacity(),
+        }
+    }
+
+    /// Reclaims a buffer created by [`FfiBuffer::from_vec`] in the same binary.
+    ///
+    /// # Safety
+    /// The buffer must have been created by [`FfiBuffer::from_vec`] with the same allocator
+    /// and must not be used afterwards.
+    pub unsafe fn into_vec(self) -> Vec<u8> {
+        // SAFETY: upheld by the caller.
+        unsafe { Vec::from_raw_parts(self.ptr, self.len, self.capacity) }
+    }
+
+    /// Views the initialized bytes of the buffer.
+    ///
+    /// # Safety
+    /// `ptr` must be valid for reads of `len` bytes for the returned lifetime.
+    pub unsafe fn as_slice(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        // SAFETY: upheld by the caller.
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl Default for FfiBuffer {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+/// Encodes call arguments into the buffer passed to a function.
+pub fn encode_args(args: &[Value]) -> Vec<u8> {
+    Value::Array(args.to_vec()).encode()
+}
+
+/// Decodes the argument buffer passed to a function.
+///
+/// # Errors
+/// Returns `PluginError::EncodingError` if the buffer is not an encoded `Value::Array`.
+pub fn decode_args(bytes: &[u8]) -> PluginResult<Vec<Value>> {
+    match Value::decode(bytes)? {
+        Value::Array(args) => Ok(args),
+        _ => Err(PluginError::EncodingError(
+            "arguments must be an array".to_string(),
+        )),
+    }
+}
+
+/// Decodes a UTF-8 function name passed across the boundary.
+///
+/// # Errors
+/// Returns `PluginError::EncodingError` if the name is not valid UTF-8.
+pub fn decode_name(bytes: &[u8]) -> PluginResult<&str> {
+    std::str::from_utf8(bytes)
+        .map_err(|e| PluginError::EncodingError(format!("invalid function name: {e}")))
+}
+
+/// Borrows a byte slice from a raw pointer and length, tolerating null for empty slices.
+///
+/// # Safety
+/// Unless `len` is zero, `ptr` must be valid for reads of `len` bytes for the returned lifetime.
+pub unsafe fn raw_slice<'a>(ptr: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        return &[];
+    }
+    // SAFETY: upheld by the caller.
+    unsafe { std::slice::from_raw_parts(ptr, len) }
+}
@@ -0,0 +1,162 @@
+//! Helpers for writing native plugins in Rust.
+//!
+//! A plugin library (built as a `cdylib`) implements a single dispatch function and
+//! exports it with [`export_plugin!`](crate::export_plugin):
+//!
+//! ```rust,ignore
+//! use tosic_plugin_native::guest::Host;
+//! use tosic_plugin_core::{PluginError, PluginResult, Value};
+//!
+//! fn handle(host: &Host<'_>, function: &str, args: Vec<Value>) -> PluginResult<Value> {
+//!     match function {
+//!         "greet" => host.call("log", &[Value::from("hello")]),
+//!         _ => Err(PluginError::FunctionNotFound(function.to_string())),
+//!     }
+//! }
+//!
+//! tosic_plugin_native::export_plugin!(handle, name = "greeter");
+//! ```
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use tosic_plugin_core::{PluginError, PluginResult, Value};
+
+use crate::abi::{self, FfiBuffer, HostApi};
+
+/// Handle through which a plugin calls host functions during a call.
+pub struct Host<'a> {
+    api: &'a HostApi,
+}
+
+impl Host<'_> {
+    /// Calls a host function by name.
+    ///
+    /// # Errors
+    /// Returns `PluginError::HostFunctionNotFound` if the host has no such function, or
+    /// `PluginError::HostError` with the host's message if the host function failed. The
+    /// host wraps errors returned by the plugin in `PluginError::CallError` itself, so this
+    /// does not.
+    pub fn call(&self, name: &str, args: &[Value]) -> PluginResult<Value> {
+        let input = abi::encode_args(args);
+        let mut out = FfiBuffer::empty();
+
+        // SAFETY: the host api is valid for the duration of the plugin call and the host
+        // frees its own output buffer.
+        let (status, output) = unsafe {
+            let status = (self.api.call)(
+                self.api.data,
+                name.as_ptr(),
+                name.len(),
+                input.as_ptr(),
+                input.len(),
+                &mut out,
+            );
+            let output = out.as_slice().to_vec();
+            (self.api.free)(out);
+            (status, output)
+        };
+
+        match status {
+            abi::STATUS_OK => Value::decode(&output),
+            abi::STATUS_NOT_FOUND => Err(PluginError::HostFunctionNotFound(name.to_string())),
+            _ => Err(PluginError::HostError(
+                String::from_utf8_lossy(&output).into_owned(),
+            )),
+        }
+    }
+}
+
+/// Implementation of [`abi::PluginCallFn`] used by [`export_plugin!`](crate::export_plugin).
+///
+/// Returning `PluginError::FunctionNotFound` from the handler reports
+/// [`abi::STATUS_NOT_FOUND`]; panics are caught and reported as errors.
+///
+/// # Safety
+/// Must only be called by the host with arguments following [`abi::PluginCallFn`].
+#[doc(hidden)]
+pub unsafe fn dispatch<F>(
+    host: *const HostApi,
+    name_ptr: *const u8,
+    name_len: usize,
+    args_ptr: *const u8,
+    args_len: usize,
+    out: *mut FfiBuffer,
+    handler: F,
+) -> i32
+where
+    F: Fn(&Host<'_>, &str, Vec<Value>) -> PluginResult<Value>,
+{
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        // SAFETY: the host passes a valid function table and buffers for the duration of the call.
+        let (api, name, args) = unsafe {
+            (
+                &*host,
+                abi::raw_slice(name_ptr, name_len),
+                abi::raw_slice(args_ptr, args_len),
+            )
+        };
+        if api.abi_version != abi::ABI_VERSION {
+            return Err(PluginError::RuntimeError(format!(
+                "host uses ABI version {}, but the plugin was built for ABI version {}",
+                api.abi_version,
+                abi::ABI_VERSION
+            )));
+        }
+        handler(
+            &Host { api },
+            abi::decode_name(name)?,
+            abi::decode_args(args)?,
+        )
+    }));
+
+    let (status, output) = match result {
+        Ok(Ok(value)) => (abi::STATUS_OK, value.encode()),
+        Ok(Err(PluginError::FunctionNotFound(_))) => (abi::STATUS_NOT_FOUND, Vec::new()),
+        Ok(Err(error)) => (abi::STATUS_ERROR, error.to_string().into_bytes()),
+        Err(_) => (abi::STATUS_ERROR, b"plugin function panicked".to_vec()),
+    };
+
+    // SAFETY: the host passes a valid pointer to an output buffer.
+    unsafe { out.write(FfiBuffer::from_vec(output)) };
+    status
+}
+
+/// Exports the symbols required by the native plugin [`abi`](crate::abi) from a `cdylib`.
+///
+/// Takes a dispatch function `fn(&Host<'_>, &str, Vec<Value>) -> PluginResult<Value>` and,
+/// optionally, the plugin's name.
+#[macro_export]
+macro_rules! export_plugin {
+    ($handler:expr $(, name = $name:literal)? $(,)?) => {
+        #[unsafe(no_mangle)]
+        pub extern "C" fn tosic_plugin_abi_version() -> u32 {
+            $crate::abi::ABI_VERSION
+        }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn tosic_plugin_call(
+            host: *const $crate::abi::HostApi,
+            name_ptr: *const u8,
+            name_len: usize,
+            args_ptr: *const u8,
+            args_len: usize,
+            out: *mut $crate::abi::FfiBuffer,
+        ) -> i32 {
+            // SAFETY: forwarded verbatim from the host.
+            unsafe { $crate::guest::dispatch(host, name_ptr, name_len, args_ptr, args_len, out, $handler) }
+        }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn tosic_plugin_free(buffer: $crate::abi::FfiBuffer) {
+            // SAFETY: every buffer handed to the host was created by `FfiBuffer::from_vec` here.
+            drop(unsafe { buffer.into_vec() });
+        }
+
+        $(
+            #[unsafe(no_mangle)]
+            pub extern "C" fn tosic_plugin_name() -> *const ::std::ffi::c_char {
+                concat!($name, "\0").as_ptr().cast()
+            }
+        )?
+    };
+}
@@ -0,0 +1,45 @@
+//! Native shared library runtime for the tosic-plugin system.
+//!
+//! This crate implements the [`Runtime`](tosic_plugin_core::Runtime) trait for plugins
+//! compiled to native shared libraries (`.so`, `.dylib`, `.dll`). Libraries are loaded
+//! from bytes through a temporary file, or directly from disk with
+//! [`NativeRuntime::load_path`]. The host and plugin talk through the versioned C ABI
+//! described in the [`abi`] module, exchanging [`Value`](tosic_plugin_core::Value)s in
+//! their binary encoding; a version handshake at load time rejects mismatched plugins
+//! with `PluginError::LoadError` instead of crashing.
+//!
+//! Rust plugins use the [`guest`] module and the [`export_plugin!`] macro to implement
+//! the ABI.
+//!
+//! # Features
+//!
+//! - **async**: Also implement `AsyncRuntime`; native calls still execute synchronously inside the returned future
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # fn main() -> tosic_plugin_core::PluginResult<()> {
+//! use tosic_plugin_core::*;
+//! use tosic_plugin_native::NativeRuntime;
+//!
+//! let mut context = HostContext::new();
+//! context.register("add", |a: i64, b: i64| a + b);
+//!
+//! let runtime = NativeRuntime::new();
+//! let plugin = runtime.load_path("libplugin.so", &context)?;
+//! let result = runtime.call(&plugin, "run", &[Value::Int(1)])?;
+//! # let _ = result;
+//! # Ok(())
+//! # }
+//! ```
+
+// Strict linting for release builds
+#![cfg_attr(not(debug_assertions), deny(missing_docs))]
+#![cfg_attr(not(debug_assertions), deny(clippy::all))]
+#![cfg_attr(not(debug_assertions), deny(unused))]
+
+pub mod abi;
+pub mod guest;
+mod runtime;
+
+pub use runtime::*;
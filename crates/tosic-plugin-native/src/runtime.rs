@@ -0,0 +1,329 @@
+//! Native shared library implementation of the [`Runtime`] and [`Plugin`] traits.
+
+use std::ffi::{CStr, c_void};
+use std::io::Write;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::Path;
+
+use libloading::Library;
+use tempfile::NamedTempFile;
+#[cfg(feature = "async")]
+use tosic_plugin_core::AsyncRuntime;
+use tosic_plugin_core::{HostContext, Plugin, PluginError, PluginResult, Runtime, Value};
+
+use crate::abi::{self, FfiBuffer, HostApi};
+
+/// Runtime that loads plugins from native shared libraries.
+///
+/// Libraries must export the symbols described in the [`abi`] module; the
+/// [`export_plugin!`](crate::export_plugin) macro generates them for Rust plugins. Native
+/// plugins run in the host process without any sandboxing, so only load trusted code.
+#[derive(Clone, Default)]
+pub struct NativeRuntime {}
+
+impl NativeRuntime {
+    /// Creates a new native runtime.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a plugin from a shared library on disk.
+    ///
+    /// # Errors
+    /// Returns `PluginError::LoadError` if the library cannot be opened, lacks a required
+    /// symbol, or was built against a different [`abi::ABI_VERSION`].
+    pub fn load_path(
+        &self,
+        path: impl AsRef<Path>,
+        context: &HostContext,
+    ) -> PluginResult<NativePlugin> {
+        NativePlugin::open(path.as_ref(), context, None)
+    }
+
+    fn load_sync(&self, bytes: &[u8], context: &HostContext) -> PluginResult<NativePlugin> {
+        // The dynamic loader needs a path, so the library is written out to a temporary file
+        // that lives as long as the plugin.
+        let mut file = tempfile::Builder::new()
+            .prefix("tosic-plugin-")
+            .suffix(std::env::consts::DLL_SUFFIX)
+            .tempfile()
+            .map_err(|e| {
+                PluginError::LoadError(format!("failed to create temporary library file: {e}"))
+            })?;
+        file.write_all(bytes)
+            .and_then(|()| file.flush())
+            .map_err(|e| {
+                PluginError::LoadError(format!("failed to write temporary library file: {e}"))
+            })?;
+
+        let path = file.path().to_path_buf();
+        NativePlugin::open(&path, context, Some(file))
+    }
+
+    fn call_sync(
+        &self,
+        plugin: &NativePlugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        let host = HostApi {
+            abi_version: abi::ABI_VERSION,
+            data: std::ptr::from_ref(&plugin.context).cast::<c_void>(),
+            call: host_call,
+            free: host_free,
+        };
+        let input = abi::encode_args(args);
+        let mut out = FfiBuffer::empty();
+
+        // SAFETY: the symbols were resolved from `plugin.library`, which is still loaded, and
+        // every pointer handed over stays valid for the duration of the call.
+        let (status, output) = unsafe {
+            let status = (plugin.call)(
+                &host,
+                function_name.as_ptr(),
+                function_name.len(),
+                input.as_ptr(),
+                input.len(),
+                &mut out,
+            );
+            let output = out.as_slice().to_vec();
+            (plugin.free)(out);
+            (status, output)
+        };
+
+        call_result(function_name, status, &output)
+    }
+}
+
+/// Converts the status and output of a plugin call into its result, wrapping the errors
+/// plugins report in `PluginError::CallError`.
+fn call_result(function_name: &str, status: i32, output: &[u8]) -> PluginResult<Value> {
+    match status {
+        abi::STATUS_OK => Value::decode(output),
+        abi::STATUS_NOT_FOUND => Err(PluginError::FunctionNotFound(function_name.to_string())),
+        abi::STATUS_ERROR => Err(PluginError::CallError {
+            function: function_name.to_string(),
+            message: String::from_utf8_lossy(output).into_owned(),
+        }),
+        status => Err(PluginError::CallError {
+            function: function_name.to_string(),
+            message: format!("plugin returned unknown status {status}"),
+        }),
+    }
+}
+
+/// A shared library loaded by a [`NativeRuntime`].
+pub struct NativePlugin {
+    name: Option<String>,
+    context: HostContext,
+    call: abi::PluginCallFn,
+    free: abi::PluginFreeFn,
+    // Declared after the function pointers and before the file so the library is unloaded
+    // only once nothing can call into it, and before its backing file is removed.
+    _library: Library,
+    _file: Option<NamedTempFile>,
+}
+
+impl Plugin for NativePlugin {
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl NativePlugin {
+    fn open(path: &Path, context: &HostContext, file: Option<NamedTempFile>) -> PluginResult<Self> {
+        let load_error = |e: libloading::Error| PluginError::LoadError(e.to_string());
+
+        // SAFETY: loading a library runs its initializers; native plugins are trusted code.
+        let library = unsafe { Library::new(path) }.map_err(load_error)?;
+
+        // SAFETY: the symbol types match the signatures documented in `abi`, and the handshake
+        // happens before any other symbol is used.
+        unsafe {
+            let abi_version = library
+                .get::<abi::AbiVersionFn>(abi::ABI_VERSION_SYMBOL)
+                .map_err(load_error)?;
+            let version = abi_version();
+            if version != abi::ABI_VERSION {
+                return Err(PluginError::LoadError(format!(
+                    "plugin was built for ABI version {version}, but the host uses ABI version {}",
+                    abi::ABI_VERSION
+                )));
+            }
+
+            let call = *library
+                .get::<abi::PluginCallFn>(abi::PLUGIN_CALL_SYMBOL)
+                .map_err(load_error)?;
+            let free = *library
+                .get::<abi::PluginFreeFn>(abi::PLUGIN_FREE_SYMBOL)
+                .map_err(load_error)?;
+            let name = library
+                .get::<abi::PluginNameFn>(abi::PLUGIN_NAME_SYMBOL)
+                .ok()
+                .map(|name| name())
+                .filter(|name| !name.is_null())
+                .map(|name| CStr::from_ptr(name).to_string_lossy().into_owned());
+
+            Ok(Self {
+                name,
+                context: context.clone(),
+                call,
+                free,
+                _library: library,
+                _file: file,
+            })
+        }
+    }
+}
+
+impl Runtime for NativeRuntime {
+    type Plugin = NativePlugin;
+
+    fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        self.load_sync(bytes, context)
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+/// Native calls run to completion within the returned future.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncRuntime for NativeRuntime {
+    type Plugin = NativePlugin;
+
+    async fn load_async(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        self.load_sync(bytes, context)
+    }
+
+    async fn call_async(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+/// [`HostApi::call`] implementation dispatching into the plugin's [`HostContext`].
+unsafe extern "C" fn host_call(
+    data: *const c_void,
+    name_ptr: *const u8,
+    name_len: usize,
+    args_ptr: *const u8,
+    args_len: usize,
+    out: *mut FfiBuffer,
+) -> i32 {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        // SAFETY: `data` is the context of the plugin being called and the buffers are
+        // provided by the plugin for the duration of this call.
+        let (context, name, args) = unsafe {
+            (
+                &*data.cast::<HostContext>(),
+                abi::raw_slice(name_ptr, name_len),
+                abi::raw_slice(args_ptr, args_len),
+            )
+        };
+        context.call_function(abi::decode_name(name)?, &abi::decode_args(args)?)
+    }));
+
+    let (status, output) = match result {
+        Ok(Ok(value)) => (abi::STATUS_OK, value.encode()),
+        Ok(Err(PluginError::HostFunctionNotFound(_))) => (abi::STATUS_NOT_FOUND, Vec::new()),
+        Ok(Err(error)) => (abi::STATUS_ERROR, error.to_string().into_bytes()),
+        Err(_) => (abi::STATUS_ERROR, b"host function panicked".to_vec()),
+    };
+
+    // SAFETY: the plugin passes a valid pointer to an output buffer.
+    unsafe { out.write(FfiBuffer::from_vec(output)) };
+    status
+}
+
+/// [`HostApi::free`] implementation for buffers returned by [`host_call`].
+unsafe extern "C" fn host_free(buffer: FfiBuffer) {
+    // SAFETY: every buffer handed to the plugin was created by `FfiBuffer::from_vec` here.
+    drop(unsafe { buffer.into_vec() });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guest::{self, Host};
+
+    /// Calls `function` through the guest side of the ABI as the host would, with `handler`
+    /// as the plugin.
+    fn call<F>(
+        context: &HostContext,
+        function: &str,
+        args: &[Value],
+        handler: F,
+    ) -> PluginResult<Value>
+    where
+        F: Fn(&Host<'_>, &str, Vec<Value>) -> PluginResult<Value>,
+    {
+        let host = HostApi {
+            abi_version: abi::ABI_VERSION,
+            data: std::ptr::from_ref(context).cast::<c_void>(),
+            call: host_call,
+            free: host_free,
+        };
+        let input = abi::encode_args(args);
+        let mut out = FfiBuffer::empty();
+        // SAFETY: every pointer stays valid for the duration of the call, and the output
+        // buffer was allocated by this binary.
+        let (status, output) = unsafe {
+            let status = guest::dispatch(
+                &host,
+                function.as_ptr(),
+                function.len(),
+                input.as_ptr(),
+                input.len(),
+                &mut out,
+                handler,
+            );
+            (status, out.into_vec())
+        };
+        call_result(function, status, &output)
+    }
+
+    #[test]
+    fn plugin_errors_are_wrapped_once() {
+        let mut context = HostContext::new();
+        context.register("add", |a: i64, b: i64| a + b);
+        let forward = |host: &Host<'_>, _: &str, args: Vec<Value>| host.call("add", &args);
+
+        assert_eq!(
+            call(&context, "sum", &[Value::Int(2), Value::Int(3)], forward).unwrap(),
+            Value::Int(5)
+        );
+        let error = call(&context, "sum", &[Value::Int(2)], forward).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Failed to call function 'sum': Host function error: Host function 'add' expects 2 argument(s), but received 1"
+        );
+
+        let fail = |_: &Host<'_>, _: &str, _: Vec<Value>| {
+            Err(PluginError::RuntimeError("boom".to_string()))
+        };
+        let error = call(&context, "fail", &[], fail).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Failed to call function 'fail': Runtime error: boom"
+        );
+        let missing = |_: &Host<'_>, function: &str, _: Vec<Value>| {
+            Err(PluginError::FunctionNotFound(function.to_string()))
+        };
+        assert!(matches!(
+            call(&context, "missing", &[], missing),
+            Err(PluginError::FunctionNotFound(_))
+        ));
+    }
+}
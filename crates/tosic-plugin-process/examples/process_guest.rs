@@ -0,0 +1,24 @@
+//! Example plugin process, spawned by the `process_runtime` example.
+//!
+//! Build with: `cargo build -p tosic-plugin-process --example process_guest`
+
+use tosic_plugin_core::{FromValue, PluginError, PluginResult, Value};
+use tosic_plugin_process::guest::{Guest, Host};
+
+fn handle(host: &Host<'_>, function: &str, args: Vec<Value>) -> PluginResult<Value> {
+    match function {
+        "echo" => Ok(Value::Array(args)),
+        "greet" => {
+            let name = String::from_value(args.first().unwrap_or(&Value::Null))?;
+            host.call("log", &[Value::from(format!("greeting {name}"))])?;
+            Ok(Value::from(format!("Hello from a plugin process, {name}!")))
+        }
+        "add" => host.call("add", &args),
+        "crash" => std::process::abort(),
+        _ => Err(PluginError::FunctionNotFound(function.to_string())),
+    }
+}
+
+fn main() -> PluginResult<()> {
+    Guest::new(handle).with_name("process-example").serve()
+}
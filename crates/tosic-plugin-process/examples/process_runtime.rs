@@ -0,0 +1,86 @@
+//! Example demonstrating the process runtime running a plugin in a child process.
+//!
+//! This example shows how to:
+//! - Spawn a plugin process and call its functions
+//! - Serve host function calls made by the plugin
+//! - Survive a plugin crash
+//!
+//! Run with:
+//! `cargo build -p tosic-plugin-process --example process_guest && cargo run -p tosic-plugin-process --example process_runtime`
+
+use std::path::PathBuf;
+use std::process::Command;
+use tosic_plugin_core::*;
+use tosic_plugin_process::ProcessRuntime;
+
+/// Path of the `process_guest` example binary next to this example's binary.
+fn guest_executable() -> PathBuf {
+    let examples_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(PathBuf::from))
+        .expect("example binary has a parent directory");
+    examples_dir.join(format!("process_guest{}", std::env::consts::EXE_SUFFIX))
+}
+
+fn main() -> PluginResult<()> {
+    println!("=== Process Runtime Example ===\n");
+
+    let mut host_context = HostContext::new();
+    host_context.register("log", |message: String| {
+        println!("[HOST LOG] {}", message);
+    });
+    host_context.register("add", |a: i64, b: i64| -> i64 { a + b });
+
+    let runtime = ProcessRuntime::new();
+
+    let path = guest_executable();
+    println!("Spawning plugin process {}...", path.display());
+    let plugin = runtime.spawn(Command::new(&path), &host_context)?;
+    println!(
+        "Loaded plugin {:?} with pid {}\n",
+        plugin.name(),
+        plugin.id()
+    );
+
+    println!("1. Calling echo(1, \"two\", bytes [3]):");
+    let args = [Value::Int(1), Value::from("two"), Value::Bytes(vec![3])];
+    let result = runtime.call(&plugin, "echo", &args)?;
+    println!("   Result: {:?}\n", result);
+
+    println!("2. Calling greet(\"World\"):");
+    let result = runtime.call(&plugin, "greet", &[Value::from("World")])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("3. Calling add(5, 3), which calls back into the host:");
+    let result = runtime.call(&plugin, "add", &[Value::Int(5), Value::Int(3)])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("=== Testing Error Cases ===\n");
+
+    println!("4. Calling add with wrong argument types:");
+    match runtime.call(&plugin, "add", &[Value::from("five"), Value::Int(3)]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n5. Calling non-existent function:");
+    match runtime.call(&plugin, "nonexistent", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n6. Calling crash, which aborts the plugin process:");
+    match runtime.call(&plugin, "crash", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n7. Calling echo on the crashed plugin:");
+    match runtime.call(&plugin, "echo", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n=== Example completed successfully! ===");
+    Ok(())
+}
@@ -0,0 +1,205 @@
+//! Helpers for writing plugin processes in Rust.
+//!
+//! A plugin process is an ordinary binary that implements a single dispatch function and
+//! hands it to a [`Guest`], which serves requests from the host until its stdin closes:
+//!
+//! ```rust,no_run
+//! use tosic_plugin_core::{PluginError, PluginResult, Value};
+//! use tosic_plugin_process::guest::{Guest, Host};
+//!
+//! fn handle(host: &Host<'_>, function: &str, args: Vec<Value>) -> PluginResult<Value> {
+//!     match function {
+//!         "greet" => host.call("log", &[Value::from("hello")]),
+//!         _ => Err(PluginError::FunctionNotFound(function.to_string())),
+//!     }
+//! }
+//!
+//! fn main() -> PluginResult<()> {
+//!     Guest::new(handle).with_name("greeter").serve()
+//! }
+//! ```
+//!
+//! Stdout carries the protocol, so plugins must log to stderr instead of printing.
+
+use std::cell::RefCell;
+use std::io::{self, StdinLock, StdoutLock};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use serde_json::{Value as Json, json};
+use tosic_plugin_core::{PluginError, PluginResult, Value};
+
+use crate::protocol::{self, Message, RpcError};
+
+/// Handle through which a plugin calls host functions during a call.
+pub struct Host<'a> {
+    io: &'a RefCell<GuestIo>,
+    functions: &'a [String],
+}
+
+impl Host<'_> {
+    /// Calls a host function by name.
+    ///
+    /// # Errors
+    /// Returns `PluginError::HostFunctionNotFound` if the host has no such function,
+    /// `PluginError::HostError` with the host's message if the host function failed, or
+    /// `PluginError::RuntimeError` if the connection to the host is lost. The host wraps
+    /// errors returned by the plugin in `PluginError::CallError` itself, so this does not.
+    pub fn call(&self, name: &str, args: &[Value]) -> PluginResult<Value> {
+        let params = protocol::call_params(name, args)?;
+        let mut io = self.io.borrow_mut();
+
+        let id = io.next_id;
+        io.next_id += 1;
+        io.send(&Message::Request {
+            id,
+            method: protocol::HOST_CALL_METHOD.to_string(),
+            params,
+        })?;
+
+        match io.receive()? {
+            Some(Message::Response {
+                id: response_id,
+                result,
+            }) if response_id == id => match result {
+                Ok(result) => protocol::from_json(result),
+                Err(error) if error.code == protocol::FUNCTION_NOT_FOUND => {
+                    Err(PluginError::HostFunctionNotFound(name.to_string()))
+                }
+                Err(error) => Err(PluginError::HostError(error.message)),
+            },
+            Some(_) => Err(PluginError::RuntimeError(format!(
+                "expected the host's response to calling '{name}'"
+            ))),
+            None => Err(PluginError::RuntimeError(
+                "host closed the connection".to_string(),
+            )),
+        }
+    }
+
+    /// Returns the names of the functions the host provides.
+    pub fn functions(&self) -> &[String] {
+        self.functions
+    }
+}
+
+/// Serves the host's requests with a dispatch function.
+pub struct Guest<F> {
+    name: Option<String>,
+    handler: F,
+}
+
+impl<F> Guest<F>
+where
+    F: Fn(&Host<'_>, &str, Vec<Value>) -> PluginResult<Value>,
+{
+    /// Creates a guest dispatching every call to `handler`.
+    ///
+    /// Returning `PluginError::FunctionNotFound` from the handler reports the function as
+    /// missing to the host; panics are caught and reported as call errors.
+    pub fn new(handler: F) -> Self {
+        Self {
+            name: None,
+            handler,
+        }
+    }
+
+    /// Sets the name reported to the host.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Serves requests on stdin and stdout until the host closes stdin.
+    ///
+    /// # Errors
+    /// Returns `PluginError::RuntimeError` if reading from or writing to the host fails.
+    pub fn serve(self) -> PluginResult<()> {
+        let io = RefCell::new(GuestIo {
+            reader: io::stdin().lock(),
+            writer: io::stdout().lock(),
+            next_id: 0,
+        });
+        let mut functions = Vec::new();
+
+        loop {
+            let Some(message) = io.borrow_mut().receive()? else {
+                return Ok(());
+            };
+            let Message::Request { id, method, params } = message else {
+                return Err(PluginError::RuntimeError(
+                    "received a response without a pending request".to_string(),
+                ));
+            };
+
+            let result = match method.as_str() {
+                protocol::INITIALIZE_METHOD => {
+                    functions = params
+                        .get("host_functions")
+                        .and_then(Json::as_array)
+                        .map(|names| {
+                            names
+                                .iter()
+                                .filter_map(Json::as_str)
+                                .map(str::to_string)
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    Ok(json!({ "protocol_version": protocol::PROTOCOL_VERSION, "name": self.name }))
+                }
+                protocol::CALL_METHOD => self.call(
+                    &Host {
+                        io: &io,
+                        functions: &functions,
+                    },
+                    params,
+                ),
+                _ => Err(RpcError::new(
+                    protocol::METHOD_NOT_FOUND,
+                    format!("unknown method `{method}`"),
+                )),
+            };
+
+            io.borrow_mut().send(&Message::Response { id, result })?;
+        }
+    }
+
+    fn call(&self, host: &Host<'_>, params: Json) -> Result<Json, RpcError> {
+        let (function, args) = protocol::parse_call_params(params)
+            .map_err(|e| RpcError::new(protocol::INVALID_PARAMS, e.to_string()))?;
+        let result = catch_unwind(AssertUnwindSafe(|| (self.handler)(host, &function, args)))
+            .unwrap_or_else(|_| {
+                Err(PluginError::RuntimeError(format!(
+                    "plugin function '{function}' panicked"
+                )))
+            });
+
+        match result.and_then(|value| protocol::to_json(&value)) {
+            Ok(value) => Ok(value),
+            // Only the called function itself missing counts, not one it called in turn.
+            Err(PluginError::FunctionNotFound(name)) if name == function => Err(RpcError::new(
+                protocol::FUNCTION_NOT_FOUND,
+                format!("function '{name}' not found"),
+            )),
+            Err(error) => Err(RpcError::call_failed(&error)),
+        }
+    }
+}
+
+/// The guest's ends of the pipes to the host.
+struct GuestIo {
+    reader: StdinLock<'static>,
+    writer: StdoutLock<'static>,
+    next_id: u64,
+}
+
+impl GuestIo {
+    fn send(&mut self, message: &Message) -> PluginResult<()> {
+        protocol::write_message(&mut self.writer, message)
+            .map_err(|e| PluginError::RuntimeError(format!("failed to write to the host: {e}")))
+    }
+
+    fn receive(&mut self) -> PluginResult<Option<Message>> {
+        protocol::read_message(&mut self.reader)
+            .map_err(|e| PluginError::RuntimeError(format!("failed to read from the host: {e}")))
+    }
+}
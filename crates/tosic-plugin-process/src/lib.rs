@@ -0,0 +1,47 @@
+//! Out-of-process runtime for the tosic-plugin system.
+//!
+//! This crate implements the [`Runtime`](tosic_plugin_core::Runtime) trait by running
+//! each plugin as a child process, isolating the host from plugin crashes. The host and
+//! plugin exchange [`Value`](tosic_plugin_core::Value)s over the plugin's stdin and stdout
+//! using the framed JSON-RPC protocol described in the [`protocol`] module. Host functions
+//! called by the plugin arrive as reverse requests and are dispatched to the
+//! [`HostContext`](tosic_plugin_core::HostContext), and a plugin that exits mid-call fails
+//! with `PluginError::PluginCrashed`.
+//!
+//! Plugins are loaded from executable bytes, or spawned from a
+//! [`Command`](std::process::Command) with [`ProcessRuntime::spawn`]. Rust plugins use the
+//! [`guest`] module to implement the protocol.
+//!
+//! # Features
+//!
+//! - **async**: Also implement `AsyncRuntime`; calls still block on the plugin process inside the returned future
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # fn main() -> tosic_plugin_core::PluginResult<()> {
+//! use std::process::Command;
+//! use tosic_plugin_core::*;
+//! use tosic_plugin_process::ProcessRuntime;
+//!
+//! let mut context = HostContext::new();
+//! context.register("add", |a: i64, b: i64| a + b);
+//!
+//! let runtime = ProcessRuntime::new();
+//! let plugin = runtime.spawn(Command::new("./my-plugin"), &context)?;
+//! let result = runtime.call(&plugin, "run", &[Value::Int(1)])?;
+//! # let _ = result;
+//! # Ok(())
+//! # }
+//! ```
+
+// Strict linting for release builds
+#![cfg_attr(not(debug_assertions), deny(missing_docs))]
+#![cfg_attr(not(debug_assertions), deny(clippy::all))]
+#![cfg_attr(not(debug_assertions), deny(unused))]
+
+pub mod guest;
+pub mod protocol;
+mod runtime;
+
+pub use runtime::*;
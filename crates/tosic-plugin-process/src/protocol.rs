@@ -0,0 +1,530 @@
+//! Wire protocol between the host and plugin processes.
+//!
+//! The host writes to the plugin's stdin and reads from its stdout. Every message is a
+//! JSON-RPC 2.0 request or response framed by a `Content-Length` header, as in the
+//! Language Server Protocol:
+//!
+//! ```text
+//! Content-Length: 61\r\n
+//! \r\n
+//! {"jsonrpc":"2.0","id":1,"method":"call","params":{"function":"run","args":[]}}
+//! ```
+//!
+//! # Methods
+//!
+//! Sent by the host:
+//!
+//! - [`INITIALIZE_METHOD`]: sent once after spawning, with params
+//!   `{"protocol_version": n, "host_functions": [name, ...]}`. The plugin answers with
+//!   `{"protocol_version": n, "name": name | null}`.
+//! - [`CALL_METHOD`]: calls a plugin function, with params `{"function": name, "args": [...]}`.
+//!   The result is the function's return value.
+//!
+//! Sent by the plugin, only while it is handling a call:
+//!
+//! - [`HOST_CALL_METHOD`]: calls a host function, with the same params and result as
+//!   [`CALL_METHOD`].
+//!
+//! Failures are reported as JSON-RPC errors with [`FUNCTION_NOT_FOUND`] when the function
+//! does not exist and [`CALL_FAILED`] otherwise.
+//!
+//! # Values
+//!
+//! | `Value`  | JSON                                       |
+//! |----------|--------------------------------------------|
+//! | `Null`   | `null`                                     |
+//! | `Bool`   | boolean                                    |
+//! | `Int`    | number without a fraction or exponent      |
+//! | `Float`  | number with a fraction or exponent         |
+//! | `String` | string                                     |
+//! | `Bytes`  | `{"$bytes": "<base64>"}`                   |
+//! | `Array`  | array                                      |
+//! | `Object` | object, keeping the order of its keys      |
+//!
+//! Non-finite floats cannot be represented in JSON and are rejected.
+
+use std::io::{self, BufRead, Read, Write};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde_json::{Map, Number, Value as Json, json};
+use tosic_plugin_core::{PluginError, PluginResult, Value};
+
+/// Version of this protocol, exchanged during initialization.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Method of the initialization handshake.
+pub const INITIALIZE_METHOD: &str = "initialize";
+
+/// Method calling a plugin function.
+pub const CALL_METHOD: &str = "call";
+
+/// Method calling a host function from the plugin.
+pub const HOST_CALL_METHOD: &str = "host/call";
+
+/// Error code for calls to functions that do not exist.
+pub const FUNCTION_NOT_FOUND: i64 = -32000;
+
+/// Error code for calls that failed.
+pub const CALL_FAILED: i64 = -32001;
+
+/// JSON-RPC error code for unknown methods.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC error code for malformed params.
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// Key of the object wrapping base64-encoded `Value::Bytes`.
+pub const BYTES_KEY: &str = "$bytes";
+
+/// Largest message body accepted, guarding against corrupted headers.
+pub const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Longest header line accepted, including its line terminator.
+pub const MAX_HEADER_LEN: usize = 8 * 1024;
+
+/// A JSON-RPC message exchanged in either direction.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    /// A request expecting a response with the same id.
+    Request {
+        /// Request id, unique among the sender's pending requests.
+        id: u64,
+        /// Method name.
+        method: String,
+        /// Method params.
+        params: Json,
+    },
+    /// The response to a request.
+    Response {
+        /// Id of the request being answered.
+        id: u64,
+        /// The result or error of the request.
+        result: Result<Json, RpcError>,
+    },
+}
+
+/// A JSON-RPC error object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    /// Error code, such as [`FUNCTION_NOT_FOUND`] or [`CALL_FAILED`].
+    pub code: i64,
+    /// Human-readable error message.
+    pub message: String,
+}
+
+impl RpcError {
+    /// Creates an error object.
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Converts the error of a failed call into an error object with code [`CALL_FAILED`].
+    pub fn call_failed(error: &PluginError) -> Self {
+        Self::new(CALL_FAILED, error.to_string())
+    }
+}
+
+impl Message {
+    /// Converts the message into its JSON-RPC representation.
+    pub fn to_json(&self) -> Json {
+        match self {
+            Self::Request { id, method, params } => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": method,
+                "params": params,
+            }),
+            Self::Response {
+                id,
+                result: Ok(result),
+            } => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": result,
+            }),
+            Self::Response {
+                id,
+                result: Err(error),
+            } => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": error.code, "message": error.message },
+            }),
+        }
+    }
+
+    /// Parses a message from its JSON-RPC representation.
+    ///
+    /// # Errors
+    /// Returns `PluginError::EncodingError` if the JSON is not a request or response with an id.
+    pub fn from_json(json: Json) -> PluginResult<Self> {
+        let Json::Object(mut object) = json else {
+            return Err(encoding_error("message must be an object"));
+        };
+        let id = object
+            .get("id")
+            .and_then(Json::as_u64)
+            .ok_or_else(|| encoding_error("message must have an unsigned integer id"))?;
+
+        if let Some(method) = object.remove("method") {
+            let Json::String(method) = method else {
+                return Err(encoding_error("method must be a string"));
+            };
+            let params = object.remove("params").unwrap_or(Json::Null);
+            return Ok(Self::Request { id, method, params });
+        }
+
+        if let Some(error) = object.remove("error") {
+            let code = error
+                .get("code")
+                .and_then(Json::as_i64)
+                .unwrap_or(CALL_FAILED);
+            let message = error
+                .get("message")
+                .and_then(Json::as_str)
+                .unwrap_or_default();
+            return Ok(Self::Response {
+                id,
+                result: Err(RpcError::new(code, message)),
+            });
+        }
+
+        match object.remove("result") {
+            Some(result) => Ok(Self::Response {
+                id,
+                result: Ok(result),
+            }),
+            None => Err(encoding_error(
+                "message has neither a method, a result, nor an error",
+            )),
+        }
+    }
+}
+
+/// Reads the next framed message, returning `None` if the stream ended between messages.
+///
+/// # Errors
+/// Returns an error of kind `InvalidData` for malformed frames or messages, including
+/// header lines longer than [`MAX_HEADER_LEN`], and `UnexpectedEof` if the stream ends
+/// within a message.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Message>> {
+    let mut content_length = None;
+    let mut line = String::new();
+    let mut first = true;
+    loop {
+        line.clear();
+        // Bounded so a peer that never sends a line terminator cannot exhaust memory.
+        if (&mut *reader)
+            .take(MAX_HEADER_LEN as u64 + 1)
+            .read_line(&mut line)?
+            == 0
+        {
+            if first {
+                return Ok(None);
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "stream ended within message header",
+            ));
+        }
+        first = false;
+        if line.len() > MAX_HEADER_LEN {
+            return Err(invalid_data(format!(
+                "header line exceeds {MAX_HEADER_LEN} bytes"
+            )));
+        }
+
+        let header = line.trim_end_matches(['\r', '\n']);
+        if header.is_empty() {
+            break;
+        }
+        let (name, value) = header
+            .split_once(':')
+            .ok_or_else(|| invalid_data(format!("malformed header `{header}`")))?;
+        if name.trim().eq_ignore_ascii_case("Content-Length") {
+            let length = value
+                .trim()
+                .parse::<usize>()
+                .map_err(|e| invalid_data(format!("invalid Content-Length: {e}")))?;
+            content_length = Some(length);
+        }
+    }
+
+    let length =
+        content_length.ok_or_else(|| invalid_data("message has no Content-Length header"))?;
+    if length > MAX_MESSAGE_SIZE {
+        return Err(invalid_data(format!(
+            "message of {length} bytes exceeds {MAX_MESSAGE_SIZE} bytes"
+        )));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    let json =
+        serde_json::from_slice(&body).map_err(|e| invalid_data(format!("invalid JSON: {e}")))?;
+    Message::from_json(json).map(Some).map_err(invalid_data)
+}
+
+/// Writes a framed message and flushes the writer.
+///
+/// # Errors
+/// Returns any error raised by the writer.
+pub fn write_message(writer: &mut impl Write, message: &Message) -> io::Result<()> {
+    let body = serde_json::to_vec(&message.to_json())?;
+    write!(writer, "Content-Length: {}\r\n\r\n", body.len())?;
+    writer.write_all(&body)?;
+    writer.flush()
+}
+
+/// Builds the params of a [`CALL_METHOD`] or [`HOST_CALL_METHOD`] request.
+///
+/// # Errors
+/// Returns `PluginError::EncodingError` if an argument cannot be represented in JSON.
+pub fn call_params(function: &str, args: &[Value]) -> PluginResult<Json> {
+    let args = args.iter().map(to_json).collect::<PluginResult<Vec<_>>>()?;
+    Ok(json!({ "function": function, "args": args }))
+}
+
+/// Parses the params of a [`CALL_METHOD`] or [`HOST_CALL_METHOD`] request.
+///
+/// # Errors
+/// Returns `PluginError::EncodingError` if the params are malformed.
+pub fn parse_call_params(params: Json) -> PluginResult<(String, Vec<Value>)> {
+    let Json::Object(mut params) = params else {
+        return Err(encoding_error("call params must be an object"));
+    };
+    let function = match params.remove("function") {
+        Some(Json::String(function)) => function,
+        _ => return Err(encoding_error("call params must have a string `function`")),
+    };
+    let args = match params.remove("args") {
+        Some(Json::Array(args)) => args
+            .into_iter()
+            .map(from_json)
+            .collect::<PluginResult<_>>()?,
+        None | Some(Json::Null) => Vec::new(),
+        Some(_) => return Err(encoding_error("call `args` must be an array")),
+    };
+    Ok((function, args))
+}
+
+/// Converts a plugin value into JSON.
+///
+/// # Errors
+/// Returns `PluginError::EncodingError` for non-finite floats.
+pub fn to_json(value: &Value) -> PluginResult<Json> {
+    Ok(match value {
+        Value::Null => Json::Null,
+        Value::Bool(b) => Json::Bool(*b),
+        Value::Int(i) => Json::Number((*i).into()),
+        Value::Float(f) => Json::Number(
+            Number::from_f64(*f)
+                .ok_or_else(|| encoding_error(format!("cannot represent {f} in JSON")))?,
+        ),
+        Value::String(s) => Json::String(s.clone()),
+        Value::Bytes(b) => json!({ BYTES_KEY: BASE64.encode(b) }),
+        Value::Array(items) => Json::Array(items.iter().map(to_json).collect::<PluginResult<_>>()?),
+        Value::Object(map) => Json::Object(
+            map.iter()
+                .map(|(key, item)| Ok((key.clone(), to_json(item)?)))
+                .collect::<PluginResult<Map<_, _>>>()?,
+        ),
+    })
+}
+
+/// Converts JSON into a plugin value.
+///
+/// # Errors
+/// Returns `PluginError::EncodingError` for integers outside the `i64` range and invalid
+/// base64 in a bytes object.
+pub fn from_json(json: Json) -> PluginResult<Value> {
+    Ok(match json {
+        Json::Null => Value::Null,
+        Json::Bool(b) => Value::Bool(b),
+        Json::Number(n) if n.is_f64() => Value::Float(n.as_f64().unwrap_or_default()),
+        Json::Number(n) => Value::Int(
+            n.as_i64()
+                .ok_or_else(|| encoding_error(format!("integer {n} is out of range")))?,
+        ),
+        Json::String(s) => Value::String(s),
+        Json::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(from_json)
+                .collect::<PluginResult<_>>()?,
+        ),
+        Json::Object(object) => {
+            if object.len() == 1
+                && let Some(Json::String(encoded)) = object.get(BYTES_KEY)
+            {
+                let bytes = BASE64
+                    .decode(encoded)
+                    .map_err(|e| encoding_error(format!("invalid base64 in bytes: {e}")))?;
+                return Ok(Value::Bytes(bytes));
+            }
+            Value::Object(
+                object
+                    .into_iter()
+                    .map(|(key, item)| Ok((key, from_json(item)?)))
+                    .collect::<PluginResult<tosic_plugin_core::Map>>()?,
+            )
+        }
+    })
+}
+
+fn encoding_error(message: impl Into<String>) -> PluginError {
+    PluginError::EncodingError(message.into())
+}
+
+fn invalid_data(error: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(bytes: &[u8]) -> io::Result<Option<Message>> {
+        read_message(&mut &bytes[..])
+    }
+
+    fn request(id: u64) -> Message {
+        Message::Request {
+            id,
+            method: CALL_METHOD.to_string(),
+            params: json!({ "function": "run", "args": [] }),
+        }
+    }
+
+    #[test]
+    fn messages_round_trip_through_frames() {
+        let messages = [
+            request(1),
+            Message::Response {
+                id: 1,
+                result: Ok(json!([1, "two"])),
+            },
+            Message::Response {
+                id: 2,
+                result: Err(RpcError::new(FUNCTION_NOT_FOUND, "no such function")),
+            },
+        ];
+        let mut stream = Vec::new();
+        for message in &messages {
+            write_message(&mut stream, message).unwrap();
+        }
+
+        let mut reader = &stream[..];
+        for message in &messages {
+            assert_eq!(read_message(&mut reader).unwrap().as_ref(), Some(message));
+        }
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn headers_are_case_insensitive_and_may_be_extended() {
+        let body = br#"{"jsonrpc":"2.0","id":7,"result":null}"#;
+        let frame = [
+            format!(
+                "content-length: {}\r\nContent-Type: json\r\n\r\n",
+                body.len()
+            )
+            .as_bytes(),
+            body,
+        ]
+        .concat();
+        assert_eq!(
+            read(&frame).unwrap(),
+            Some(Message::Response {
+                id: 7,
+                result: Ok(Json::Null)
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_frames() {
+        let kind = |bytes: &[u8]| read(bytes).unwrap_err().kind();
+        assert_eq!(
+            kind(b"Content-Length: 10\r\n"),
+            io::ErrorKind::UnexpectedEof
+        );
+        assert_eq!(
+            kind(b"Content-Length: 10\r\n\r\n{}"),
+            io::ErrorKind::UnexpectedEof
+        );
+        assert_eq!(
+            kind(b"Content-Type: json\r\n\r\n{}"),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(kind(b"Content-Length\r\n\r\n"), io::ErrorKind::InvalidData);
+        assert_eq!(
+            kind(b"Content-Length: -1\r\n\r\n"),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            kind(b"Content-Length: 2\r\n\r\n[]"),
+            io::ErrorKind::InvalidData
+        );
+        let oversized = format!("Content-Length: {}\r\n\r\n", MAX_MESSAGE_SIZE + 1);
+        assert_eq!(kind(oversized.as_bytes()), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_header_lines_longer_than_the_limit() {
+        let padding = "x".repeat(MAX_HEADER_LEN);
+        let long = format!("X-Padding: {padding}\r\nContent-Length: 2\r\n\r\n{{}}");
+        let error = read(long.as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("header line exceeds"));
+
+        let padding = "x".repeat(MAX_HEADER_LEN - "X-Padding: \r\n".len());
+        let body = br#"{"jsonrpc":"2.0","id":1,"result":1}"#;
+        let frame = [
+            format!(
+                "X-Padding: {padding}\r\nContent-Length: {}\r\n\r\n",
+                body.len()
+            )
+            .as_bytes(),
+            body,
+        ]
+        .concat();
+        assert_eq!(
+            read(&frame).unwrap(),
+            Some(Message::Response {
+                id: 1,
+                result: Ok(json!(1))
+            })
+        );
+    }
+
+    #[test]
+    fn values_round_trip_through_json() {
+        let value = Value::Object(
+            [
+                ("z".to_string(), Value::Int(i64::MIN)),
+                ("a".to_string(), Value::Float(1.0)),
+                ("bytes".to_string(), Value::Bytes(vec![0, 255])),
+                (
+                    "list".to_string(),
+                    Value::Array(vec![Value::Null, Value::Bool(true), Value::from("s")]),
+                ),
+            ]
+            .into(),
+        );
+        let json = to_json(&value).unwrap();
+        assert_eq!(
+            json.to_string(),
+            r#"{"z":-9223372036854775808,"a":1.0,"bytes":{"$bytes":"AP8="},"list":[null,true,"s"]}"#
+        );
+        assert_eq!(from_json(json).unwrap(), value);
+
+        assert!(to_json(&Value::Float(f64::NAN)).is_err());
+        assert!(from_json(json!(u64::MAX)).is_err());
+        assert!(from_json(json!({ BYTES_KEY: "not base64!" })).is_err());
+    }
+}
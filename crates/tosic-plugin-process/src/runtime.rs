@@ -0,0 +1,385 @@
+//! Out-of-process implementation of the [`Runtime`] and [`Plugin`] traits.
+
+use std::io::{self, BufReader, Write};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::process::{Child, ChildStdin, ChildStdout, Command, ExitStatus, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde_json::{Value as Json, json};
+use tempfile::TempPath;
+#[cfg(feature = "async")]
+use tosic_plugin_core::AsyncRuntime;
+use tosic_plugin_core::{HostContext, Plugin, PluginError, PluginResult, Runtime, Value};
+
+use crate::protocol::{self, Message, RpcError};
+
+/// How long a plugin process may take to exit on its own before it is killed.
+const EXIT_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
+/// Runtime that runs each plugin in its own child process.
+///
+/// The host talks to the plugin over its stdin and stdout using the framed JSON-RPC
+/// protocol described in the [`protocol`] module; the plugin's stderr is inherited. A
+/// plugin that crashes or exits only fails its own calls with `PluginError::PluginCrashed`
+/// and never takes the host down with it.
+#[derive(Clone, Default)]
+pub struct ProcessRuntime {}
+
+impl ProcessRuntime {
+    /// Creates a new process runtime.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawns a plugin process from a prepared command.
+    ///
+    /// The command's stdin and stdout are replaced by pipes to the host; its arguments,
+    /// environment, and working directory are kept as configured.
+    ///
+    /// # Errors
+    /// Returns `PluginError::LoadError` if the process cannot be spawned or fails the
+    /// initialization handshake.
+    pub fn spawn(&self, command: Command, context: &HostContext) -> PluginResult<ProcessPlugin> {
+        ProcessPlugin::spawn(command, context, None)
+    }
+
+    fn load_sync(&self, bytes: &[u8], context: &HostContext) -> PluginResult<ProcessPlugin> {
+        // The bytes are an executable, which is written out to a temporary file that lives as
+        // long as the plugin.
+        let mut file = tempfile::Builder::new()
+            .prefix("tosic-plugin-")
+            .suffix(std::env::consts::EXE_SUFFIX)
+            .tempfile()
+            .map_err(|e| {
+                PluginError::LoadError(format!("failed to create temporary executable: {e}"))
+            })?;
+        file.write_all(bytes)
+            .and_then(|()| file.flush())
+            .map_err(|e| {
+                PluginError::LoadError(format!("failed to write temporary executable: {e}"))
+            })?;
+
+        // Closing the file first avoids "text file busy" errors when executing it.
+        let path = file.into_temp_path();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o700)).map_err(
+                |e| {
+                    PluginError::LoadError(format!(
+                        "failed to make temporary executable runnable: {e}"
+                    ))
+                },
+            )?;
+        }
+
+        ProcessPlugin::spawn(Command::new(&path), context, Some(path))
+    }
+
+    fn call_sync(
+        &self,
+        plugin: &ProcessPlugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        let params = protocol::call_params(function_name, args)?;
+        let mut connection = plugin
+            .connection
+            .lock()
+            .map_err(|_| PluginError::InvalidPluginState)?;
+
+        match connection.request(protocol::CALL_METHOD, params, &plugin.context)? {
+            Ok(result) => protocol::from_json(result),
+            Err(error) if error.code == protocol::FUNCTION_NOT_FOUND => {
+                Err(PluginError::FunctionNotFound(function_name.to_string()))
+            }
+            Err(error) => Err(PluginError::CallError {
+                function: function_name.to_string(),
+                message: error.message,
+            }),
+        }
+    }
+}
+
+/// A plugin process spawned by a [`ProcessRuntime`].
+///
+/// Calls are sent to the process one at a time. Dropping the plugin closes its stdin and
+/// kills the process if it does not exit shortly after.
+pub struct ProcessPlugin {
+    name: Option<String>,
+    id: u32,
+    context: HostContext,
+    // Declared before the executable so the process is gone before its file is removed.
+    connection: Mutex<Connection>,
+    _executable: Option<TempPath>,
+}
+
+impl Plugin for ProcessPlugin {
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl ProcessPlugin {
+    /// Returns the OS process id of the plugin process.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn spawn(
+        mut command: Command,
+        context: &HostContext,
+        executable: Option<TempPath>,
+    ) -> PluginResult<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| PluginError::LoadError(format!("failed to spawn plugin process: {e}")))?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let mut connection = Connection {
+            child,
+            stdin: Some(stdin),
+            stdout: BufReader::new(stdout),
+            next_id: 0,
+            failure: None,
+        };
+
+        let params = json!({
+            "protocol_version": protocol::PROTOCOL_VERSION,
+            "host_functions": context.function_names().collect::<Vec<_>>(),
+        });
+        let result = connection
+            .request(protocol::INITIALIZE_METHOD, params, context)
+            .map_err(|e| {
+                PluginError::LoadError(format!("plugin process failed to initialize: {e}"))
+            })?
+            .map_err(|e| {
+                PluginError::LoadError(format!(
+                    "plugin process rejected initialization: {}",
+                    e.message
+                ))
+            })?;
+
+        let version = result.get("protocol_version").and_then(Json::as_u64);
+        if version != Some(u64::from(protocol::PROTOCOL_VERSION)) {
+            return Err(PluginError::LoadError(format!(
+                "plugin speaks protocol version {}, but the host speaks protocol version {}",
+                version.map_or_else(|| "unknown".to_string(), |v| v.to_string()),
+                protocol::PROTOCOL_VERSION
+            )));
+        }
+
+        Ok(Self {
+            name: result
+                .get("name")
+                .and_then(Json::as_str)
+                .map(str::to_string),
+            id: connection.child.id(),
+            context: context.clone(),
+            connection: Mutex::new(connection),
+            _executable: executable,
+        })
+    }
+}
+
+/// The pipes to a plugin process and the state of the protocol.
+struct Connection {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+    /// Set once the process crashed or broke the protocol; every later call fails with it.
+    failure: Option<String>,
+}
+
+impl Connection {
+    /// Sends a request and serves reverse requests from the plugin until it responds.
+    fn request(
+        &mut self,
+        method: &str,
+        params: Json,
+        context: &HostContext,
+    ) -> PluginResult<Result<Json, RpcError>> {
+        if let Some(failure) = &self.failure {
+            return Err(PluginError::PluginCrashed(failure.clone()));
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&Message::Request {
+            id,
+            method: method.to_string(),
+            params,
+        })?;
+
+        loop {
+            match self.receive()? {
+                Message::Response {
+                    id: response_id,
+                    result,
+                } if response_id == id => return Ok(result),
+                Message::Response {
+                    id: response_id, ..
+                } => {
+                    return Err(self.fail(invalid_data(format!(
+                        "unexpected response to request {response_id}"
+                    ))));
+                }
+                Message::Request {
+                    id: request_id,
+                    method,
+                    params,
+                } => {
+                    let result = handle_request(&method, params, context);
+                    self.send(&Message::Response {
+                        id: request_id,
+                        result,
+                    })?;
+                }
+            }
+        }
+    }
+
+    fn send(&mut self, message: &Message) -> PluginResult<()> {
+        let result = match &mut self.stdin {
+            Some(stdin) => protocol::write_message(stdin, message),
+            None => Err(io::ErrorKind::BrokenPipe.into()),
+        };
+        result.map_err(|e| self.fail(e))
+    }
+
+    fn receive(&mut self) -> PluginResult<Message> {
+        match protocol::read_message(&mut self.stdout) {
+            Ok(Some(message)) => Ok(message),
+            Ok(None) => Err(self.fail(io::ErrorKind::UnexpectedEof.into())),
+            Err(e) => Err(self.fail(e)),
+        }
+    }
+
+    /// Marks the connection as failed, reaping the process, and returns the error to report.
+    ///
+    /// Pipe errors mean the process went away and are reported as crashes. Any other error
+    /// leaves the stream out of sync, so the process is killed.
+    fn fail(&mut self, error: io::Error) -> PluginError {
+        let crashed = matches!(
+            error.kind(),
+            io::ErrorKind::UnexpectedEof | io::ErrorKind::BrokenPipe
+        );
+        let status = self.terminate();
+
+        let failure = match (crashed, status) {
+            (true, Some(status)) => format!("plugin process exited with {status}"),
+            (true, None) => format!("lost connection to plugin process: {error}"),
+            (false, _) => format!("plugin process was killed after a protocol error: {error}"),
+        };
+        self.failure = Some(failure.clone());
+
+        if crashed {
+            PluginError::PluginCrashed(failure)
+        } else {
+            PluginError::RuntimeError(failure)
+        }
+    }
+
+    /// Closes stdin and waits for the process to exit, killing it after
+    /// [`EXIT_GRACE_PERIOD`]. Returns the exit status if the process exited on its own.
+    fn terminate(&mut self) -> Option<ExitStatus> {
+        drop(self.stdin.take());
+
+        let deadline = Instant::now() + EXIT_GRACE_PERIOD;
+        loop {
+            match self.child.try_wait() {
+                Ok(Some(status)) => return Some(status),
+                Ok(None) if Instant::now() < deadline => {
+                    std::thread::sleep(Duration::from_millis(5))
+                }
+                _ => break,
+            }
+        }
+
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        None
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        if self.failure.is_none() {
+            self.terminate();
+        }
+    }
+}
+
+/// Serves a request sent by the plugin.
+fn handle_request(method: &str, params: Json, context: &HostContext) -> Result<Json, RpcError> {
+    if method != protocol::HOST_CALL_METHOD {
+        return Err(RpcError::new(
+            protocol::METHOD_NOT_FOUND,
+            format!("unknown method `{method}`"),
+        ));
+    }
+
+    let (function, args) = protocol::parse_call_params(params)
+        .map_err(|e| RpcError::new(protocol::INVALID_PARAMS, e.to_string()))?;
+    let result = catch_unwind(AssertUnwindSafe(|| context.call_function(&function, &args)))
+        .unwrap_or_else(|_| {
+            Err(PluginError::RuntimeError(format!(
+                "host function '{function}' panicked"
+            )))
+        });
+
+    match result.and_then(|value| protocol::to_json(&value)) {
+        Ok(value) => Ok(value),
+        Err(PluginError::HostFunctionNotFound(name)) if name == function => Err(RpcError::new(
+            protocol::FUNCTION_NOT_FOUND,
+            format!("host function '{name}' not found"),
+        )),
+        Err(error) => Err(RpcError::call_failed(&error)),
+    }
+}
+
+impl Runtime for ProcessRuntime {
+    type Plugin = ProcessPlugin;
+
+    fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        self.load_sync(bytes, context)
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+/// Calls block on the plugin process within the returned future.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncRuntime for ProcessRuntime {
+    type Plugin = ProcessPlugin;
+
+    async fn load_async(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        self.load_sync(bytes, context)
+    }
+
+    async fn call_async(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,111 @@
+//! Example demonstrating the Rhai runtime with a scripted plugin.
+//!
+//! This example shows how to:
+//! - Load a Rhai script as a plugin and call its functions
+//! - Pass arrays, object maps and BLOBs between the host and Rhai
+//! - Call host functions from Rhai, including catching their errors
+//! - Limit the work a script may do through the engine configuration
+//!
+//! Run with: `cargo run -p tosic-plugin-rhai --example rhai_runtime`
+
+use tosic_plugin_core::*;
+use tosic_plugin_rhai::RhaiRuntime;
+
+const PLUGIN: &str = r#"
+log("plugin loaded");
+
+fn configure(overrides) {
+    let config = #{ retries: 3, verbose: false };
+    config.mixin(overrides);
+    log(`configured with ${config.retries} retries`);
+    config
+}
+
+fn double_all(items) {
+    items.map(|item| multiply(item, 2))
+}
+
+fn checksum(data) {
+    let sum = 0;
+    for i in 0..data.len() { sum += data[i]; }
+    sum
+}
+
+fn checked_multiply(a, b) {
+    try {
+        return multiply(a, b);
+    } catch (err) {
+        return `host error: ${err}`;
+    }
+}
+
+fn spin() {
+    loop {}
+}
+
+private fn helper() {
+    42
+}
+"#;
+
+fn main() -> PluginResult<()> {
+    println!("=== Rhai Runtime Example ===\n");
+
+    let mut host_context = HostContext::new();
+    host_context.register("log", |message: String| {
+        println!("[HOST LOG] {}", message);
+    });
+    host_context.register("multiply", |a: i64, b: i64| -> i64 { a * b });
+
+    let runtime = RhaiRuntime::with_engine_config(|engine| {
+        engine.set_max_operations(100_000);
+    });
+
+    println!("Loading plugin...");
+    let plugin = runtime.load(PLUGIN.as_bytes(), &host_context)?;
+
+    println!("\n1. Calling configure(#{{ retries: 5 }}):");
+    let overrides = Map::from([("retries".to_string(), Value::Int(5))]);
+    let result = runtime.call(&plugin, "configure", &[Value::Object(overrides)])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("2. Calling double_all([1, 2, 3]):");
+    let items = Value::Array(vec![Value::Int(1), Value::Int(2), Value::Int(3)]);
+    let result = runtime.call(&plugin, "double_all", &[items])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("3. Calling checksum with a BLOB:");
+    let result = runtime.call(&plugin, "checksum", &[Value::Bytes(vec![1, 2, 3, 250])])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("=== Testing Error Cases ===\n");
+
+    println!("4. Host function error caught by the plugin with try/catch:");
+    let result = runtime.call(
+        &plugin,
+        "checked_multiply",
+        &[Value::Int(2), Value::String("x".to_string())],
+    )?;
+    println!("   Result: {:?}\n", result);
+
+    println!("5. Calling a script that exceeds the operation limit:");
+    match runtime.call(&plugin, "spin", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n6. Calling a private function:");
+    match runtime.call(&plugin, "helper", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n7. Calling non-existent function:");
+    match runtime.call(&plugin, "nonexistent", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n=== Example completed successfully! ===");
+    Ok(())
+}
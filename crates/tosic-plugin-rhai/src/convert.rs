@@ -0,0 +1,88 @@
+//! Conversions between plugin [`Value`]s and Rhai [`Dynamic`] values.
+//!
+//! | `Value`  | Rhai                           |
+//! |----------|--------------------------------|
+//! | `Null`   | `()`                           |
+//! | `Bool`   | `bool`                         |
+//! | `Int`    | `INT` (`i64`)                  |
+//! | `Float`  | `FLOAT` (`f64`)                |
+//! | `String` | string                         |
+//! | `Bytes`  | BLOB                           |
+//! | `Array`  | array                          |
+//! | `Object` | object map, sorted by key      |
+//!
+//! Going back, a Rhai character becomes a one-character `Value::String`.
+
+use rhai::{Array, Dynamic, Map};
+use tosic_plugin_core::{PluginError, PluginResult, Value};
+
+/// Converts a plugin value into a Rhai value.
+pub fn to_dynamic(value: &Value) -> Dynamic {
+    match value {
+        Value::Null => Dynamic::UNIT,
+        Value::Bool(b) => Dynamic::from_bool(*b),
+        Value::Int(i) => Dynamic::from_int(*i),
+        Value::Float(f) => Dynamic::from_float(*f),
+        Value::String(s) => Dynamic::from(s.clone()),
+        Value::Bytes(b) => Dynamic::from_blob(b.clone()),
+        Value::Array(items) => Dynamic::from_array(items.iter().map(to_dynamic).collect::<Array>()),
+        Value::Object(map) => Dynamic::from_map(
+            map.iter()
+                .map(|(key, item)| (key.as_str().into(), to_dynamic(item)))
+                .collect::<Map>(),
+        ),
+    }
+}
+
+/// Converts a Rhai value into a plugin value.
+///
+/// # Errors
+/// Returns `PluginError::RuntimeError` for Rhai values without a `Value` counterpart,
+/// such as function pointers or custom types.
+pub fn from_dynamic(value: Dynamic) -> PluginResult<Value> {
+    let value = value.flatten();
+
+    if value.is_unit() {
+        return Ok(Value::Null);
+    }
+    if let Ok(b) = value.as_bool() {
+        return Ok(Value::Bool(b));
+    }
+    if let Ok(i) = value.as_int() {
+        return Ok(Value::Int(i));
+    }
+    if let Ok(f) = value.as_float() {
+        return Ok(Value::Float(f));
+    }
+    if let Ok(c) = value.as_char() {
+        return Ok(Value::String(c.to_string()));
+    }
+    if value.is_string() {
+        let string = value.into_immutable_string().expect("value is a string");
+        return Ok(Value::String(string.into_owned()));
+    }
+    if value.is_blob() {
+        return Ok(Value::Bytes(value.into_blob().expect("value is a BLOB")));
+    }
+    if value.is_array() {
+        let array = value.into_array().expect("value is an array");
+        return array
+            .into_iter()
+            .map(from_dynamic)
+            .collect::<PluginResult<_>>()
+            .map(Value::Array);
+    }
+    if value.is_map() {
+        let map = value.cast::<Map>();
+        return map
+            .into_iter()
+            .map(|(key, item)| Ok((key.into(), from_dynamic(item)?)))
+            .collect::<PluginResult<tosic_plugin_core::Map>>()
+            .map(Value::Object);
+    }
+
+    Err(PluginError::RuntimeError(format!(
+        "cannot convert Rhai {} to a plugin value",
+        value.type_name()
+    )))
+}
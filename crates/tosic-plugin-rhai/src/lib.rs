@@ -0,0 +1,40 @@
+//! Rhai runtime for the tosic-plugin system.
+//!
+//! This crate implements the [`Runtime`](tosic_plugin_core::Runtime) trait on top of
+//! [Rhai](https://rhai.rs), an embedded scripting language written in pure Rust. With no
+//! C dependencies it builds for any target Rust supports, which makes it a good default
+//! scripting backend. A plugin is a Rhai script: loading it compiles the script and runs
+//! its top-level statements, and calling a function invokes the public script function of
+//! that name. Host functions are registered as native Rhai functions. See the [`convert`]
+//! module for how [`Value`](tosic_plugin_core::Value)s map to Rhai values.
+//!
+//! # Features
+//!
+//! - **async**: Also implement `AsyncRuntime`; scripts still execute synchronously inside the returned future
+//!
+//! # Example
+//!
+//! ```rust
+//! # fn main() -> tosic_plugin_core::PluginResult<()> {
+//! use tosic_plugin_core::*;
+//! use tosic_plugin_rhai::RhaiRuntime;
+//!
+//! let mut context = HostContext::new();
+//! context.register("add", |a: i64, b: i64| a + b);
+//!
+//! let runtime = RhaiRuntime::new();
+//! let plugin = runtime.load(b"fn run(x) { add(x, 1) }", &context)?;
+//! assert_eq!(runtime.call(&plugin, "run", &[Value::Int(41)])?, Value::Int(42));
+//! # Ok(())
+//! # }
+//! ```
+
+// Strict linting for release builds
+#![cfg_attr(not(debug_assertions), deny(missing_docs))]
+#![cfg_attr(not(debug_assertions), deny(clippy::all))]
+#![cfg_attr(not(debug_assertions), deny(unused))]
+
+pub mod convert;
+mod runtime;
+
+pub use runtime::*;
@@ -0,0 +1,259 @@
+//! Rhai implementation of the [`Runtime`] and [`Plugin`] traits.
+
+use std::any::TypeId;
+use std::sync::Arc;
+
+use rhai::{AST, CallFnOptions, Dynamic, Engine, EvalAltResult, FnAccess, Scope};
+#[cfg(feature = "async")]
+use tosic_plugin_core::AsyncRuntime;
+use tosic_plugin_core::{HostContext, Plugin, PluginError, PluginResult, Runtime, Value};
+
+use crate::convert::{from_dynamic, to_dynamic};
+
+/// Largest number of arguments host functions can be called with from Rhai.
+///
+/// This is the number of parameters Rhai resolves against `Dynamic` wildcards, which is
+/// fewer than the 32 parameters host functions may take. Loading a plugin fails if a host
+/// function requires more arguments, while optional and variadic parameters past the
+/// limit simply cannot be passed.
+pub const MAX_HOST_ARGS: usize = 16;
+
+/// Hook applied to every engine created by a [`RhaiRuntime`].
+type EngineConfig = Arc<dyn Fn(&mut Engine) + Send + Sync>;
+
+/// Runtime that executes Rhai scripts as plugins.
+///
+/// Loading a plugin compiles the script to an AST and runs its top-level statements once;
+/// the public functions it defines become the plugin's callable functions. Host functions
+/// are registered as native Rhai functions under their own names, so a script calls
+/// `log("hello")` directly. Script functions shadow host functions of the same name and arity.
+#[derive(Clone, Default)]
+pub struct RhaiRuntime {
+    config: Option<EngineConfig>,
+}
+
+impl RhaiRuntime {
+    /// Creates a runtime with Rhai's default engine settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a runtime that applies `config` to the engine of every plugin it loads,
+    /// e.g. to set operation or memory limits.
+    pub fn with_engine_config(config: impl Fn(&mut Engine) + Send + Sync + 'static) -> Self {
+        Self {
+            config: Some(Arc::new(config)),
+        }
+    }
+
+    fn load_sync(&self, bytes: &[u8], context: &HostContext) -> PluginResult<RhaiPlugin> {
+        let script = std::str::from_utf8(bytes)
+            .map_err(|e| PluginError::LoadError(format!("script is not valid UTF-8: {e}")))?;
+
+        let mut engine = Engine::new();
+        if let Some(config) = &self.config {
+            config(&mut engine);
+        }
+        register_host_functions(&mut engine, context)?;
+
+        let ast = engine
+            .compile(script)
+            .map_err(|e| PluginError::LoadError(e.to_string()))?;
+        engine
+            .run_ast(&ast)
+            .map_err(|e| PluginError::LoadError(e.to_string()))?;
+
+        Ok(RhaiPlugin { engine, ast })
+    }
+
+    fn call_sync(
+        &self,
+        plugin: &RhaiPlugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        let exported = plugin
+            .ast
+            .iter_functions()
+            .any(|function| function.name == function_name && function.access == FnAccess::Public);
+        if !exported {
+            return Err(PluginError::FunctionNotFound(function_name.to_string()));
+        }
+
+        let args = args.iter().map(to_dynamic).collect::<Vec<_>>();
+        let options = CallFnOptions::new().eval_ast(false);
+        let result = plugin
+            .engine
+            .call_fn_with_options::<Dynamic>(
+                options,
+                &mut Scope::new(),
+                &plugin.ast,
+                function_name,
+                args,
+            )
+            .map_err(|e| PluginError::CallError {
+                function: function_name.to_string(),
+                message: e.to_string(),
+            })?;
+
+        from_dynamic(result)
+    }
+}
+
+/// A Rhai script compiled by a [`RhaiRuntime`], together with its engine.
+pub struct RhaiPlugin {
+    engine: Engine,
+    ast: AST,
+}
+
+impl Plugin for RhaiPlugin {}
+
+impl RhaiPlugin {
+    /// Returns the engine the plugin runs on.
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// Returns the compiled script.
+    pub fn ast(&self) -> &AST {
+        &self.ast
+    }
+}
+
+impl Runtime for RhaiRuntime {
+    type Plugin = RhaiPlugin;
+
+    fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        self.load_sync(bytes, context)
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+/// Rhai scripts run to completion within the returned future.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncRuntime for RhaiRuntime {
+    type Plugin = RhaiPlugin;
+
+    async fn load_async(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        self.load_sync(bytes, context)
+    }
+
+    async fn call_async(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+/// Registers every host function as a native Rhai function.
+///
+/// Host functions have no fixed arity, so each one is registered once per arity up to
+/// [`MAX_HOST_ARGS`] with `Dynamic` parameters. Host function errors are raised as Rhai
+/// runtime errors, which scripts can catch with `try`/`catch`.
+///
+/// # Errors
+/// Returns `PluginError::LoadError` if a host function requires more than
+/// [`MAX_HOST_ARGS`] arguments, since scripts could never call it.
+fn register_host_functions(engine: &mut Engine, context: &HostContext) -> PluginResult<()> {
+    let context = Arc::new(context.clone());
+
+    for name in context.function_names() {
+        let required = context
+            .signature(name)
+            .map_or(0, |signature| signature.required());
+        if required > MAX_HOST_ARGS {
+            return Err(PluginError::LoadError(format!(
+                "host function '{name}' requires {required} arguments, but Rhai scripts can pass at most {MAX_HOST_ARGS}"
+            )));
+        }
+        for arity in 0..=MAX_HOST_ARGS {
+            let context = Arc::clone(&context);
+            let function_name = name.clone();
+            engine.register_raw_fn(
+                name.as_str(),
+                vec![TypeId::of::<Dynamic>(); arity],
+                move |_, args| {
+                    let args = args
+                        .iter_mut()
+                        .map(|arg| from_dynamic(std::mem::take(*arg)))
+                        .collect::<PluginResult<Vec<_>>>()
+                        .map_err(rhai_error)?;
+                    let result = context
+                        .call_function(&function_name, &args)
+                        .map_err(rhai_error)?;
+                    Ok(to_dynamic(&result))
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+fn rhai_error(error: PluginError) -> Box<EvalAltResult> {
+    error.to_string().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tosic_plugin_core::Rest;
+
+    #[test]
+    fn host_functions_are_callable_up_to_the_argument_limit() {
+        let mut context = HostContext::new();
+        context.register("sum", |Rest(items): Rest<i64>| items.iter().sum::<i64>());
+        let runtime = RhaiRuntime::new();
+
+        let plugin = runtime
+            .load(
+                b"fn run() { sum(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16) }",
+                &context,
+            )
+            .unwrap();
+        assert_eq!(runtime.call(&plugin, "run", &[]).unwrap(), Value::Int(136));
+    }
+
+    #[test]
+    fn loading_fails_for_host_functions_requiring_too_many_arguments() {
+        let mut context = HostContext::new();
+        context.register(
+            "wide",
+            |a: i64,
+             b: i64,
+             c: i64,
+             d: i64,
+             e: i64,
+             f: i64,
+             g: i64,
+             h: i64,
+             i: i64,
+             j: i64,
+             k: i64,
+             l: i64,
+             m: i64,
+             n: i64,
+             o: i64,
+             p: i64,
+             q: i64| {
+                a + b + c + d + e + f + g + h + i + j + k + l + m + n + o + p + q
+            },
+        );
+
+        let error = RhaiRuntime::new().load(b"", &context).err().unwrap();
+        assert!(
+            matches!(error, PluginError::LoadError(message) if message.contains("'wide' requires 17 arguments"))
+        );
+    }
+}
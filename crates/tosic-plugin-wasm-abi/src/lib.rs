@@ -0,0 +1,270 @@
+//! WebAssembly guest ABI shared by the tosic-plugin wasm runtimes.
+//!
+//! Every wasm backend speaks this ABI, so the same plugin binary can be loaded by any
+//! of them.
+//!
+//! Values cross the boundary as buffers in the guest's linear memory, encoded with
+//! [`Value::encode`]. A buffer is described by a pointer and a length which are
+//! packed into a single `i64` when returned from a function (see [`pack`]).
+//!
+//! # Guest exports
+//!
+//! - [`MEMORY_EXPORT`]: the guest's linear memory.
+//! - [`ALLOC_EXPORT`] `(len: i32) -> i32`: allocates `len` bytes and returns the pointer.
+//! - [`DEALLOC_EXPORT`] `(ptr: i32, len: i32)` (optional): releases a buffer the guest
+//!   handed back to the host.
+//! - Every plugin function, as `(ptr: i32, len: i32) -> i64`. The input buffer holds
+//!   the encoded `Value::Array` of arguments and is owned by the guest from then on.
+//!   The returned buffer holds an encoded result (see [`encode_result`]).
+//!
+//! # Host imports
+//!
+//! Every function registered in the [`HostContext`](tosic_plugin_core::HostContext) is
+//! importable from the [`HOST_MODULE`] module with the same `(ptr: i32, len: i32) -> i64`
+//! signature. The host allocates the result buffer through [`ALLOC_EXPORT`] and the
+//! guest owns it afterwards.
+
+// Strict linting for release builds
+#![cfg_attr(not(debug_assertions), deny(missing_docs))]
+#![cfg_attr(not(debug_assertions), deny(clippy::all))]
+#![cfg_attr(not(debug_assertions), deny(unsafe_code))]
+#![cfg_attr(not(debug_assertions), deny(unused))]
+
+use tosic_plugin_core::{HostContext, PluginError, PluginResult, Value};
+
+/// Module name under which host functions are imported.
+pub const HOST_MODULE: &str = "host";
+
+/// Name of the exported linear memory.
+pub const MEMORY_EXPORT: &str = "memory";
+
+/// Name of the exported allocation function.
+pub const ALLOC_EXPORT: &str = "tosic_alloc";
+
+/// Name of the optional exported deallocation function.
+pub const DEALLOC_EXPORT: &str = "tosic_dealloc";
+
+/// Result status byte for a successful call.
+const STATUS_OK: u8 = 0;
+
+/// Result status byte for a failed call, followed by a UTF-8 error message.
+const STATUS_ERR: u8 = 1;
+
+/// Packs a buffer pointer and length into a single `i64`, pointer in the high half.
+pub fn pack(ptr: u32, len: u32) -> i64 {
+    ((u64::from(ptr) << 32) | u64::from(len)) as i64
+}
+
+/// Splits a packed `i64` back into its pointer and length.
+pub fn unpack(packed: i64) -> (u32, u32) {
+    let packed = packed as u64;
+    ((packed >> 32) as u32, packed as u32)
+}
+
+/// Borrows the buffer at `ptr` of `len` bytes from a guest's linear memory.
+///
+/// The guest controls both values, so they are checked against the memory before
+/// anything is read or allocated.
+///
+/// # Errors
+/// Returns `PluginError::RuntimeError` if the buffer lies outside the memory.
+pub fn read_buffer(memory: &[u8], ptr: u32, len: u32) -> PluginResult<&[u8]> {
+    let start = ptr as usize;
+    start
+        .checked_add(len as usize)
+        .and_then(|end| memory.get(start..end))
+        .ok_or_else(|| out_of_bounds(ptr, len, memory.len()))
+}
+
+/// Copies `bytes` into a guest's linear memory at `ptr`.
+///
+/// # Errors
+/// Returns `PluginError::RuntimeError` if the buffer lies outside the memory.
+pub fn write_buffer(memory: &mut [u8], ptr: u32, bytes: &[u8]) -> PluginResult<()> {
+    let start = ptr as usize;
+    let size = memory.len();
+    start
+        .checked_add(bytes.len())
+        .and_then(|end| memory.get_mut(start..end))
+        .ok_or_else(|| out_of_bounds(ptr, bytes.len(), size))?
+        .copy_from_slice(bytes);
+    Ok(())
+}
+
+/// Converts the length of a buffer passed to the guest into a wasm `i32`.
+///
+/// # Errors
+/// Returns `PluginError::RuntimeError` if the buffer does not fit in the guest's address space.
+pub fn buffer_len(len: usize) -> PluginResult<i32> {
+    i32::try_from(len).map_err(|_| {
+        PluginError::RuntimeError(format!("buffer of {len} bytes exceeds guest address space"))
+    })
+}
+
+fn out_of_bounds(ptr: u32, len: impl std::fmt::Display, size: usize) -> PluginError {
+    PluginError::RuntimeError(format!(
+        "guest buffer out of bounds: {len} bytes at {ptr} in a memory of {size} bytes"
+    ))
+}
+
+/// Encodes the outcome of a call as a status byte followed by the payload.
+pub fn encode_result(result: &PluginResult<Value>) -> Vec<u8> {
+    match result {
+        Ok(value) => {
+            let mut out = vec![STATUS_OK];
+            value.encode_into(&mut out);
+            out
+        }
+        Err(error) => {
+            let mut out = vec![STATUS_ERR];
+            out.extend_from_slice(error.to_string().as_bytes());
+            out
+        }
+    }
+}
+
+/// Decodes a result buffer produced by [`encode_result`].
+///
+/// The outer result reports a malformed buffer, the inner one carries the error
+/// message reported by the other side of the boundary.
+///
+/// # Errors
+/// Returns `PluginError::EncodingError` if the buffer is malformed.
+pub fn decode_result(bytes: &[u8]) -> PluginResult<Result<Value, String>> {
+    match bytes.split_first() {
+        Some((&STATUS_OK, payload)) => Value::decode(payload).map(Ok),
+        Some((&STATUS_ERR, payload)) => Ok(Err(String::from_utf8_lossy(payload).into_owned())),
+        Some((status, _)) => Err(PluginError::EncodingError(format!(
+            "unknown result status {status}"
+        ))),
+        None => Err(PluginError::EncodingError(
+            "empty result buffer".to_string(),
+        )),
+    }
+}
+
+/// Decodes a plugin function's result buffer into its result, reporting the error message
+/// of a failed call as `PluginError::CallError`.
+///
+/// # Errors
+/// Returns `PluginError::EncodingError` if the buffer is malformed, or
+/// `PluginError::CallError` if the call failed.
+pub fn call_result(function_name: &str, bytes: &[u8]) -> PluginResult<Value> {
+    decode_result(bytes)?.map_err(|message| PluginError::CallError {
+        function: function_name.to_string(),
+        message,
+    })
+}
+
+/// Calls the host function `name` with the arguments encoded in `input` and encodes its
+/// result for the guest.
+///
+/// Malformed arguments and host function errors are encoded for the guest to handle.
+/// Async host functions need an executor injected into the context with
+/// `HostContext::with_block_on`.
+pub fn call_host_function(context: &HostContext, name: &str, input: &[u8]) -> Vec<u8> {
+    let result = decode_args(input).and_then(|args| context.call_function(name, &args));
+    encode_result(&result)
+}
+
+/// Decodes the argument buffer passed to a function.
+///
+/// # Errors
+/// Returns `PluginError::EncodingError` if the buffer is not an encoded `Value::Array`.
+pub fn decode_args(bytes: &[u8]) -> PluginResult<Vec<Value>> {
+    match Value::decode(bytes)? {
+        Value::Array(args) => Ok(args),
+        _ => Err(PluginError::EncodingError(
+            "arguments must be an array".to_string(),
+        )),
+    }
+}
+
+/// Encodes call arguments into the buffer passed to a function.
+pub fn encode_args(args: &[Value]) -> Vec<u8> {
+    Value::Array(args.to_vec()).encode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_round_trips_pointer_and_length() {
+        for (ptr, len) in [
+            (0, 0),
+            (8, 16),
+            (u32::MAX, 1),
+            (1, u32::MAX),
+            (u32::MAX, u32::MAX),
+        ] {
+            assert_eq!(unpack(pack(ptr, len)), (ptr, len));
+        }
+    }
+
+    #[test]
+    fn buffers_are_bounds_checked() {
+        let mut memory = vec![0; 16];
+        write_buffer(&mut memory, 12, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(read_buffer(&memory, 12, 4).unwrap(), [1, 2, 3, 4]);
+        assert_eq!(read_buffer(&memory, 16, 0).unwrap(), []);
+
+        assert!(read_buffer(&memory, 13, 4).is_err());
+        assert!(read_buffer(&memory, 0, u32::MAX).is_err());
+        assert!(read_buffer(&memory, u32::MAX, u32::MAX).is_err());
+        assert!(write_buffer(&mut memory, 13, &[0; 4]).is_err());
+        assert!(write_buffer(&mut memory, u32::MAX, &[0]).is_err());
+        assert_eq!(memory[12..], [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn buffer_lengths_fit_in_i32() {
+        assert_eq!(buffer_len(16).unwrap(), 16);
+        assert!(buffer_len(i32::MAX as usize + 1).is_err());
+    }
+
+    #[test]
+    fn args_round_trip() {
+        let args = [Value::Int(1), Value::from("two"), Value::Null];
+        assert_eq!(decode_args(&encode_args(&args)).unwrap(), args);
+        assert!(decode_args(&Value::Int(1).encode()).is_err());
+    }
+
+    #[test]
+    fn results_round_trip() {
+        let ok = encode_result(&Ok(Value::Bool(true)));
+        assert_eq!(decode_result(&ok).unwrap(), Ok(Value::Bool(true)));
+
+        let err = encode_result(&Err(PluginError::HostError("boom".to_string())));
+        assert_eq!(
+            decode_result(&err).unwrap(),
+            Err(PluginError::HostError("boom".to_string()).to_string())
+        );
+    }
+
+    #[test]
+    fn host_functions_are_called_with_encoded_args() {
+        let mut context = HostContext::new();
+        context.register("add", |a: i64, b: i64| a + b);
+
+        let output = call_host_function(
+            &context,
+            "add",
+            &encode_args(&[Value::Int(1), Value::Int(2)]),
+        );
+        assert_eq!(decode_result(&output).unwrap(), Ok(Value::Int(3)));
+        let output = call_host_function(&context, "add", &[0xff]);
+        assert!(decode_result(&output).unwrap().is_err());
+        let output = call_host_function(&context, "missing", &encode_args(&[]));
+        assert!(
+            matches!(call_result("run", &output), Err(PluginError::CallError { function, .. }) if function == "run")
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_results() {
+        assert!(decode_result(&[]).is_err());
+        assert!(decode_result(&[2]).is_err());
+        assert!(decode_result(&[STATUS_OK, 42]).is_err());
+    }
+}
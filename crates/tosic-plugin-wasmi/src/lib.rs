@@ -0,0 +1,39 @@
+//! Interpreter-based WebAssembly runtime for the tosic-plugin system.
+//!
+//! This crate implements the [`Runtime`](tosic_plugin_core::Runtime) trait on top of
+//! [wasmi](https://github.com/wasmi-labs/wasmi), a pure WebAssembly interpreter. It never
+//! generates machine code, so plugins can run on targets where executable memory is
+//! forbidden. Modules follow the same [`abi`] as the Wasmtime runtime, so a plugin binary
+//! built for one backend runs unchanged on the other.
+//!
+//! # Features
+//!
+//! - **async**: Also implement `AsyncRuntime`; plugins still execute synchronously inside the returned future
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # fn main() -> tosic_plugin_core::PluginResult<()> {
+//! use tosic_plugin_core::*;
+//! use tosic_plugin_wasmi::WasmiRuntime;
+//!
+//! let mut context = HostContext::new();
+//! context.register("add", |a: i64, b: i64| a + b);
+//!
+//! let runtime = WasmiRuntime::new();
+//! let plugin = runtime.load(&std::fs::read("plugin.wasm").unwrap(), &context)?;
+//! let result = runtime.call(&plugin, "run", &[Value::Int(1)])?;
+//! # let _ = result;
+//! # Ok(())
+//! # }
+//! ```
+
+// Strict linting for release builds
+#![cfg_attr(not(debug_assertions), deny(missing_docs))]
+#![cfg_attr(not(debug_assertions), deny(clippy::all))]
+#![cfg_attr(not(debug_assertions), deny(unused))]
+
+mod runtime;
+
+pub use runtime::*;
+pub use tosic_plugin_wasm_abi as abi;
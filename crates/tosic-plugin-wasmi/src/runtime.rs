@@ -0,0 +1,262 @@
+//! wasmi implementation of the [`Runtime`] and [`Plugin`] traits.
+
+use std::sync::Mutex;
+
+#[cfg(feature = "async")]
+use tosic_plugin_core::AsyncRuntime;
+use tosic_plugin_core::{HostContext, Plugin, PluginError, PluginResult, Runtime, Value};
+use wasmi::{
+    AsContext, AsContextMut, Caller, Config, Engine, Extern, Instance, Linker, Memory, Module,
+    Store, TypedFunc,
+};
+
+use crate::abi;
+
+/// Signature of plugin functions and host imports: `(ptr, len) -> packed (ptr, len)`.
+type AbiFunc = TypedFunc<(i32, i32), i64>;
+
+/// Per-store data made available to host function imports.
+struct HostState {
+    context: HostContext,
+}
+
+/// Runtime that interprets core WebAssembly modules with wasmi.
+///
+/// No executable memory is ever allocated, which makes this runtime usable on targets
+/// that forbid JIT compilation. See the [`abi`] module for the contract a module must
+/// follow to be loaded.
+#[derive(Clone, Default)]
+pub struct WasmiRuntime {
+    engine: Engine,
+}
+
+impl WasmiRuntime {
+    /// Creates a runtime with the default wasmi configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a runtime from a custom wasmi configuration.
+    pub fn with_config(config: &Config) -> Self {
+        Self {
+            engine: Engine::new(config),
+        }
+    }
+
+    /// Returns the underlying wasmi engine.
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    fn load_sync(&self, bytes: &[u8], context: &HostContext) -> PluginResult<WasmiPlugin> {
+        let module = Module::new(&self.engine, bytes).map_err(load_error)?;
+
+        let mut linker = Linker::new(&self.engine);
+        for name in context.function_names() {
+            define_host_function(&mut linker, name.clone())?;
+        }
+
+        let mut store = Store::new(
+            &self.engine,
+            HostState {
+                context: context.clone(),
+            },
+        );
+        let instance = linker
+            .instantiate_and_start(&mut store, &module)
+            .map_err(load_error)?;
+        let exports = GuestExports::resolve(&store, &instance)?;
+
+        Ok(WasmiPlugin {
+            instance,
+            exports,
+            store: Mutex::new(store),
+        })
+    }
+
+    fn call_sync(
+        &self,
+        plugin: &WasmiPlugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        let mut store = plugin
+            .store
+            .lock()
+            .map_err(|_| PluginError::InvalidPluginState)?;
+        let func = plugin.function(&store, function_name)?;
+        let exports = &plugin.exports;
+        let to_call_error = |e| call_error(function_name, e);
+
+        let input = abi::encode_args(args);
+        let ptr = exports
+            .alloc
+            .call(&mut *store, abi::buffer_len(input.len())?)
+            .map_err(to_call_error)?;
+        write_guest(&mut *store, exports.memory, ptr, &input)?;
+
+        let packed = func
+            .call(&mut *store, (ptr, abi::buffer_len(input.len())?))
+            .map_err(to_call_error)?;
+        let (ptr, len) = abi::unpack(packed);
+        let output = read_guest(&*store, exports.memory, ptr, len)?;
+        if let Some(dealloc) = &exports.dealloc {
+            dealloc
+                .call(&mut *store, (ptr as i32, len as i32))
+                .map_err(to_call_error)?;
+        }
+
+        abi::call_result(function_name, &output)
+    }
+}
+
+/// A WebAssembly module instantiated by a [`WasmiRuntime`].
+pub struct WasmiPlugin {
+    instance: Instance,
+    exports: GuestExports,
+    store: Mutex<Store<HostState>>,
+}
+
+impl Plugin for WasmiPlugin {}
+
+impl WasmiPlugin {
+    fn function(&self, store: &Store<HostState>, function_name: &str) -> PluginResult<AbiFunc> {
+        let func = self
+            .instance
+            .get_func(store, function_name)
+            .ok_or_else(|| PluginError::FunctionNotFound(function_name.to_string()))?;
+        func.typed(store).map_err(|e| call_error(function_name, e))
+    }
+}
+
+/// Exports every loadable module must provide.
+struct GuestExports {
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    dealloc: Option<TypedFunc<(i32, i32), ()>>,
+}
+
+impl GuestExports {
+    fn resolve(store: &Store<HostState>, instance: &Instance) -> PluginResult<Self> {
+        let memory = instance
+            .get_memory(store, abi::MEMORY_EXPORT)
+            .ok_or_else(|| {
+                PluginError::LoadError(format!("module does not export `{}`", abi::MEMORY_EXPORT))
+            })?;
+        let alloc = instance
+            .get_typed_func(store, abi::ALLOC_EXPORT)
+            .map_err(load_error)?;
+        let dealloc = match instance.get_func(store, abi::DEALLOC_EXPORT) {
+            Some(func) => Some(func.typed(store).map_err(load_error)?),
+            None => None,
+        };
+        Ok(Self {
+            memory,
+            alloc,
+            dealloc,
+        })
+    }
+}
+
+impl Runtime for WasmiRuntime {
+    type Plugin = WasmiPlugin;
+
+    fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        self.load_sync(bytes, context)
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+/// wasmi has no async support, so the async runtime executes plugins to completion
+/// within the returned future.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncRuntime for WasmiRuntime {
+    type Plugin = WasmiPlugin;
+
+    async fn load_async(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        self.load_sync(bytes, context)
+    }
+
+    async fn call_async(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+/// Defines an import in [`abi::HOST_MODULE`] that forwards to the named host function.
+///
+/// Host function errors are encoded for the guest to handle; only ABI violations trap.
+fn define_host_function(linker: &mut Linker<HostState>, name: String) -> PluginResult<()> {
+    linker
+        .func_wrap(
+            abi::HOST_MODULE,
+            &name.clone(),
+            move |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                let memory = caller
+                    .get_export(abi::MEMORY_EXPORT)
+                    .and_then(Extern::into_memory)
+                    .ok_or_else(|| {
+                        wasmi::Error::new(format!(
+                            "module does not export `{}`",
+                            abi::MEMORY_EXPORT
+                        ))
+                    })?;
+                let input = read_guest(&caller, memory, ptr as u32, len as u32).map_err(trap)?;
+                let output = abi::call_host_function(&caller.data().context, &name, &input);
+
+                let alloc: TypedFunc<i32, i32> = caller
+                    .get_export(abi::ALLOC_EXPORT)
+                    .and_then(Extern::into_func)
+                    .ok_or_else(|| {
+                        wasmi::Error::new(format!("module does not export `{}`", abi::ALLOC_EXPORT))
+                    })?
+                    .typed(&caller)?;
+                let ptr = alloc.call(&mut caller, abi::buffer_len(output.len()).map_err(trap)?)?;
+                write_guest(&mut caller, memory, ptr, &output).map_err(trap)?;
+                Ok(abi::pack(ptr as u32, output.len() as u32))
+            },
+        )
+        .map_err(|e| PluginError::LoadError(e.to_string()))?;
+    Ok(())
+}
+
+fn read_guest(store: impl AsContext, memory: Memory, ptr: u32, len: u32) -> PluginResult<Vec<u8>> {
+    abi::read_buffer(memory.data(&store), ptr, len).map(<[u8]>::to_vec)
+}
+
+fn write_guest(
+    mut store: impl AsContextMut,
+    memory: Memory,
+    ptr: i32,
+    bytes: &[u8],
+) -> PluginResult<()> {
+    abi::write_buffer(memory.data_mut(&mut store), ptr as u32, bytes)
+}
+
+fn trap(error: PluginError) -> wasmi::Error {
+    wasmi::Error::new(error.to_string())
+}
+
+fn load_error(error: wasmi::Error) -> PluginError {
+    PluginError::LoadError(error.to_string())
+}
+
+fn call_error(function_name: &str, error: wasmi::Error) -> PluginError {
+    PluginError::CallError {
+        function: function_name.to_string(),
+        message: error.to_string(),
+    }
+}
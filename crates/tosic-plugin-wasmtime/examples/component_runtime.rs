@@ -0,0 +1,226 @@
+//! Example demonstrating the component runtime with a WebAssembly component.
+//!
+//! This example shows how to:
+//! - Load a component and satisfy its WIT imports from the host context
+//! - Pass strings, records and lists through their WIT types
+//! - Receive `option` and `result` values
+//! - Handle arguments that do not match the WIT signature
+//! - Reject host functions whose signature does not match the component's imports
+//!
+//! The component is written in the WebAssembly text format; real plugins would be built
+//! from WIT with a toolchain such as `cargo component`. Its interface is:
+//!
+//! ```wit
+//! record point { x: s32, y: s32 }
+//!
+//! import log: func(message: string);
+//! import add: func(a: s32, b: s32) -> s32;
+//!
+//! export greet: func(name: string) -> string;
+//! export add-twice: func(a: s32, b: s32) -> s32;
+//! export manhattan: func(p: point) -> s32;
+//! export first-negative: func(items: list<s32>) -> option<s32>;
+//! export safe-div: func(a: s32, b: s32) -> result<s32, string>;
+//! ```
+//!
+//! Run with: `cargo run -p tosic-plugin-wasmtime --features component-model --example component_runtime`
+
+use tosic_plugin_core::*;
+use tosic_plugin_wasmtime::ComponentRuntime;
+
+const PLUGIN_WAT: &str = r#"
+(component
+  (type $point (record (field "x" s32) (field "y" s32)))
+  (export $point-export "point" (type $point))
+
+  (import "log" (func $log (param "message" string)))
+  (import "add" (func $add (param "a" s32) (param "b" s32) (result s32)))
+
+  ;; Memory and a bump allocator shared by the canonical ABI
+  (core module $libc
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 1024))
+    (func (export "realloc") (param $old i32) (param $old_size i32) (param $align i32) (param $size i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get $align) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get $align))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get $size)))
+      (local.get $ptr))
+  )
+  (core instance $libc (instantiate $libc))
+
+  (core func $log-lowered (canon lower (func $log) (memory (core memory $libc "memory"))))
+  (core func $add-lowered (canon lower (func $add)))
+
+  (core module $plugin
+    (import "libc" "memory" (memory 1))
+    (import "libc" "realloc" (func $realloc (param i32 i32 i32 i32) (result i32)))
+    (import "host" "log" (func $log (param i32 i32)))
+    (import "host" "add" (func $add (param i32 i32) (result i32)))
+
+    (data (i32.const 0) "Hello, ")
+    (data (i32.const 16) "greeting from a component")
+    (data (i32.const 48) "division by zero")
+
+    (func (export "greet") (param $ptr i32) (param $len i32) (result i32)
+      (local $out i32)
+      (local $ret i32)
+      (call $log (i32.const 16) (i32.const 25))
+      (local.set $out (call $realloc (i32.const 0) (i32.const 0) (i32.const 1) (i32.add (local.get $len) (i32.const 7))))
+      (memory.copy (local.get $out) (i32.const 0) (i32.const 7))
+      (memory.copy (i32.add (local.get $out) (i32.const 7)) (local.get $ptr) (local.get $len))
+      (local.set $ret (call $realloc (i32.const 0) (i32.const 0) (i32.const 4) (i32.const 8)))
+      (i32.store (local.get $ret) (local.get $out))
+      (i32.store offset=4 (local.get $ret) (i32.add (local.get $len) (i32.const 7)))
+      (local.get $ret))
+
+    (func (export "add-twice") (param $a i32) (param $b i32) (result i32)
+      (call $add (call $add (local.get $a) (local.get $b)) (local.get $b)))
+
+    (func $abs (param $n i32) (result i32)
+      (select (i32.sub (i32.const 0) (local.get $n)) (local.get $n) (i32.lt_s (local.get $n) (i32.const 0))))
+
+    (func (export "manhattan") (param $x i32) (param $y i32) (result i32)
+      (i32.add (call $abs (local.get $x)) (call $abs (local.get $y))))
+
+    (func (export "first-negative") (param $ptr i32) (param $len i32) (result i32)
+      (local $ret i32)
+      (local $item i32)
+      (local.set $ret (call $realloc (i32.const 0) (i32.const 0) (i32.const 4) (i32.const 8)))
+      (i32.store (local.get $ret) (i32.const 0))
+      (block $done
+        (loop $next
+          (br_if $done (i32.eqz (local.get $len)))
+          (local.set $item (i32.load (local.get $ptr)))
+          (if (i32.lt_s (local.get $item) (i32.const 0))
+            (then
+              (i32.store (local.get $ret) (i32.const 1))
+              (i32.store offset=4 (local.get $ret) (local.get $item))
+              (br $done)))
+          (local.set $ptr (i32.add (local.get $ptr) (i32.const 4)))
+          (local.set $len (i32.sub (local.get $len) (i32.const 1)))
+          (br $next)))
+      (local.get $ret))
+
+    (func (export "safe-div") (param $a i32) (param $b i32) (result i32)
+      (local $ret i32)
+      (local.set $ret (call $realloc (i32.const 0) (i32.const 0) (i32.const 4) (i32.const 12)))
+      (if (i32.eqz (local.get $b))
+        (then
+          (i32.store (local.get $ret) (i32.const 1))
+          (i32.store offset=4 (local.get $ret) (i32.const 48))
+          (i32.store offset=8 (local.get $ret) (i32.const 16)))
+        (else
+          (i32.store (local.get $ret) (i32.const 0))
+          (i32.store offset=4 (local.get $ret) (i32.div_s (local.get $a) (local.get $b)))))
+      (local.get $ret))
+  )
+  (core instance $plugin (instantiate $plugin
+    (with "libc" (instance $libc))
+    (with "host" (instance
+      (export "log" (func $log-lowered))
+      (export "add" (func $add-lowered))))))
+
+  (func (export "greet") (param "name" string) (result string)
+    (canon lift (core func $plugin "greet") (memory (core memory $libc "memory")) (realloc (core func $libc "realloc"))))
+  (func (export "add-twice") (param "a" s32) (param "b" s32) (result s32)
+    (canon lift (core func $plugin "add-twice")))
+  (func (export "manhattan") (param "p" $point-export) (result s32)
+    (canon lift (core func $plugin "manhattan")))
+  (func (export "first-negative") (param "items" (list s32)) (result (option s32))
+    (canon lift (core func $plugin "first-negative") (memory (core memory $libc "memory")) (realloc (core func $libc "realloc"))))
+  (func (export "safe-div") (param "a" s32) (param "b" s32) (result (result s32 (error string)))
+    (canon lift (core func $plugin "safe-div") (memory (core memory $libc "memory"))))
+)
+"#;
+
+fn main() -> PluginResult<()> {
+    println!("=== Component Runtime Example ===\n");
+
+    let mut host_context = HostContext::new();
+    host_context.register("log", |message: String| {
+        println!("[HOST LOG] {}", message);
+    });
+    host_context.register("add", |a: i64, b: i64| -> i64 { a + b });
+
+    let runtime = ComponentRuntime::new()?;
+
+    println!("Loading component...");
+    let plugin = runtime.load(PLUGIN_WAT.as_bytes(), &host_context)?;
+
+    println!("\n1. Calling greet(\"World\"):");
+    let result = runtime.call(&plugin, "greet", &[Value::from("World")])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("2. Calling add-twice(5, 3), which calls the host's add twice:");
+    let result = runtime.call(&plugin, "add-twice", &[Value::Int(5), Value::Int(3)])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("3. Calling manhattan({{ x: -3, y: 4 }}):");
+    let point = Map::from([
+        ("x".to_string(), Value::Int(-3)),
+        ("y".to_string(), Value::Int(4)),
+    ]);
+    let result = runtime.call(&plugin, "manhattan", &[Value::Object(point)])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("4. Calling first-negative([4, -2, -7]) and first-negative([1]):");
+    let items = Value::Array(vec![Value::Int(4), Value::Int(-2), Value::Int(-7)]);
+    let result = runtime.call(&plugin, "first-negative", &[items])?;
+    println!("   Result: {:?}", result);
+    let result = runtime.call(
+        &plugin,
+        "first-negative",
+        &[Value::Array(vec![Value::Int(1)])],
+    )?;
+    println!("   Result: {:?}\n", result);
+
+    println!("5. Calling safe-div(7, 2) and safe-div(7, 0):");
+    let result = runtime.call(&plugin, "safe-div", &[Value::Int(7), Value::Int(2)])?;
+    println!("   Result: {:?}", result);
+    let result = runtime.call(&plugin, "safe-div", &[Value::Int(7), Value::Int(0)])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("=== Testing Error Cases ===\n");
+
+    println!("6. Calling manhattan with a string coordinate:");
+    let point = Map::from([
+        ("x".to_string(), Value::Int(1)),
+        ("y".to_string(), Value::from("two")),
+    ]);
+    match runtime.call(&plugin, "manhattan", &[Value::Object(point)]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n7. Calling add-twice with an out-of-range argument:");
+    match runtime.call(&plugin, "add-twice", &[Value::Int(1), Value::Int(i64::MAX)]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n8. Calling greet without arguments:");
+    match runtime.call(&plugin, "greet", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n9. Calling non-existent function:");
+    match runtime.call(&plugin, "nonexistent", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n10. Loading the component with a mismatched `add` host function:");
+    let mut mismatched_context = host_context.clone();
+    mismatched_context.register("add", |a: String, b: String| format!("{}{}", a, b));
+    match runtime.load(PLUGIN_WAT.as_bytes(), &mismatched_context) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n=== Example completed successfully! ===");
+    Ok(())
+}
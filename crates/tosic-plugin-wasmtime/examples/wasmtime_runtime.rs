@@ -0,0 +1,90 @@
+//! Example demonstrating the Wasmtime runtime with a hand-written WebAssembly plugin.
+//!
+//! This example shows how to:
+//! - Load a core WebAssembly module that follows the tosic-plugin ABI
+//! - Pass `Value`s into a plugin function and receive them back
+//! - Let a plugin call a host function registered in the `HostContext`
+//!
+//! Run with: `cargo run -p tosic-plugin-wasmtime --example wasmtime_runtime`
+
+use tosic_plugin_core::*;
+use tosic_plugin_wasmtime::WasmtimeRuntime;
+
+/// A minimal plugin with a bump allocator.
+///
+/// - `echo` returns its argument array unchanged by prefixing the input buffer with an OK status.
+/// - `add` forwards its arguments to the host's `add` function and returns the host's result.
+const PLUGIN: &str = r#"
+(module $example
+  (import "host" "add" (func $host_add (param i32 i32) (result i64)))
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 1024))
+
+  (func $alloc (export "tosic_alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+    (local.get $ptr))
+
+  (func (export "echo") (param $ptr i32) (param $len i32) (result i64)
+    (local $out i32)
+    (local.set $out (call $alloc (i32.add (local.get $len) (i32.const 1))))
+    (i32.store8 (local.get $out) (i32.const 0))
+    (memory.copy (i32.add (local.get $out) (i32.const 1)) (local.get $ptr) (local.get $len))
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $out)) (i64.const 32))
+      (i64.extend_i32_u (i32.add (local.get $len) (i32.const 1)))))
+
+  (func (export "add") (param $ptr i32) (param $len i32) (result i64)
+    (call $host_add (local.get $ptr) (local.get $len))))
+"#;
+
+fn main() -> PluginResult<()> {
+    println!("=== Wasmtime Runtime Example ===\n");
+
+    let mut host_context = HostContext::new();
+    host_context.register("add", |a: i64, b: i64| -> i64 {
+        println!("[HOST] Adding {} + {}", a, b);
+        a + b
+    });
+
+    let runtime = WasmtimeRuntime::new()?;
+
+    println!("Loading plugin...");
+    let plugin = runtime.load(PLUGIN.as_bytes(), &host_context)?;
+    println!("Loaded plugin: {:?}\n", plugin.name());
+
+    println!("1. Calling echo(\"hello\", 42, [true, null]):");
+    let args = [
+        Value::String("hello".to_string()),
+        Value::Int(42),
+        Value::Array(vec![Value::Bool(true), Value::Null]),
+    ];
+    let result = runtime.call(&plugin, "echo", &args)?;
+    println!("   Result: {:?}\n", result);
+
+    println!("2. Calling add(5, 3), which calls back into the host:");
+    let result = runtime.call(&plugin, "add", &[Value::Int(5), Value::Int(3)])?;
+    println!("   Result: {:?}\n", result);
+
+    println!("=== Testing Error Cases ===\n");
+
+    println!("3. Calling add with wrong argument types:");
+    match runtime.call(
+        &plugin,
+        "add",
+        &[Value::String("five".to_string()), Value::Int(3)],
+    ) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n4. Calling non-existent function:");
+    match runtime.call(&plugin, "nonexistent", &[]) {
+        Ok(_) => println!("   Unexpected success!"),
+        Err(e) => println!("   Expected error: {}", e),
+    }
+
+    println!("\n=== Example completed successfully! ===");
+    Ok(())
+}
@@ -0,0 +1,578 @@
+//! Wasmtime component model implementation of the [`Runtime`] and [`Plugin`] traits.
+
+use futures::lock::Mutex;
+#[cfg(feature = "async")]
+use tosic_plugin_core::AsyncRuntime;
+use tosic_plugin_core::{HostContext, Plugin, PluginError, PluginResult, Runtime, Value};
+use wasmtime::component::types::{ComponentFunc, ComponentItem};
+use wasmtime::component::{Component, Func, Instance, Linker, LinkerInstance, Type, Val};
+use wasmtime::{Config, Engine, Store, StoreContextMut};
+
+use crate::wit;
+
+/// Per-store data made available to host function imports.
+struct ComponentState {
+    context: HostContext,
+}
+
+/// Runtime that instantiates WebAssembly components with Wasmtime.
+///
+/// Values cross the boundary as typed component values, converted as described in the
+/// [`wit`] module. Every function the component imports, at the top level or from an
+/// imported interface, is satisfied from the [`HostContext`]: an import `log` of interface
+/// `local:demo/host` resolves to the host function `local:demo/host#log`, then `log`, then
+/// `log` with dashes replaced by underscores. Exports are called by name, with functions of
+/// exported interfaces addressed as `interface#function`.
+///
+/// Imports are checked against the [`Signature`](tosic_plugin_core::Signature) of their host
+/// function when the component is loaded, so a component importing a function with the
+/// wrong number or kinds of parameters fails to load rather than on its first call.
+///
+/// When an import's WIT signature returns `result<T, string>`, the host function's return
+/// value becomes the `ok` payload and its error message the `err` payload.
+///
+/// Like [`WasmtimeRuntime`](crate::WasmtimeRuntime), a component runtime either runs on
+/// Wasmtime's async support or not, and implements both runtime traits either way.
+#[derive(Clone)]
+pub struct ComponentRuntime {
+    engine: Engine,
+    async_support: bool,
+}
+
+impl ComponentRuntime {
+    /// Creates a runtime with the default Wasmtime configuration.
+    ///
+    /// # Errors
+    /// Returns `PluginError::RuntimeError` if the engine cannot be created.
+    pub fn new() -> PluginResult<Self> {
+        Self::with_config(Config::new())
+    }
+
+    /// Creates a runtime from a custom Wasmtime configuration.
+    ///
+    /// The component model is always enabled.
+    ///
+    /// # Errors
+    /// Returns `PluginError::RuntimeError` if the engine cannot be created.
+    pub fn with_config(mut config: Config) -> PluginResult<Self> {
+        #[cfg(feature = "async")]
+        config.async_support(false);
+        #[cfg(not(feature = "async"))]
+        let _ = &mut config;
+
+        Self::from_config(config, false)
+    }
+
+    /// Creates a runtime with the default Wasmtime configuration and async support.
+    ///
+    /// # Errors
+    /// Returns `PluginError::RuntimeError` if the engine cannot be created.
+    #[cfg(feature = "async")]
+    pub fn new_async() -> PluginResult<Self> {
+        Self::with_async_config(Config::new())
+    }
+
+    /// Creates a runtime from a custom Wasmtime configuration, turning on async support.
+    ///
+    /// The component model is always enabled.
+    ///
+    /// # Errors
+    /// Returns `PluginError::RuntimeError` if the engine cannot be created.
+    #[cfg(feature = "async")]
+    pub fn with_async_config(mut config: Config) -> PluginResult<Self> {
+        config.async_support(true);
+        Self::from_config(config, true)
+    }
+
+    fn from_config(mut config: Config, async_support: bool) -> PluginResult<Self> {
+        config.wasm_component_model(true);
+        let engine =
+            Engine::new(&config).map_err(|e| PluginError::RuntimeError(format!("{e:#}")))?;
+        Ok(Self {
+            engine,
+            async_support,
+        })
+    }
+
+    /// Returns the underlying Wasmtime engine.
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// Returns true if the runtime runs components on Wasmtime's async support.
+    pub fn is_async(&self) -> bool {
+        self.async_support
+    }
+
+    /// Compiles the component and links every function it imports to the host context.
+    fn prepare(
+        &self,
+        bytes: &[u8],
+        context: &HostContext,
+    ) -> PluginResult<(Component, Linker<ComponentState>, Store<ComponentState>)> {
+        let component = Component::new(&self.engine, bytes).map_err(load_error)?;
+
+        let mut linker = Linker::new(&self.engine);
+        for (name, item) in component.component_type().imports(&self.engine) {
+            match item {
+                ComponentItem::ComponentFunc(ty) => {
+                    define_host_function(
+                        &mut linker.root(),
+                        context,
+                        None,
+                        name,
+                        &ty,
+                        self.async_support,
+                    )?;
+                }
+                ComponentItem::ComponentInstance(instance) => {
+                    let mut linker_instance = linker.instance(name).map_err(load_error)?;
+                    for (function, item) in instance.exports(&self.engine) {
+                        let ComponentItem::ComponentFunc(ty) = item else {
+                            return Err(PluginError::LoadError(format!(
+                                "component imports `{name}#{function}`, which is not a function"
+                            )));
+                        };
+                        define_host_function(
+                            &mut linker_instance,
+                            context,
+                            Some(name),
+                            function,
+                            &ty,
+                            self.async_support,
+                        )?;
+                    }
+                }
+                ComponentItem::Type(_) => {}
+                _ => {
+                    return Err(PluginError::LoadError(format!(
+                        "component imports `{name}`, which is neither a function nor an interface"
+                    )));
+                }
+            }
+        }
+
+        let store = Store::new(
+            &self.engine,
+            ComponentState {
+                context: context.clone(),
+            },
+        );
+        Ok((component, linker, store))
+    }
+}
+
+/// A WebAssembly component instantiated by a [`ComponentRuntime`].
+pub struct ComponentPlugin {
+    instance: Instance,
+    store: Mutex<Store<ComponentState>>,
+}
+
+impl Plugin for ComponentPlugin {}
+
+impl ComponentPlugin {
+    /// Looks up an exported function, with `interface#function` addressing a function of an
+    /// exported interface.
+    fn function(
+        &self,
+        store: &mut Store<ComponentState>,
+        function_name: &str,
+    ) -> PluginResult<Func> {
+        let not_found = || PluginError::FunctionNotFound(function_name.to_string());
+
+        let index = match function_name.split_once('#') {
+            Some((interface, function)) => {
+                let interface = self
+                    .instance
+                    .get_export_index(&mut *store, None, interface)
+                    .ok_or_else(not_found)?;
+                self.instance
+                    .get_export_index(&mut *store, Some(&interface), function)
+            }
+            None => self
+                .instance
+                .get_export_index(&mut *store, None, function_name),
+        };
+        index
+            .and_then(|index| self.instance.get_func(&mut *store, index))
+            .ok_or_else(not_found)
+    }
+}
+
+/// Converts call arguments to the parameter types of the function's WIT signature.
+fn lower_args(function_name: &str, ty: &ComponentFunc, args: &[Value]) -> PluginResult<Vec<Val>> {
+    let mismatch = |message: String| PluginError::CallError {
+        function: function_name.to_string(),
+        message: format!("{message} (WIT signature `{}`)", wit::signature(ty)),
+    };
+
+    if args.len() != ty.params().len() {
+        return Err(mismatch(format!(
+            "expected {} arguments, got {}",
+            ty.params().len(),
+            args.len()
+        )));
+    }
+    ty.params()
+        .zip(args)
+        .map(|((name, param_ty), arg)| {
+            wit::to_val(arg, &param_ty)
+                .map_err(|e| mismatch(format!("argument `{name}` does not match: {e}")))
+        })
+        .collect()
+}
+
+/// Converts the results of a call back into a plugin value.
+fn lift_results(ty: &ComponentFunc, results: Vec<Val>) -> PluginResult<Value> {
+    let mut values = results
+        .into_iter()
+        .zip(ty.results())
+        .map(|(val, result_ty)| wit::from_val(val, &result_ty))
+        .collect::<PluginResult<Vec<_>>>()?;
+    match values.len() {
+        0 => Ok(Value::Null),
+        1 => Ok(values.remove(0)),
+        _ => Ok(Value::Array(values)),
+    }
+}
+
+impl ComponentRuntime {
+    fn load_sync(&self, bytes: &[u8], context: &HostContext) -> PluginResult<ComponentPlugin> {
+        let (component, linker, mut store) = self.prepare(bytes, context)?;
+        let instance = linker
+            .instantiate(&mut store, &component)
+            .map_err(load_error)?;
+        Ok(ComponentPlugin {
+            instance,
+            store: Mutex::new(store),
+        })
+    }
+
+    fn call_sync(
+        &self,
+        plugin: &ComponentPlugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        let mut store = futures::executor::block_on(plugin.store.lock());
+        let func = plugin.function(&mut store, function_name)?;
+        let ty = func.ty(&*store);
+        let to_call_error = |e| call_error(function_name, e);
+
+        let params = lower_args(function_name, &ty, args)?;
+        let mut results = vec![Val::Bool(false); ty.results().len()];
+        func.call(&mut *store, &params, &mut results)
+            .map_err(to_call_error)?;
+        func.post_return(&mut *store).map_err(to_call_error)?;
+
+        lift_results(&ty, results)
+    }
+
+    #[cfg(feature = "async")]
+    async fn load_on_async_engine(
+        &self,
+        bytes: &[u8],
+        context: &HostContext,
+    ) -> PluginResult<ComponentPlugin> {
+        let (component, linker, mut store) = self.prepare(bytes, context)?;
+        let instance = linker
+            .instantiate_async(&mut store, &component)
+            .await
+            .map_err(load_error)?;
+        Ok(ComponentPlugin {
+            instance,
+            store: Mutex::new(store),
+        })
+    }
+
+    #[cfg(feature = "async")]
+    async fn call_on_async_engine(
+        &self,
+        plugin: &ComponentPlugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        let mut store = plugin.store.lock().await;
+        let func = plugin.function(&mut store, function_name)?;
+        let ty = func.ty(&*store);
+        let to_call_error = |e| call_error(function_name, e);
+
+        let params = lower_args(function_name, &ty, args)?;
+        let mut results = vec![Val::Bool(false); ty.results().len()];
+        func.call_async(&mut *store, &params, &mut results)
+            .await
+            .map_err(to_call_error)?;
+        func.post_return_async(&mut *store)
+            .await
+            .map_err(to_call_error)?;
+
+        lift_results(&ty, results)
+    }
+}
+
+impl Runtime for ComponentRuntime {
+    type Plugin = ComponentPlugin;
+
+    fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        #[cfg(feature = "async")]
+        if self.async_support {
+            return futures::executor::block_on(self.load_on_async_engine(bytes, context));
+        }
+        self.load_sync(bytes, context)
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        #[cfg(feature = "async")]
+        if self.async_support {
+            return futures::executor::block_on(self.call_on_async_engine(
+                plugin,
+                function_name,
+                args,
+            ));
+        }
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncRuntime for ComponentRuntime {
+    type Plugin = ComponentPlugin;
+
+    async fn load_async(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        if self.async_support {
+            self.load_on_async_engine(bytes, context).await
+        } else {
+            self.load_sync(bytes, context)
+        }
+    }
+
+    async fn call_async(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        if self.async_support {
+            self.call_on_async_engine(plugin, function_name, args).await
+        } else {
+            self.call_sync(plugin, function_name, args)
+        }
+    }
+}
+
+/// Defines the imported function `function` of `interface`, or of the component itself,
+/// forwarding to the matching host function. On an engine with async support, async host
+/// functions are awaited.
+///
+/// Host function errors trap, unless the import returns `result<T, string>`. Imports whose
+/// WIT signature does not match the signature of their host function are rejected.
+fn define_host_function(
+    linker: &mut LinkerInstance<'_, ComponentState>,
+    context: &HostContext,
+    interface: Option<&str>,
+    function: &str,
+    ty: &ComponentFunc,
+    async_support: bool,
+) -> PluginResult<()> {
+    let import = match interface {
+        Some(interface) => format!("{interface}#{function}"),
+        None => function.to_string(),
+    };
+    let name = [
+        import.clone(),
+        function.to_string(),
+        function.replace('-', "_"),
+    ]
+    .into_iter()
+    .find(|name| context.has_function(name))
+    .ok_or_else(|| {
+        PluginError::LoadError(format!(
+            "component imports `{import}`, but no such host function is registered"
+        ))
+    })?;
+    if let Some(signature) = context.signature(&name)
+        && !wit::matches_signature(ty, signature)
+    {
+        return Err(PluginError::LoadError(format!(
+            "component imports `{import}` as `{}`, but host function `{name}` has signature `{signature}`",
+            wit::signature(ty)
+        )));
+    }
+
+    #[cfg(feature = "async")]
+    if async_support {
+        return linker
+            .func_new_async(
+                function,
+                move |store: StoreContextMut<'_, ComponentState>, ty, params, results| {
+                    let name = name.clone();
+                    Box::new(async move {
+                        let args = lift_params(&ty, params)?;
+                        let result = store.data().context.call_function_async(&name, &args).await;
+                        store_result(&name, &ty, result, results)
+                    })
+                },
+            )
+            .map_err(load_error);
+    }
+    #[cfg(not(feature = "async"))]
+    let _ = async_support;
+
+    linker
+        .func_new(
+            function,
+            move |store: StoreContextMut<'_, ComponentState>, ty, params, results| {
+                let args = lift_params(&ty, params)?;
+                let result = store.data().context.call_function(&name, &args);
+                store_result(&name, &ty, result, results)
+            },
+        )
+        .map_err(load_error)
+}
+
+/// Converts the arguments of an import call into plugin values.
+fn lift_params(ty: &ComponentFunc, params: &[Val]) -> PluginResult<Vec<Value>> {
+    params
+        .iter()
+        .zip(ty.params())
+        .map(|(param, (_, param_ty))| wit::from_val(param.clone(), &param_ty))
+        .collect()
+}
+
+/// Stores the result of a host function in the import's result slot.
+fn store_result(
+    name: &str,
+    ty: &ComponentFunc,
+    result: PluginResult<Value>,
+    results: &mut [Val],
+) -> wasmtime::Result<()> {
+    let (Some(result_ty), Some(slot)) = (ty.results().next(), results.first_mut()) else {
+        return result.map(drop).map_err(Into::into);
+    };
+    let mismatch = |e: wit::TypeMismatch| {
+        wasmtime::Error::msg(format!(
+            "host function `{name}` returned a value that does not match WIT signature `{}`: {e}",
+            wit::signature(ty)
+        ))
+    };
+    *slot = match (&result_ty, result) {
+        (Type::Result(result_ty), result) if matches!(result_ty.err(), Some(Type::String)) => {
+            Val::Result(match (result, result_ty.ok()) {
+                (Ok(_), None) => Ok(None),
+                (Ok(value), Some(ok_ty)) => Ok(Some(Box::new(
+                    wit::to_val(&value, &ok_ty).map_err(mismatch)?,
+                ))),
+                (Err(error), _) => Err(Some(Box::new(Val::String(error.to_string())))),
+            })
+        }
+        (_, Ok(value)) => wit::to_val(&value, &result_ty).map_err(mismatch)?,
+        (_, Err(error)) => return Err(error.into()),
+    };
+    Ok(())
+}
+
+fn load_error(error: wasmtime::Error) -> PluginError {
+    PluginError::LoadError(format!("{error:#}"))
+}
+
+fn call_error(function_name: &str, error: wasmtime::Error) -> PluginError {
+    PluginError::CallError {
+        function: function_name.to_string(),
+        message: format!("{error:#}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the type of a function `f` imported by a component, e.g. `(param "a" s32)`.
+    fn func(func: &str) -> ComponentFunc {
+        let engine = Engine::default();
+        let component =
+            Component::new(&engine, format!("(component (import \"f\" (func {func})))")).unwrap();
+        component
+            .component_type()
+            .imports(&engine)
+            .find_map(|(_, item)| match item {
+                ComponentItem::ComponentFunc(func) => Some(func),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn arguments_are_checked_against_the_wit_signature() {
+        let add = func(r#"(param "a" s32) (param "b" s32) (result s32)"#);
+        assert_eq!(
+            lower_args("add", &add, &[Value::Int(1), Value::Int(2)]).unwrap(),
+            [Val::S32(1), Val::S32(2)]
+        );
+
+        let Err(PluginError::CallError { message, .. }) = lower_args("add", &add, &[Value::Int(1)])
+        else {
+            panic!("expected a call error");
+        };
+        assert_eq!(
+            message,
+            "expected 2 arguments, got 1 (WIT signature `func(a: s32, b: s32) -> s32`)"
+        );
+
+        let Err(PluginError::CallError { message, .. }) =
+            lower_args("add", &add, &[Value::from("five"), Value::Int(2)])
+        else {
+            panic!("expected a call error");
+        };
+        assert!(message.starts_with("argument `a` does not match: expected s32, found string"));
+    }
+
+    #[test]
+    fn host_function_errors_become_err_payloads_of_fallible_imports() {
+        let div = func(r#"(param "a" s32) (param "b" s32) (result (result s32 (error string)))"#);
+        let mut results = [Val::Bool(false)];
+
+        store_result("div", &div, Ok(Value::Int(3)), &mut results).unwrap();
+        assert_eq!(results[0], Val::Result(Ok(Some(Box::new(Val::S32(3))))));
+
+        store_result(
+            "div",
+            &div,
+            Err(PluginError::HostError("division by zero".into())),
+            &mut results,
+        )
+        .unwrap();
+        let Val::Result(Err(Some(payload))) = &results[0] else {
+            panic!("expected an err payload, got {:?}", results[0]);
+        };
+        assert!(matches!(&**payload, Val::String(message) if message.contains("division by zero")));
+    }
+
+    #[test]
+    fn host_function_errors_trap_infallible_imports() {
+        let add = func(r#"(param "a" s32) (param "b" s32) (result s32)"#);
+        let mut results = [Val::S32(0)];
+        assert!(
+            store_result(
+                "add",
+                &add,
+                Err(PluginError::HostError("failed".into())),
+                &mut results
+            )
+            .is_err()
+        );
+        assert!(store_result("add", &add, Ok(Value::from("three")), &mut results).is_err());
+    }
+
+    #[test]
+    fn results_are_lifted_into_a_single_value() {
+        let ty = func("(result s32)");
+        assert_eq!(lift_results(&ty, vec![Val::S32(4)]).unwrap(), Value::Int(4));
+        assert_eq!(lift_results(&func(""), Vec::new()).unwrap(), Value::Null);
+    }
+}
@@ -0,0 +1,46 @@
+//! Wasmtime-backed WebAssembly runtime for the tosic-plugin system.
+//!
+//! This crate implements the [`Runtime`](tosic_plugin_core::Runtime) trait on top of
+//! [Wasmtime](https://wasmtime.dev), loading core WebAssembly modules and exposing every
+//! function registered in a [`HostContext`](tosic_plugin_core::HostContext) as an import.
+//! Plugins exchange [`Value`](tosic_plugin_core::Value)s with the host through linear
+//! memory, following the contract described in the [`abi`] module.
+//!
+//! # Features
+//!
+//! - **async**: Also implement `AsyncRuntime`, and add runtimes running WebAssembly on Wasmtime's async support
+//! - **component-model**: Add [`ComponentRuntime`], which loads WebAssembly components and maps WIT types to and from `Value`s as described in the [`wit`] module
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # fn main() -> tosic_plugin_core::PluginResult<()> {
+//! use tosic_plugin_core::*;
+//! use tosic_plugin_wasmtime::WasmtimeRuntime;
+//!
+//! let mut context = HostContext::new();
+//! context.register("add", |a: i64, b: i64| a + b);
+//!
+//! let runtime = WasmtimeRuntime::new()?;
+//! let plugin = runtime.load(&std::fs::read("plugin.wasm").unwrap(), &context)?;
+//! let result = runtime.call(&plugin, "run", &[Value::Int(1)])?;
+//! # let _ = result;
+//! # Ok(())
+//! # }
+//! ```
+
+// Strict linting for release builds
+#![cfg_attr(not(debug_assertions), deny(missing_docs))]
+#![cfg_attr(not(debug_assertions), deny(clippy::all))]
+#![cfg_attr(not(debug_assertions), deny(unused))]
+
+#[cfg(feature = "component-model")]
+mod component;
+mod runtime;
+#[cfg(feature = "component-model")]
+pub mod wit;
+
+#[cfg(feature = "component-model")]
+pub use component::*;
+pub use runtime::*;
+pub use tosic_plugin_wasm_abi as abi;
@@ -0,0 +1,437 @@
+//! Wasmtime implementation of the [`Runtime`] and [`Plugin`] traits.
+
+use futures::lock::Mutex;
+#[cfg(feature = "async")]
+use tosic_plugin_core::AsyncRuntime;
+use tosic_plugin_core::{HostContext, Plugin, PluginError, PluginResult, Runtime, Value};
+use wasmtime::{
+    AsContext, AsContextMut, Caller, Config, Engine, Extern, Instance, Linker, Memory, Module,
+    Store, TypedFunc,
+};
+
+use crate::abi;
+
+/// Signature of plugin functions and host imports: `(ptr, len) -> packed (ptr, len)`.
+type AbiFunc = TypedFunc<(i32, i32), i64>;
+
+/// Per-store data made available to host function imports.
+struct HostState {
+    context: HostContext,
+}
+
+/// Runtime that compiles and instantiates core WebAssembly modules with Wasmtime.
+///
+/// See the [`abi`] module for the contract a module must follow to be loaded.
+///
+/// A runtime created with [`new_async`](Self::new_async) or
+/// [`with_async_config`](Self::with_async_config) runs WebAssembly on Wasmtime's async
+/// support, which lets calls yield to the executor. Either kind of runtime implements both
+/// [`Runtime`] and, with the `async` feature, `AsyncRuntime`: sync calls block on an async
+/// runtime's futures, and async calls on a sync runtime run to completion within the
+/// returned future.
+#[derive(Clone)]
+pub struct WasmtimeRuntime {
+    engine: Engine,
+    async_support: bool,
+}
+
+impl WasmtimeRuntime {
+    /// Creates a runtime with the default Wasmtime configuration.
+    ///
+    /// # Errors
+    /// Returns `PluginError::RuntimeError` if the engine cannot be created.
+    pub fn new() -> PluginResult<Self> {
+        Self::with_config(Config::new())
+    }
+
+    /// Creates a runtime from a custom Wasmtime configuration.
+    ///
+    /// # Errors
+    /// Returns `PluginError::RuntimeError` if the engine cannot be created.
+    pub fn with_config(mut config: Config) -> PluginResult<Self> {
+        #[cfg(feature = "async")]
+        config.async_support(false);
+        #[cfg(not(feature = "async"))]
+        let _ = &mut config;
+
+        Self::from_config(&config, false)
+    }
+
+    /// Creates a runtime with the default Wasmtime configuration and async support.
+    ///
+    /// # Errors
+    /// Returns `PluginError::RuntimeError` if the engine cannot be created.
+    #[cfg(feature = "async")]
+    pub fn new_async() -> PluginResult<Self> {
+        Self::with_async_config(Config::new())
+    }
+
+    /// Creates a runtime from a custom Wasmtime configuration, turning on async support.
+    ///
+    /// # Errors
+    /// Returns `PluginError::RuntimeError` if the engine cannot be created.
+    #[cfg(feature = "async")]
+    pub fn with_async_config(mut config: Config) -> PluginResult<Self> {
+        config.async_support(true);
+        Self::from_config(&config, true)
+    }
+
+    fn from_config(config: &Config, async_support: bool) -> PluginResult<Self> {
+        let engine =
+            Engine::new(config).map_err(|e| PluginError::RuntimeError(format!("{e:#}")))?;
+        Ok(Self {
+            engine,
+            async_support,
+        })
+    }
+
+    /// Returns the underlying Wasmtime engine.
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// Returns true if the runtime runs WebAssembly on Wasmtime's async support.
+    pub fn is_async(&self) -> bool {
+        self.async_support
+    }
+
+    /// Compiles the module and links every host function in the context.
+    fn prepare(
+        &self,
+        bytes: &[u8],
+        context: &HostContext,
+    ) -> PluginResult<(Module, Linker<HostState>, Store<HostState>)> {
+        let module = Module::new(&self.engine, bytes).map_err(load_error)?;
+
+        let mut linker = Linker::new(&self.engine);
+        for name in context.function_names() {
+            #[cfg(feature = "async")]
+            if self.async_support {
+                define_async_host_function(&mut linker, name.clone())?;
+                continue;
+            }
+            define_host_function(&mut linker, name.clone())?;
+        }
+
+        let store = Store::new(
+            &self.engine,
+            HostState {
+                context: context.clone(),
+            },
+        );
+        Ok((module, linker, store))
+    }
+}
+
+/// A WebAssembly module instantiated by a [`WasmtimeRuntime`].
+pub struct WasmtimePlugin {
+    name: Option<String>,
+    instance: Instance,
+    exports: GuestExports,
+    store: Mutex<Store<HostState>>,
+}
+
+impl Plugin for WasmtimePlugin {
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl WasmtimePlugin {
+    fn new(module: &Module, instance: Instance, mut store: Store<HostState>) -> PluginResult<Self> {
+        let exports = GuestExports::resolve(&mut store, &instance)?;
+        Ok(Self {
+            name: module.name().map(str::to_string),
+            instance,
+            exports,
+            store: Mutex::new(store),
+        })
+    }
+
+    fn function(&self, store: &mut Store<HostState>, function_name: &str) -> PluginResult<AbiFunc> {
+        let func = self
+            .instance
+            .get_func(&mut *store, function_name)
+            .ok_or_else(|| PluginError::FunctionNotFound(function_name.to_string()))?;
+        func.typed(&*store)
+            .map_err(|e| call_error(function_name, e))
+    }
+}
+
+/// Exports every loadable module must provide.
+struct GuestExports {
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    dealloc: Option<TypedFunc<(i32, i32), ()>>,
+}
+
+impl GuestExports {
+    fn resolve(store: &mut Store<HostState>, instance: &Instance) -> PluginResult<Self> {
+        let memory = instance
+            .get_memory(&mut *store, abi::MEMORY_EXPORT)
+            .ok_or_else(|| {
+                PluginError::LoadError(format!("module does not export `{}`", abi::MEMORY_EXPORT))
+            })?;
+        let alloc = instance
+            .get_typed_func(&mut *store, abi::ALLOC_EXPORT)
+            .map_err(load_error)?;
+        let dealloc = match instance.get_func(&mut *store, abi::DEALLOC_EXPORT) {
+            Some(func) => Some(func.typed(&*store).map_err(load_error)?),
+            None => None,
+        };
+        Ok(Self {
+            memory,
+            alloc,
+            dealloc,
+        })
+    }
+}
+
+impl WasmtimeRuntime {
+    fn load_sync(&self, bytes: &[u8], context: &HostContext) -> PluginResult<WasmtimePlugin> {
+        let (module, linker, mut store) = self.prepare(bytes, context)?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(load_error)?;
+        WasmtimePlugin::new(&module, instance, store)
+    }
+
+    fn call_sync(
+        &self,
+        plugin: &WasmtimePlugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        let mut store = futures::executor::block_on(plugin.store.lock());
+        let func = plugin.function(&mut store, function_name)?;
+        let exports = &plugin.exports;
+        let to_call_error = |e| call_error(function_name, e);
+
+        let input = abi::encode_args(args);
+        let ptr = exports
+            .alloc
+            .call(&mut *store, abi::buffer_len(input.len())?)
+            .map_err(to_call_error)?;
+        write_guest(&mut *store, exports.memory, ptr, &input)?;
+
+        let packed = func
+            .call(&mut *store, (ptr, abi::buffer_len(input.len())?))
+            .map_err(to_call_error)?;
+        let (ptr, len) = abi::unpack(packed);
+        let output = read_guest(&*store, exports.memory, ptr, len)?;
+        if let Some(dealloc) = &exports.dealloc {
+            dealloc
+                .call(&mut *store, (ptr as i32, len as i32))
+                .map_err(to_call_error)?;
+        }
+
+        abi::call_result(function_name, &output)
+    }
+
+    #[cfg(feature = "async")]
+    async fn load_on_async_engine(
+        &self,
+        bytes: &[u8],
+        context: &HostContext,
+    ) -> PluginResult<WasmtimePlugin> {
+        let (module, linker, mut store) = self.prepare(bytes, context)?;
+        let instance = linker
+            .instantiate_async(&mut store, &module)
+            .await
+            .map_err(load_error)?;
+        WasmtimePlugin::new(&module, instance, store)
+    }
+
+    #[cfg(feature = "async")]
+    async fn call_on_async_engine(
+        &self,
+        plugin: &WasmtimePlugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        let mut store = plugin.store.lock().await;
+        let func = plugin.function(&mut store, function_name)?;
+        let exports = &plugin.exports;
+        let to_call_error = |e| call_error(function_name, e);
+
+        let input = abi::encode_args(args);
+        let ptr = exports
+            .alloc
+            .call_async(&mut *store, abi::buffer_len(input.len())?)
+            .await
+            .map_err(to_call_error)?;
+        write_guest(&mut *store, exports.memory, ptr, &input)?;
+
+        let packed = func
+            .call_async(&mut *store, (ptr, abi::buffer_len(input.len())?))
+            .await
+            .map_err(to_call_error)?;
+        let (ptr, len) = abi::unpack(packed);
+        let output = read_guest(&*store, exports.memory, ptr, len)?;
+        if let Some(dealloc) = &exports.dealloc {
+            dealloc
+                .call_async(&mut *store, (ptr as i32, len as i32))
+                .await
+                .map_err(to_call_error)?;
+        }
+
+        abi::call_result(function_name, &output)
+    }
+}
+
+impl Runtime for WasmtimeRuntime {
+    type Plugin = WasmtimePlugin;
+
+    fn load(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        #[cfg(feature = "async")]
+        if self.async_support {
+            return futures::executor::block_on(self.load_on_async_engine(bytes, context));
+        }
+        self.load_sync(bytes, context)
+    }
+
+    fn call(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        #[cfg(feature = "async")]
+        if self.async_support {
+            return futures::executor::block_on(self.call_on_async_engine(
+                plugin,
+                function_name,
+                args,
+            ));
+        }
+        self.call_sync(plugin, function_name, args)
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncRuntime for WasmtimeRuntime {
+    type Plugin = WasmtimePlugin;
+
+    async fn load_async(&self, bytes: &[u8], context: &HostContext) -> PluginResult<Self::Plugin> {
+        if self.async_support {
+            self.load_on_async_engine(bytes, context).await
+        } else {
+            self.load_sync(bytes, context)
+        }
+    }
+
+    async fn call_async(
+        &self,
+        plugin: &Self::Plugin,
+        function_name: &str,
+        args: &[Value],
+    ) -> PluginResult<Value> {
+        if self.async_support {
+            self.call_on_async_engine(plugin, function_name, args).await
+        } else {
+            self.call_sync(plugin, function_name, args)
+        }
+    }
+}
+
+/// Defines an import in [`abi::HOST_MODULE`] that forwards to the named host function.
+fn define_host_function(linker: &mut Linker<HostState>, name: String) -> PluginResult<()> {
+    linker
+        .func_wrap(
+            abi::HOST_MODULE,
+            &name.clone(),
+            move |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                let memory = caller_memory(&mut caller)?;
+                let input = read_guest(&caller, memory, ptr as u32, len as u32)?;
+                let output = abi::call_host_function(&caller.data().context, &name, &input);
+
+                let ptr =
+                    caller_alloc(&mut caller)?.call(&mut caller, abi::buffer_len(output.len())?)?;
+                write_guest(&mut caller, memory, ptr, &output)?;
+                Ok(abi::pack(ptr as u32, output.len() as u32))
+            },
+        )
+        .map_err(load_error)?;
+    Ok(())
+}
+
+/// Defines an import in [`abi::HOST_MODULE`] that forwards to the named host function on
+/// an engine with async support, awaiting async host functions.
+#[cfg(feature = "async")]
+fn define_async_host_function(linker: &mut Linker<HostState>, name: String) -> PluginResult<()> {
+    linker
+        .func_wrap_async(
+            abi::HOST_MODULE,
+            &name.clone(),
+            move |mut caller: Caller<'_, HostState>, (ptr, len): (i32, i32)| {
+                let name = name.clone();
+                Box::new(async move {
+                    let memory = caller_memory(&mut caller)?;
+                    let input = read_guest(&caller, memory, ptr as u32, len as u32)?;
+                    let result = match abi::decode_args(&input) {
+                        Ok(args) => {
+                            caller
+                                .data()
+                                .context
+                                .call_function_async(&name, &args)
+                                .await
+                        }
+                        Err(error) => Err(error),
+                    };
+                    let output = abi::encode_result(&result);
+
+                    let ptr = caller_alloc(&mut caller)?
+                        .call_async(&mut caller, abi::buffer_len(output.len())?)
+                        .await?;
+                    write_guest(&mut caller, memory, ptr, &output)?;
+                    Ok(abi::pack(ptr as u32, output.len() as u32))
+                })
+            },
+        )
+        .map_err(load_error)?;
+    Ok(())
+}
+
+fn caller_memory(caller: &mut Caller<'_, HostState>) -> PluginResult<Memory> {
+    caller
+        .get_export(abi::MEMORY_EXPORT)
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| {
+            PluginError::RuntimeError(format!("module does not export `{}`", abi::MEMORY_EXPORT))
+        })
+}
+
+fn caller_alloc(caller: &mut Caller<'_, HostState>) -> wasmtime::Result<TypedFunc<i32, i32>> {
+    caller
+        .get_export(abi::ALLOC_EXPORT)
+        .and_then(Extern::into_func)
+        .ok_or_else(|| {
+            wasmtime::Error::msg(format!("module does not export `{}`", abi::ALLOC_EXPORT))
+        })?
+        .typed(&*caller)
+}
+
+fn read_guest(store: impl AsContext, memory: Memory, ptr: u32, len: u32) -> PluginResult<Vec<u8>> {
+    abi::read_buffer(memory.data(&store), ptr, len).map(<[u8]>::to_vec)
+}
+
+fn write_guest(
+    mut store: impl AsContextMut,
+    memory: Memory,
+    ptr: i32,
+    bytes: &[u8],
+) -> PluginResult<()> {
+    abi::write_buffer(memory.data_mut(&mut store), ptr as u32, bytes)
+}
+
+fn load_error(error: wasmtime::Error) -> PluginError {
+    PluginError::LoadError(format!("{error:#}"))
+}
+
+fn call_error(function_name: &str, error: wasmtime::Error) -> PluginError {
+    PluginError::CallError {
+        function: function_name.to_string(),
+        message: format!("{error:#}"),
+    }
+}
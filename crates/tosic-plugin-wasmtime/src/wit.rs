@@ -0,0 +1,732 @@
+//! Conversions between plugin [`Value`]s and component model values, driven by WIT types.
+//!
+//! | WIT                          | `Value`                                              |
+//! |------------------------------|------------------------------------------------------|
+//! | `bool`                       | `Bool`                                               |
+//! | `s8` … `s64`, `u8` … `u64`   | `Int`, range-checked                                 |
+//! | `f32`, `f64`                 | `Float`, range-checked (an exact `Int` as well)      |
+//! | `char`                       | one-character `String`                               |
+//! | `string`                     | `String`                                             |
+//! | `list<u8>`                   | `Bytes` (an `Array` of ints is accepted as well)     |
+//! | `list<T>`, `tuple<…>`        | `Array`                                              |
+//! | `record`                     | `Object` keyed by field name, in declaration order   |
+//! | `option<T>`                  | `Null` for `none`, the payload itself for `some`     |
+//! | `option<option<T>>`          | `Null` for `none`, `{"some": payload}` for `some`    |
+//! | `result<T, E>`               | `{"ok": payload}` or `{"err": payload}`              |
+//! | `variant`                    | `{"case": payload}`, or `"case"` without a payload   |
+//! | `enum`                       | `String` naming the case                             |
+//! | `flags`                      | `Array` of the set flag names                        |
+//!
+//! Missing payloads are `Null`, and a record field of type `option<T>` may be omitted. An
+//! option nested in another one wraps the payload of the outer `some`, which would be
+//! indistinguishable from `none` otherwise.
+//! Resources, futures, and streams have no `Value` counterpart.
+
+use std::fmt;
+
+use tosic_plugin_core::{Map, PluginError, PluginResult, Signature, Value, ValueKind};
+use wasmtime::component::types::ComponentFunc;
+use wasmtime::component::{Type, Val};
+
+/// A [`Value`] that does not match the WIT type it is converted to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeMismatch {
+    /// Where in the value the mismatch is, such as `.point.x` or `[2]`; empty at the top level.
+    pub path: String,
+    /// The expected WIT type.
+    pub expected: String,
+    /// What was found instead.
+    pub found: String,
+}
+
+impl TypeMismatch {
+    fn new(expected: impl Into<String>, found: impl Into<String>) -> Self {
+        Self {
+            path: String::new(),
+            expected: expected.into(),
+            found: found.into(),
+        }
+    }
+
+    fn within(mut self, segment: impl fmt::Display) -> Self {
+        self.path = format!("{segment}{}", self.path);
+        self
+    }
+}
+
+impl fmt::Display for TypeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {}, found {}", self.expected, self.found)?;
+        if !self.path.is_empty() {
+            write!(f, " at `{}`", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TypeMismatch {}
+
+/// Converts a plugin value into a component value of type `ty`.
+///
+/// # Errors
+/// Returns a [`TypeMismatch`] if the value does not have the shape `ty` requires.
+pub fn to_val(value: &Value, ty: &Type) -> Result<Val, TypeMismatch> {
+    let mismatch = || TypeMismatch::new(type_name(ty), describe(value));
+
+    Ok(match (ty, value) {
+        (Type::Bool, Value::Bool(b)) => Val::Bool(*b),
+        (Type::S8, Value::Int(i)) => Val::S8(int(ty, *i)?),
+        (Type::U8, Value::Int(i)) => Val::U8(int(ty, *i)?),
+        (Type::S16, Value::Int(i)) => Val::S16(int(ty, *i)?),
+        (Type::U16, Value::Int(i)) => Val::U16(int(ty, *i)?),
+        (Type::S32, Value::Int(i)) => Val::S32(int(ty, *i)?),
+        (Type::U32, Value::Int(i)) => Val::U32(int(ty, *i)?),
+        (Type::S64, Value::Int(i)) => Val::S64(*i),
+        (Type::U64, Value::Int(i)) => Val::U64(int(ty, *i)?),
+        (Type::Float32, Value::Float(f)) if f.is_finite() && (*f as f32).is_infinite() => {
+            return Err(TypeMismatch::new(
+                type_name(ty),
+                format!("{f}, which is out of range"),
+            ));
+        }
+        (Type::Float32, Value::Float(f)) => Val::Float32(*f as f32),
+        (Type::Float32, Value::Int(i)) => Val::Float32(exact_float(ty, *i, *i as f32)?),
+        (Type::Float64, Value::Float(f)) => Val::Float64(*f),
+        (Type::Float64, Value::Int(i)) => Val::Float64(exact_float(ty, *i, *i as f64)?),
+        (Type::Char, Value::String(s)) => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Val::Char(c),
+                _ => return Err(mismatch()),
+            }
+        }
+        (Type::String, Value::String(s)) => Val::String(s.clone()),
+        (Type::List(list), Value::Bytes(bytes)) if matches!(list.ty(), Type::U8) => {
+            Val::List(bytes.iter().copied().map(Val::U8).collect())
+        }
+        (Type::List(list), Value::Array(items)) => {
+            let item_ty = list.ty();
+            Val::List(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        to_val(item, &item_ty).map_err(|e| e.within(format!("[{index}]")))
+                    })
+                    .collect::<Result<_, _>>()?,
+            )
+        }
+        (Type::Tuple(tuple), Value::Array(items)) => {
+            if items.len() != tuple.types().len() {
+                return Err(mismatch());
+            }
+            Val::Tuple(
+                tuple
+                    .types()
+                    .zip(items)
+                    .enumerate()
+                    .map(|(index, (item_ty, item))| {
+                        to_val(item, &item_ty).map_err(|e| e.within(format!("[{index}]")))
+                    })
+                    .collect::<Result<_, _>>()?,
+            )
+        }
+        (Type::Record(record), Value::Object(map)) => {
+            if let Some(key) = map
+                .keys()
+                .find(|key| !record.fields().any(|field| field.name == key.as_str()))
+            {
+                return Err(TypeMismatch::new("no such field", describe(&map[key]))
+                    .within(format!(".{key}")));
+            }
+            Val::Record(
+                record
+                    .fields()
+                    .map(|field| {
+                        let val = match (map.get(field.name), &field.ty) {
+                            (Some(item), _) => to_val(item, &field.ty),
+                            (None, Type::Option(_)) => Ok(Val::Option(None)),
+                            (None, _) => Err(TypeMismatch::new(type_name(&field.ty), "nothing")),
+                        };
+                        Ok((
+                            field.name.to_string(),
+                            val.map_err(|e| e.within(format!(".{}", field.name)))?,
+                        ))
+                    })
+                    .collect::<Result<_, TypeMismatch>>()?,
+            )
+        }
+        (Type::Variant(variant), Value::String(name)) => {
+            match variant.cases().find(|case| case.name == name.as_str()) {
+                Some(case) if case.ty.is_none() => Val::Variant(name.clone(), None),
+                _ => return Err(mismatch()),
+            }
+        }
+        (Type::Variant(variant), Value::Object(map)) if map.len() == 1 => {
+            let (name, payload) = map.iter().next().expect("map has one entry");
+            let case = variant
+                .cases()
+                .find(|case| case.name == name.as_str())
+                .ok_or_else(mismatch)?;
+            let payload = payload_to_val(payload, case.ty.as_ref())
+                .map_err(|e| e.within(format!(".{name}")))?;
+            Val::Variant(name.clone(), payload)
+        }
+        (Type::Enum(enum_ty), Value::String(name))
+            if enum_ty.names().any(|case| case == name.as_str()) =>
+        {
+            Val::Enum(name.clone())
+        }
+        (Type::Option(_), Value::Null) => Val::Option(None),
+        (Type::Option(option), Value::Object(map)) if is_option(&option.ty()) => {
+            match map.get("some") {
+                Some(payload) if map.len() == 1 => Val::Option(Some(Box::new(
+                    to_val(payload, &option.ty()).map_err(|e| e.within(".some"))?,
+                ))),
+                _ => return Err(mismatch()),
+            }
+        }
+        (Type::Option(option), _) if is_option(&option.ty()) => return Err(mismatch()),
+        (Type::Option(option), value) => Val::Option(Some(Box::new(to_val(value, &option.ty())?))),
+        (Type::Result(result), Value::Object(map)) if map.len() == 1 => {
+            let (name, payload) = map.iter().next().expect("map has one entry");
+            match name.as_str() {
+                "ok" => {
+                    Val::Result(Ok(payload_to_val(payload, result.ok().as_ref())
+                        .map_err(|e| e.within(".ok"))?))
+                }
+                "err" => {
+                    Val::Result(Err(payload_to_val(payload, result.err().as_ref())
+                        .map_err(|e| e.within(".err"))?))
+                }
+                _ => return Err(mismatch()),
+            }
+        }
+        (Type::Flags(flags), Value::Array(items)) => Val::Flags(
+            items
+                .iter()
+                .map(|item| match item {
+                    Value::String(name) if flags.names().any(|flag| flag == name.as_str()) => {
+                        Ok(name.clone())
+                    }
+                    _ => Err(mismatch()),
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        _ => return Err(mismatch()),
+    })
+}
+
+/// Converts a component value of type `ty` into a plugin value.
+///
+/// # Errors
+/// Returns `PluginError::RuntimeError` for values without a `Value` counterpart, such as
+/// resources or `u64`s above `i64::MAX`.
+pub fn from_val(val: Val, ty: &Type) -> PluginResult<Value> {
+    Ok(match (val, ty) {
+        (Val::Bool(b), _) => Value::Bool(b),
+        (Val::S8(i), _) => Value::Int(i.into()),
+        (Val::U8(i), _) => Value::Int(i.into()),
+        (Val::S16(i), _) => Value::Int(i.into()),
+        (Val::U16(i), _) => Value::Int(i.into()),
+        (Val::S32(i), _) => Value::Int(i.into()),
+        (Val::U32(i), _) => Value::Int(i.into()),
+        (Val::S64(i), _) => Value::Int(i),
+        (Val::U64(i), _) => Value::Int(i64::try_from(i).map_err(|_| {
+            PluginError::RuntimeError(format!("u64 {i} does not fit in a plugin integer"))
+        })?),
+        (Val::Float32(f), _) => Value::Float(f.into()),
+        (Val::Float64(f), _) => Value::Float(f),
+        (Val::Char(c), _) => Value::String(c.to_string()),
+        (Val::String(s), _) => Value::String(s),
+        (Val::List(items), Type::List(list)) if matches!(list.ty(), Type::U8) => Value::Bytes(
+            items
+                .into_iter()
+                .map(|item| match item {
+                    Val::U8(byte) => Ok(byte),
+                    other => Err(unexpected(&other, ty)),
+                })
+                .collect::<PluginResult<_>>()?,
+        ),
+        (Val::List(items), Type::List(list)) => {
+            let item_ty = list.ty();
+            Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| from_val(item, &item_ty))
+                    .collect::<PluginResult<_>>()?,
+            )
+        }
+        (Val::Tuple(items), Type::Tuple(tuple)) => Value::Array(
+            items
+                .into_iter()
+                .zip(tuple.types())
+                .map(|(item, item_ty)| from_val(item, &item_ty))
+                .collect::<PluginResult<_>>()?,
+        ),
+        (Val::Record(fields), Type::Record(record)) => Value::Object(
+            fields
+                .into_iter()
+                .zip(record.fields())
+                .map(|((name, item), field)| Ok((name, from_val(item, &field.ty)?)))
+                .collect::<PluginResult<Map>>()?,
+        ),
+        (Val::Variant(name, payload), Type::Variant(variant)) => {
+            let case_ty = variant
+                .cases()
+                .find(|case| case.name == name)
+                .and_then(|case| case.ty);
+            match (payload, case_ty) {
+                (Some(payload), Some(case_ty)) => {
+                    Value::Object(Map::from([(name, from_val(*payload, &case_ty)?)]))
+                }
+                _ => Value::String(name),
+            }
+        }
+        (Val::Enum(name), _) => Value::String(name),
+        (Val::Option(None), _) => Value::Null,
+        (Val::Option(Some(payload)), Type::Option(option)) if is_option(&option.ty()) => {
+            Value::Object(Map::from([(
+                "some".to_string(),
+                from_val(*payload, &option.ty())?,
+            )]))
+        }
+        (Val::Option(Some(payload)), Type::Option(option)) => from_val(*payload, &option.ty())?,
+        (Val::Result(result), Type::Result(result_ty)) => {
+            let (key, payload, payload_ty) = match result {
+                Ok(payload) => ("ok", payload, result_ty.ok()),
+                Err(payload) => ("err", payload, result_ty.err()),
+            };
+            let payload = match (payload, payload_ty) {
+                (Some(payload), Some(payload_ty)) => from_val(*payload, &payload_ty)?,
+                _ => Value::Null,
+            };
+            Value::Object(Map::from([(key.to_string(), payload)]))
+        }
+        (Val::Flags(names), _) => Value::Array(names.into_iter().map(Value::String).collect()),
+        (other, ty) => return Err(unexpected(&other, ty)),
+    })
+}
+
+/// Renders a WIT type, e.g. `list<option<string>>`.
+pub fn type_name(ty: &Type) -> String {
+    let join = |items: Vec<String>| items.join(", ");
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::S8 => "s8".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::S16 => "s16".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::S32 => "s32".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::S64 => "s64".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::Float32 => "f32".to_string(),
+        Type::Float64 => "f64".to_string(),
+        Type::Char => "char".to_string(),
+        Type::String => "string".to_string(),
+        Type::List(list) => format!("list<{}>", type_name(&list.ty())),
+        Type::Tuple(tuple) => format!(
+            "tuple<{}>",
+            join(tuple.types().map(|ty| type_name(&ty)).collect())
+        ),
+        Type::Record(record) => format!(
+            "record {{ {} }}",
+            join(
+                record
+                    .fields()
+                    .map(|field| format!("{}: {}", field.name, type_name(&field.ty)))
+                    .collect()
+            )
+        ),
+        Type::Variant(variant) => format!(
+            "variant {{ {} }}",
+            join(
+                variant
+                    .cases()
+                    .map(|case| match &case.ty {
+                        Some(ty) => format!("{}({})", case.name, type_name(ty)),
+                        None => case.name.to_string(),
+                    })
+                    .collect()
+            )
+        ),
+        Type::Enum(enum_ty) => format!(
+            "enum {{ {} }}",
+            join(enum_ty.names().map(str::to_string).collect())
+        ),
+        Type::Option(option) => format!("option<{}>", type_name(&option.ty())),
+        Type::Result(result) => {
+            let payload =
+                |ty: Option<Type>| ty.map_or_else(|| "_".to_string(), |ty| type_name(&ty));
+            format!(
+                "result<{}, {}>",
+                payload(result.ok()),
+                payload(result.err())
+            )
+        }
+        Type::Flags(flags) => format!(
+            "flags {{ {} }}",
+            join(flags.names().map(str::to_string).collect())
+        ),
+        Type::Own(_) => "own<resource>".to_string(),
+        Type::Borrow(_) => "borrow<resource>".to_string(),
+        Type::Future(_) => "future".to_string(),
+        Type::Stream(_) => "stream".to_string(),
+        Type::ErrorContext => "error-context".to_string(),
+    }
+}
+
+/// Renders a WIT function signature, e.g. `func(a: s32, b: s32) -> s32`.
+pub fn signature(func: &ComponentFunc) -> String {
+    let params = func
+        .params()
+        .map(|(name, ty)| format!("{name}: {}", type_name(&ty)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    match func.results().next() {
+        Some(result) => format!("func({params}) -> {}", type_name(&result)),
+        None => format!("func({params})"),
+    }
+}
+
+/// Returns the kind of the values [`from_val`] converts values of type `ty` into.
+pub fn value_kind(ty: &Type) -> ValueKind {
+    match ty {
+        Type::Bool => ValueKind::Bool,
+        Type::S8
+        | Type::U8
+        | Type::S16
+        | Type::U16
+        | Type::S32
+        | Type::U32
+        | Type::S64
+        | Type::U64 => ValueKind::Int,
+        Type::Float32 | Type::Float64 => ValueKind::Float,
+        Type::Char | Type::String | Type::Enum(_) => ValueKind::String,
+        Type::List(list) if matches!(list.ty(), Type::U8) => ValueKind::Bytes,
+        Type::List(_) | Type::Tuple(_) | Type::Flags(_) => ValueKind::Array,
+        Type::Record(_) | Type::Result(_) => ValueKind::Object,
+        _ => ValueKind::Any,
+    }
+}
+
+/// Returns true if [`to_val`] may convert values of `kind` into values of type `ty`.
+pub fn accepts(ty: &Type, kind: ValueKind) -> bool {
+    match (ty, kind) {
+        (_, ValueKind::Any) => true,
+        (Type::Float32 | Type::Float64, ValueKind::Int) => true,
+        (Type::List(list), ValueKind::Array) if matches!(list.ty(), Type::U8) => true,
+        (Type::Option(_), ValueKind::Null) => true,
+        (Type::Option(option), kind) if is_option(&option.ty()) => kind == ValueKind::Object,
+        (Type::Option(option), kind) => accepts(&option.ty(), kind),
+        (Type::Variant(_), ValueKind::String | ValueKind::Object) => true,
+        (ty, kind) => value_kind(ty).matches(kind),
+    }
+}
+
+/// Returns true if a host function with the given signature can serve an import of type
+/// `func`: it takes as many arguments, leaving out only optional ones or taking extra ones
+/// when variadic, of matching kinds, and returns a value the import's result accepts. The
+/// payload of a `result<T, string>` is checked against `T`, since host function errors
+/// become its `err` case.
+pub fn matches_signature(func: &ComponentFunc, signature: &Signature) -> bool {
+    let params_match = signature.accepts_arity(func.params().len())
+        && func.params().enumerate().all(|(index, (_, ty))| {
+            signature
+                .param(index)
+                .is_some_and(|kind| kind.matches(value_kind(&ty)))
+        });
+    let returns_match = match func.results().next() {
+        None => true,
+        Some(Type::Result(result)) if matches!(result.err(), Some(Type::String)) => result
+            .ok()
+            .is_none_or(|ok| accepts(&ok, signature.returns())),
+        Some(ty) => accepts(&ty, signature.returns()),
+    };
+    params_match && returns_match
+}
+
+fn payload_to_val(payload: &Value, ty: Option<&Type>) -> Result<Option<Box<Val>>, TypeMismatch> {
+    match (ty, payload) {
+        (None, Value::Null) => Ok(None),
+        (None, other) => Err(TypeMismatch::new("no payload", describe(other))),
+        (Some(ty), payload) => to_val(payload, ty).map(|val| Some(Box::new(val))),
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    matches!(ty, Type::Option(_))
+}
+
+/// Checks that `float`, the integer `i` converted to a float type, represents it exactly.
+fn exact_float<F: Into<f64> + Copy>(ty: &Type, i: i64, float: F) -> Result<F, TypeMismatch> {
+    if i128::from(i) == float.into() as i128 {
+        Ok(float)
+    } else {
+        Err(TypeMismatch::new(
+            type_name(ty),
+            format!("{i}, which it cannot represent exactly"),
+        ))
+    }
+}
+
+fn int<T: TryFrom<i64>>(ty: &Type, i: i64) -> Result<T, TypeMismatch> {
+    T::try_from(i)
+        .map_err(|_| TypeMismatch::new(type_name(ty), format!("{i}, which is out of range")))
+}
+
+/// Describes a value in mismatch errors, e.g. `string` or `array of 2 items`.
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(_) => "bool".to_string(),
+        Value::Int(_) => "int".to_string(),
+        Value::Float(_) => "float".to_string(),
+        Value::String(_) => "string".to_string(),
+        Value::Bytes(_) => "bytes".to_string(),
+        Value::Array(items) => format!("array of {} items", items.len()),
+        Value::Object(_) => "object".to_string(),
+    }
+}
+
+fn unexpected(val: &Val, ty: &Type) -> PluginError {
+    PluginError::RuntimeError(format!(
+        "cannot convert WIT {} value {val:?} to a plugin value",
+        type_name(ty)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use wasmtime::Engine;
+    use wasmtime::component::Component;
+    use wasmtime::component::types::ComponentItem;
+
+    use super::*;
+
+    const TYPES: &str = r#"
+        (type $point' (record (field "x" s32) (field "y" s32)))
+        (import "point" (type $point (eq $point')))
+        (type $shape' (variant (case "circle" f64) (case "empty")))
+        (import "shape" (type $shape (eq $shape')))
+        (type $color' (enum "red" "green"))
+        (import "color" (type $color (eq $color')))
+        (type $perms' (flags "read" "write"))
+        (import "perms" (type $perms (eq $perms')))
+    "#;
+
+    /// Returns the type of a function `f` imported by a component, e.g. `(param "a" s32)`.
+    fn func(func: &str) -> ComponentFunc {
+        let engine = Engine::default();
+        let component = Component::new(
+            &engine,
+            format!("(component {TYPES} (import \"f\" (func {func})))"),
+        )
+        .unwrap();
+        component
+            .component_type()
+            .imports(&engine)
+            .find_map(|(_, item)| match item {
+                ComponentItem::ComponentFunc(func) => Some(func),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    /// Returns a WIT type, e.g. `(list u8)` or `$point`.
+    fn wit(ty: &str) -> Type {
+        func(&format!("(param \"value\" {ty})"))
+            .params()
+            .next()
+            .unwrap()
+            .1
+    }
+
+    fn round_trip(value: &Value, ty: &Type) -> Value {
+        from_val(to_val(value, ty).unwrap(), ty).unwrap()
+    }
+
+    fn object<const N: usize>(entries: [(&str, Value); N]) -> Value {
+        Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let cases = [
+            ("bool", Value::Bool(true)),
+            ("s8", Value::Int(-128)),
+            ("u64", Value::Int(i64::MAX)),
+            ("f32", Value::Float(1.5)),
+            ("f64", Value::Float(f64::INFINITY)),
+            ("char", Value::from("é")),
+            ("string", Value::from("hello")),
+            ("(list u8)", Value::Bytes(vec![0, 255])),
+            (
+                "(list string)",
+                Value::Array(vec![Value::from("a"), Value::from("b")]),
+            ),
+            (
+                "(tuple s32 string)",
+                Value::Array(vec![Value::Int(1), Value::from("a")]),
+            ),
+            (
+                "$point",
+                object([("x", Value::Int(1)), ("y", Value::Int(-2))]),
+            ),
+            ("$shape", object([("circle", Value::Float(2.5))])),
+            ("$shape", Value::from("empty")),
+            ("$color", Value::from("green")),
+            (
+                "$perms",
+                Value::Array(vec![Value::from("read"), Value::from("write")]),
+            ),
+            ("(option s32)", Value::Null),
+            ("(option s32)", Value::Int(7)),
+            (
+                "(result s32 (error string))",
+                object([("ok", Value::Int(7))]),
+            ),
+            (
+                "(result s32 (error string))",
+                object([("err", Value::from("failed"))]),
+            ),
+            ("(result)", object([("ok", Value::Null)])),
+        ];
+        for (ty, value) in cases {
+            assert_eq!(round_trip(&value, &wit(ty)), value, "{ty}");
+        }
+    }
+
+    #[test]
+    fn nested_options_keep_some_none_apart_from_none() {
+        let ty = wit("(option (option s32))");
+        for value in [
+            Value::Null,
+            object([("some", Value::Null)]),
+            object([("some", Value::Int(7))]),
+        ] {
+            assert_eq!(round_trip(&value, &ty), value);
+        }
+        assert_eq!(
+            to_val(&object([("some", Value::Null)]), &ty).unwrap(),
+            Val::Option(Some(Box::new(Val::Option(None))))
+        );
+
+        let error = to_val(&Value::Int(7), &ty).unwrap_err();
+        assert_eq!(error.expected, "option<option<s32>>");
+        assert!(!accepts(&ty, ValueKind::Int));
+        assert!(accepts(&ty, ValueKind::Object));
+    }
+
+    #[test]
+    fn byte_lists_accept_arrays_of_ints() {
+        let ty = wit("(list u8)");
+        assert_eq!(
+            round_trip(&Value::Array(vec![Value::Int(1), Value::Int(2)]), &ty),
+            Value::Bytes(vec![1, 2])
+        );
+        assert_eq!(
+            to_val(&Value::Array(vec![Value::Int(256)]), &ty)
+                .unwrap_err()
+                .path,
+            "[0]"
+        );
+    }
+
+    #[test]
+    fn floats_are_range_checked() {
+        let f32_ty = wit("f32");
+        assert!(
+            to_val(&Value::Float(1e40), &f32_ty)
+                .unwrap_err()
+                .found
+                .contains("out of range")
+        );
+        assert_eq!(
+            to_val(&Value::Float(f64::NEG_INFINITY), &f32_ty).unwrap(),
+            Val::Float32(f32::NEG_INFINITY)
+        );
+        assert_eq!(
+            to_val(&Value::Int(1 << 24), &f32_ty).unwrap(),
+            Val::Float32(16_777_216.0)
+        );
+        assert!(to_val(&Value::Int((1 << 24) + 1), &f32_ty).is_err());
+
+        let f64_ty = wit("f64");
+        assert_eq!(
+            to_val(&Value::Int(1 << 53), &f64_ty).unwrap(),
+            Val::Float64(9_007_199_254_740_992.0)
+        );
+        assert!(to_val(&Value::Int(i64::MAX), &f64_ty).is_err());
+    }
+
+    #[test]
+    fn mismatches_report_where_they_are() {
+        let error = to_val(
+            &object([("x", Value::Int(1)), ("y", Value::from("2"))]),
+            &wit("$point"),
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "expected s32, found string at `.y`");
+
+        let error = to_val(&object([("x", Value::Int(1))]), &wit("$point")).unwrap_err();
+        assert_eq!(error.to_string(), "expected s32, found nothing at `.y`");
+
+        assert!(to_val(&Value::Int(256), &wit("u8")).is_err());
+        assert!(to_val(&Value::from("blue"), &wit("$color")).is_err());
+    }
+
+    #[test]
+    fn u64_above_i64_max_has_no_plugin_value() {
+        assert!(from_val(Val::U64(u64::MAX), &wit("u64")).is_err());
+    }
+
+    #[test]
+    fn signatures_match_by_arity_and_kind() {
+        let add = func(r#"(param "a" s32) (param "b" s32) (result s32)"#);
+        assert_eq!(signature(&add), "func(a: s32, b: s32) -> s32");
+
+        let mut ints = Signature::new(ValueKind::Int);
+        ints.push_param(ValueKind::Int);
+        ints.push_param(ValueKind::Int);
+        assert!(matches_signature(&add, &ints));
+
+        let mut strings = Signature::new(ValueKind::Int);
+        strings.push_param(ValueKind::Int);
+        strings.push_param(ValueKind::String);
+        assert!(!matches_signature(&add, &strings));
+
+        let mut unary = Signature::new(ValueKind::Int);
+        unary.push_param(ValueKind::Int);
+        assert!(!matches_signature(&add, &unary));
+
+        let mut variadic = Signature::new(ValueKind::Int);
+        variadic.push_rest(ValueKind::Int);
+        assert!(matches_signature(&add, &variadic));
+
+        let mut returns_string = Signature::new(ValueKind::String);
+        returns_string.push_param(ValueKind::Int);
+        returns_string.push_param(ValueKind::Int);
+        assert!(!matches_signature(&add, &returns_string));
+    }
+
+    #[test]
+    fn fallible_imports_match_on_their_ok_payload() {
+        let div = func(r#"(param "a" s32) (param "b" s32) (result (result s32 (error string)))"#);
+        let mut ints = Signature::new(ValueKind::Int);
+        ints.push_param(ValueKind::Int);
+        ints.push_param(ValueKind::Int);
+        assert!(matches_signature(&div, &ints));
+
+        let mut strings = Signature::new(ValueKind::String);
+        strings.push_param(ValueKind::Int);
+        strings.push_param(ValueKind::Int);
+        assert!(!matches_signature(&div, &strings));
+    }
+}
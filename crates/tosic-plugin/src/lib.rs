@@ -1,14 +1,65 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+//! The main entry point for the tosic-plugin system.
+//!
+//! This crate re-exports everything from [`tosic_plugin_core`] together with the
+//! built-in runtime implementations, each behind its own cargo feature.
+//!
+//! # Features
+//!
+//! - **async**: Add the `AsyncRuntime` trait alongside `Runtime`, implemented by every enabled runtime
+//! - **serde**: Convert serde types to and from `Value` with `to_value`, `from_value` and `Serde<T>`
+//! - **derive**: `FromValue` and `IntoValue` derive macros and the `host_function` attribute;
+//!   annotate types with `#[value(crate = "tosic_plugin")]` and functions with
+//!   `#[host_function(crate = "tosic_plugin")]` when depending on this crate alone
+//! - **wasmtime**: WebAssembly runtime backed by Wasmtime, available as [`wasmtime`]
+//! - **component-model**: WebAssembly component runtime with WIT type mapping, available as `wasmtime::ComponentRuntime`
+//! - **wasmi**: Interpreter-based WebAssembly runtime for targets without JIT, available as [`wasmi`]
+//! - **lua**: Lua 5.4 runtime backed by mlua, available as [`lua`]
+//! - **js**: JavaScript runtime backed by the embedded QuickJS engine, available as [`js`]
+//! - **rhai**: Pure Rust Rhai scripting runtime, available as [`rhai`]
+//! - **native**: Native shared library runtime with a versioned C ABI, available as [`native`]
+//! - **process**: Out-of-process runtime talking JSON-RPC over stdio, available as [`process`]
+
+// Strict linting for release builds
+#![cfg_attr(not(debug_assertions), deny(missing_docs))]
+#![cfg_attr(not(debug_assertions), deny(clippy::all))]
+#![cfg_attr(not(debug_assertions), deny(unused))]
+
+pub use tosic_plugin_core::*;
+
+#[cfg(feature = "wasmtime")]
+pub use tosic_plugin_wasmtime as wasmtime;
+
+#[cfg(feature = "wasmi")]
+pub use tosic_plugin_wasmi as wasmi;
+
+#[cfg(feature = "lua")]
+pub use tosic_plugin_lua as lua;
+
+#[cfg(feature = "js")]
+pub use tosic_plugin_js as js;
+
+#[cfg(feature = "rhai")]
+pub use tosic_plugin_rhai as rhai;
+
+#[cfg(feature = "native")]
+pub use tosic_plugin_native as native;
+
+#[cfg(feature = "process")]
+pub use tosic_plugin_process as process;
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+    fn reexports_core() {
+        let mut context = HostContext::new();
+        context.register("add", |a: i64, b: i64| a + b);
+        assert_eq!(
+            context
+                .call_function("add", &[Value::Int(2), Value::Int(2)])
+                .unwrap(),
+            Value::Int(4)
+        );
     }
 }